@@ -32,11 +32,9 @@ async fn main() {
     let mut bot_name = String::new();
 
     // 接收 bot_name
-    if let Some(Ok(msg)) = read.next().await {
-        if let Message::Text(name) = msg {
-            println!("Connected to {}", name);
-            bot_name = name.to_string();
-        }
+    if let Some(Ok(Message::Text(name))) = read.next().await {
+        println!("Connected to {}", name);
+        bot_name = name.to_string();
     }
 
     // 创建一个 watch 用于通知退出
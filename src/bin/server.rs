@@ -1,14 +1,21 @@
 /// 基于 tokio-tungstenite 的 WebSocket 服务端
 /// 用法：cargo run --bin server <config file> <host> <port>
 /// 默认监听地址为 127.0.0.1:3000
+/// 客户端在握手时请求 sakiko.json 子协议时，消息以 JSON 形式发送（包含结构化元素），否则发送纯文本
 use futures_util::{SinkExt, StreamExt};
 use log::{error, info, debug};
 use sakiko::{load_config, Session};
 use std::env;
 use tokio::net::TcpListener;
-use tokio_tungstenite::accept_async;
+use tokio_tungstenite::accept_hdr_async;
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::http::header::SEC_WEBSOCKET_PROTOCOL;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
 use tokio_tungstenite::tungstenite::protocol::Message;
 
+/// JSON 消息的子协议名
+const JSON_PROTOCOL: &str = "sakiko.json";
+
 #[tokio::main]
 async fn main() {
     let args: Vec<String> = env::args().collect();
@@ -34,11 +41,29 @@ async fn main() {
 
     // 为每个连接创建一个新的会话
     while let Ok((stream, cilent)) = listener.accept().await {
-        info!("New client: {}", cilent);
         let mut session = Session::new(config.clone());
+        info!("New client: {} (seed {})", cilent, session.get_seed());
         tokio::spawn(async move {
-            // 创建 WebSocket 连接
-            let ws_stream = match accept_async(stream).await {
+            // 创建 WebSocket 连接，协商消息格式
+            let mut json = false;
+            // 错误类型由 tungstenite 决定
+            #[allow(clippy::result_large_err)]
+            let negotiate = |request: &Request, mut response: Response| {
+                json = request
+                    .headers()
+                    .get_all(SEC_WEBSOCKET_PROTOCOL)
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .flat_map(|value| value.split(','))
+                    .any(|protocol| protocol.trim() == JSON_PROTOCOL);
+                if json {
+                    response
+                        .headers_mut()
+                        .insert(SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static(JSON_PROTOCOL));
+                }
+                Ok(response)
+            };
+            let ws_stream = match accept_hdr_async(stream, negotiate).await {
                 Ok(ws_stream) => ws_stream,
                 Err(err) => {
                     error!("Error during WebSocket handshake: {}", err);
@@ -65,7 +90,12 @@ async fn main() {
                         break;
                     }
                 }
-                let msg = match session.output() {
+                let output = if json {
+                    session.message().map(|message| message.to_json())
+                } else {
+                    session.output()
+                };
+                let msg = match output {
                     Ok(output) => Message::Text(output.into()),
                     Err(err) => {
                         error!("Error: {}", err);
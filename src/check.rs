@@ -1,5 +1,4 @@
 /// 实现配置的检测
-
 use crate::config::SakikoConfig;
 
 /// 检测 config 是否合法
@@ -34,6 +33,16 @@ pub fn check_config(config: &SakikoConfig) -> Result<(), String> {
         result.push("Operation check failed".to_string());
         result.push(e);
     }
+    // 检测意图和转移的匹配方式是否正常
+    if let Err(e) = config.check_matching() {
+        result.push("Matching check failed".to_string());
+        result.push(e);
+    }
+    // 检测营业日历是否正常
+    if let Err(e) = config.get_calendar().check() {
+        result.push("Calendar check failed".to_string());
+        result.push(e);
+    }
     // 检查结果
     if !result.is_empty() {
         return Err(result.join("\n"));
@@ -49,6 +58,6 @@ mod tests {
     fn test_check_config() {
         let config = SakikoConfig::deserialize_from_file("demo/error.yaml").unwrap();
         assert!(check_config(&config).is_err());
-        print!("{}\n", check_config(&config).unwrap_err());
+        println!("{}", check_config(&config).unwrap_err());
     }
 }
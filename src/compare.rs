@@ -2,10 +2,12 @@
 /// 比较类包含了一个比较类型和一个比较值，比较类型包含了等于、不等于、大于、大于等于、小于、小于等于，比较值包含了两个变量名。
 /// 支持的类型有整数、浮点数和字符串，比较时会检查类型是否匹配。
 /// 计算时支持与和或
+/// 日期时间还支持判断是否处于营业时间
 use std::ops::Not;
 
 use ::serde::{Deserialize, Serialize};
 
+use crate::time::Calendar;
 pub use crate::variable::VariableType;
 pub use crate::variable::Variables;
 
@@ -18,6 +20,8 @@ pub enum CompareType {
     Ge(String, String), // Greater or Equal
     Lt(String, String), // Less Than
     Le(String, String), // Less or Equal
+    Open(String),       // In opening hours
+    Closed(String),     // Not in opening hours
 }
 
 /// 比较
@@ -42,6 +46,12 @@ impl CompareType {
     /// 检查比较类型
     pub fn compare_check(&self, variable: &Variables) -> Result<(), String> {
         match self {
+            CompareType::Open(a) | CompareType::Closed(a) => {
+                match variable.get(a).ok_or_else(|| format!("Variable {} not found", a))? {
+                    VariableType::DateTime(_) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
             CompareType::Eq(a, b)
             | CompareType::Ne(a, b)
             | CompareType::Gt(a, b)
@@ -59,9 +69,16 @@ impl CompareType {
         }
     }
 
-    /// 计算比较
-    pub fn compare(&self, variable: &Variables) -> Result<bool, String> {
+    /// 计算比较，营业时间由日历决定
+    pub fn compare(&self, variable: &Variables, calendar: &Calendar) -> Result<bool, String> {
         let (a, b) = match self {
+            CompareType::Open(a) | CompareType::Closed(a) => {
+                let open = match variable.get(a).ok_or_else(|| format!("Variable {} not found", a))? {
+                    VariableType::DateTime(a) => calendar.is_open(*a)?,
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                return Ok(open == matches!(self, CompareType::Open(_)));
+            }
             CompareType::Eq(a, b)
             | CompareType::Ne(a, b)
             | CompareType::Gt(a, b)
@@ -95,6 +112,20 @@ impl CompareType {
             (CompareType::Ge(_, _), VariableType::Str(a), VariableType::Str(b)) => Ok(a >= b),
             (CompareType::Lt(_, _), VariableType::Str(a), VariableType::Str(b)) => Ok(a < b),
             (CompareType::Le(_, _), VariableType::Str(a), VariableType::Str(b)) => Ok(a <= b),
+
+            (CompareType::Eq(_, _), VariableType::DateTime(a), VariableType::DateTime(b)) => Ok(a == b),
+            (CompareType::Ne(_, _), VariableType::DateTime(a), VariableType::DateTime(b)) => Ok(a != b),
+            (CompareType::Gt(_, _), VariableType::DateTime(a), VariableType::DateTime(b)) => Ok(a > b),
+            (CompareType::Ge(_, _), VariableType::DateTime(a), VariableType::DateTime(b)) => Ok(a >= b),
+            (CompareType::Lt(_, _), VariableType::DateTime(a), VariableType::DateTime(b)) => Ok(a < b),
+            (CompareType::Le(_, _), VariableType::DateTime(a), VariableType::DateTime(b)) => Ok(a <= b),
+
+            (CompareType::Eq(_, _), VariableType::Duration(a), VariableType::Duration(b)) => Ok(a == b),
+            (CompareType::Ne(_, _), VariableType::Duration(a), VariableType::Duration(b)) => Ok(a != b),
+            (CompareType::Gt(_, _), VariableType::Duration(a), VariableType::Duration(b)) => Ok(a > b),
+            (CompareType::Ge(_, _), VariableType::Duration(a), VariableType::Duration(b)) => Ok(a >= b),
+            (CompareType::Lt(_, _), VariableType::Duration(a), VariableType::Duration(b)) => Ok(a < b),
+            (CompareType::Le(_, _), VariableType::Duration(a), VariableType::Duration(b)) => Ok(a <= b),
     
             _ => Err("Type mismatch".to_string()),
         }
@@ -132,13 +163,13 @@ impl Compares {
     }
 
     /// 计算比较
-    pub fn calc(&self, variable: &Variables) -> Result<bool, String> {
+    pub fn calc(&self, variable: &Variables, calendar: &Calendar) -> Result<bool, String> {
         self.0
             .iter()
             .try_fold(None, |acc, compare_item| {
                 let current_result = match compare_item.compare {
                     Compare::And | Compare::Or => {
-                        compare_item.compare_type.compare(variable)?
+                        compare_item.compare_type.compare(variable, calendar)?
                     }
                 };
                 Ok(Some(match (acc, compare_item.compare) {
@@ -190,6 +221,6 @@ mod tests {
             compare: Compare::And,
             compare_type: CompareType::Ne("int1".to_string(), "int3".to_string()),
         });
-        assert_eq!(compares.calc(&variables).unwrap(), true);
+        assert!(compares.calc(&variables, &Calendar::default()).unwrap());
     }
 }
@@ -1,6 +1,5 @@
 /// Sakiko 的配置类
 /// 包含配置的数据结构定义，序列化和反序列化方法
-
 use std::ops::Not;
 
 use ::serde::{Deserialize, Serialize};
@@ -8,28 +7,240 @@ use ::serde_yaml;
 use ::std::collections::HashMap;
 
 use crate::compare::Compares;
+use crate::extract::{Extractions, Extractors};
+use crate::faq::Faq;
+use crate::form::Form;
+use crate::intent::Intents;
+use crate::normalize::{self, Normalizer};
 use crate::operation::Operations;
+use crate::segment::Dictionary;
+use crate::sensitive::SensitiveFilter;
+use crate::sentiment::Sentiment;
 use crate::variable::{VariableType, Variables};
-use crate::output::Output;
+use crate::message::Element;
+use crate::output::{Label, Output, Policy, Variant};
+use crate::time::Calendar;
 
 /// 转移类
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct Transaction {
     #[serde(default = "String::new")]
     pub pattern: String, // 匹配字符串（可置空）
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    pub fuzzy: Option<f64>, // 模糊匹配的相似度阈值，声明时按编辑距离匹配 pattern（可置空）
+    #[serde(default = "bool::default", skip_serializing_if = "std::ops::Not::not")]
+    pub pinyin: bool, // 拼音匹配，开启时 pattern 也与其全拼和首字母匹配（可置空）
     #[serde(default = "Compares::new")]
     pub compares: Compares, // 比较条件（可置空）
+    #[serde(default = "String::new", skip_serializing_if = "String::is_empty")]
+    pub intent: String, // 匹配意图（可置空）
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>, // 意图的置信度阈值，默认为 0.5（可置空）
+    #[serde(default = "Extractions::new", skip_serializing_if = "Extractions::is_empty")]
+    pub extract: Extractions, // 提取规则，声明时输入中必须能提取到所有实体（可置空）
+    #[serde(default = "String::new", skip_serializing_if = "String::is_empty")]
+    pub label: String, // 澄清时显示的名称，默认为意图名或下一步（可置空）
     pub step: String, // 下一步（必须）
     #[serde(default = "Operations::new")]
     pub operation: Operations, // 操作（可置空）
 }
 
+impl Transaction {
+    /// 是否不需要用户输入（未声明匹配字符串、意图和提取规则）
+    pub fn is_empty(&self) -> bool {
+        self.pattern.is_empty() && self.intent.is_empty() && self.extract.is_empty()
+    }
+
+    /// 意图的置信度阈值
+    pub fn confidence(&self) -> f64 {
+        self.confidence.unwrap_or(0.5)
+    }
+
+    /// 澄清时显示的名称
+    pub fn label(&self) -> &str {
+        if !self.label.is_empty() {
+            &self.label
+        } else if !self.intent.is_empty() {
+            &self.intent
+        } else {
+            &self.step
+        }
+    }
+}
+
+/// 带序号的选项列表，每行一个
+pub fn numbered<'a>(labels: impl IntoIterator<Item = &'a str>) -> String {
+    labels
+        .into_iter()
+        .enumerate()
+        .map(|(i, label)| format!("{}. {}", i + 1, label))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// 根据输入选择选项，依次尝试序号、完整选项名和唯一的选项名前缀（忽略大小写）
+pub fn choose_label(labels: &[&str], input: &str) -> Option<usize> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+    if let Ok(number) = input.parse::<usize>() {
+        return number.checked_sub(1).filter(|&i| i < labels.len());
+    }
+    let input = input.to_lowercase();
+    if let Some(i) = labels
+        .iter()
+        .position(|label| label.to_lowercase() == input)
+    {
+        return Some(i);
+    }
+    let mut prefixed = labels
+        .iter()
+        .enumerate()
+        .filter(|(_, label)| label.to_lowercase().starts_with(&input));
+    match (prefixed.next(), prefixed.next()) {
+        (Some((i, _)), None) => Some(i),
+        _ => None,
+    }
+}
+
+/// 澄清设置，多个转移的得分接近时询问用户
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Clarification {
+    #[serde(default = "Clarification::default_margin")]
+    pub margin: f64, // 与最高得分相差不超过该值的转移视为接近，默认为 0.1（可置空）
+    #[serde(default = "Clarification::default_prompt")]
+    pub prompt: Output, // 询问的提示（可置空）
+}
+
+impl Clarification {
+    // 默认的接近范围
+    fn default_margin() -> f64 {
+        0.1
+    }
+
+    // 默认的询问提示
+    fn default_prompt() -> Output {
+        Output::new("您是想要：".to_string(), Vec::new())
+    }
+
+    /// 检查设置（检测用，会报告所有错误）
+    pub fn check(&self, variables: &Variables) -> Result<(), String> {
+        let mut errors = Vec::new();
+        if (0.0..1.0).contains(&self.margin).not() {
+            errors.push(format!("Invalid margin: {}", self.margin));
+        }
+        if let Err(err) = self.prompt.check(variables) {
+            errors.push(format!("Prompt failed: {}", err));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+}
+
+/// 菜单选项类
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct MenuOption {
+    pub label: String, // 选项名（必须）
+    pub step: String,  // 下一步（必须）
+    #[serde(default = "Operations::new")]
+    pub operation: Operations, // 操作（可置空）
+}
+
+/// 菜单类
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Menu {
+    pub options: Vec<MenuOption>, // 选项（必须）
+    #[serde(default = "Menu::default_invalid")]
+    pub invalid: Output, // 输入无效时的提示（可置空）
+}
+
+impl Menu {
+    // 默认的无效输入提示
+    fn default_invalid() -> Output {
+        Output::new("无效的选项，请重新选择。".to_string(), Vec::new())
+    }
+
+    /// 带序号的选项列表，每行一个
+    pub fn prompt(&self) -> String {
+        numbered(self.options.iter().map(|option| option.label.as_str()))
+    }
+
+    /// 根据输入选择选项，依次尝试序号、完整选项名和唯一的选项名前缀（忽略大小写）
+    pub fn choose(&self, input: &str) -> Option<&MenuOption> {
+        let labels: Vec<&str> = self.options.iter().map(|option| option.label.as_str()).collect();
+        choose_label(&labels, input).map(|i| &self.options[i])
+    }
+
+    /// 检查选项（检测用，会报告所有错误）
+    pub fn check(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        if self.options.is_empty() {
+            errors.push("Menu has no option".to_string());
+        }
+        for (i, option) in self.options.iter().enumerate() {
+            if option.label.trim().is_empty() || option.label.trim().parse::<usize>().is_ok() {
+                errors.push(format!("Invalid menu label: {}", option.label));
+            }
+            let label = option.label.to_lowercase();
+            if self.options[..i]
+                .iter()
+                .any(|other| other.label.to_lowercase() == label)
+            {
+                errors.push(format!("Duplicate menu label: {}", option.label));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+}
+
 /// 步骤类
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct Step {
     #[serde(default = "Output::default")]
     pub description: Output, // 给用户的输出（可置空）
-    pub transaction: Vec<Transaction>, // 转移
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<Variant>, // 候选输出，与 description 二选一（可置空）
+    #[serde(default = "Policy::default", skip_serializing_if = "Policy::is_default")]
+    pub policy: Policy, // 候选输出的选取策略（可置空）
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub elements: Vec<Element<Label>>, // 结构化元素，如快捷回复、链接、图片和卡片（可置空）
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    pub menu: Option<Menu>, // 菜单（可置空）
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    pub form: Option<Form>, // 表单（可置空）
+    #[serde(default = "Vec::new")]
+    pub transaction: Vec<Transaction>, // 转移（菜单步骤可置空）
+}
+
+impl Step {
+    /// 判断是否没有输出
+    pub fn is_silent(&self) -> bool {
+        self.description.is_empty()
+            && self.variants.is_empty()
+            && self.elements.is_empty()
+            && self.menu.is_none()
+            && self.form.is_none()
+    }
+
+    /// 获取输出，有候选输出时按序号选取
+    pub fn get_description(&self, index: usize) -> &Output {
+        self.variants
+            .get(index)
+            .map_or(&self.description, |variant| &variant.description)
+    }
+
+    /// 各候选输出的权重
+    pub fn weights(&self) -> Vec<u32> {
+        self.variants.iter().map(|variant| variant.weight).collect()
+    }
 }
 
 /// Sakiko 配置类
@@ -40,6 +251,24 @@ pub struct SakikoConfig {
     #[serde(default = "Variables::new")]
     variables: Variables, // 变量（可置空）
     steps: HashMap<String, Step>, // 步骤
+    #[serde(default = "Calendar::default", skip_serializing_if = "Calendar::is_empty")]
+    calendar: Calendar, // 营业日历（可置空）
+    #[serde(default = "Intents::default", skip_serializing_if = "Intents::is_empty")]
+    intents: Intents, // 意图（可置空）
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    clarification: Option<Clarification>, // 澄清设置，不声明时按顺序选取第一个匹配的转移（可置空）
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    normalize: Vec<Normalizer>, // 匹配前对输入的规范化步骤，按顺序执行（可置空）
+    #[serde(default = "Dictionary::default", skip_serializing_if = "Dictionary::is_empty")]
+    dictionary: Dictionary, // 用户词典，用于分词（可置空）
+    #[serde(default = "Extractors::new", skip_serializing_if = "Extractors::is_empty")]
+    extractors: Extractors, // 自定义提取器（可置空）
+    #[serde(default = "SensitiveFilter::default", skip_serializing_if = "SensitiveFilter::is_empty")]
+    sensitive: SensitiveFilter, // 敏感词过滤（可置空）
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    sentiment: Option<Sentiment>, // 情感分析，不声明时不打分（可置空）
+    #[serde(default = "Faq::default", skip_serializing_if = "Faq::is_empty")]
+    faq: Faq, // 知识库（可置空）
 }
 
 impl SakikoConfig {
@@ -50,6 +279,15 @@ impl SakikoConfig {
             start_step: start_step.to_string(),
             variables: Variables::new(),
             steps: HashMap::new(),
+            calendar: Calendar::default(),
+            intents: Intents::default(),
+            clarification: None,
+            normalize: Vec::new(),
+            dictionary: Dictionary::default(),
+            extractors: Extractors::new(),
+            sensitive: SensitiveFilter::default(),
+            sentiment: None,
+            faq: Faq::default(),
         }
     }
 
@@ -65,12 +303,30 @@ impl SakikoConfig {
 
     /// 从字符串反序列化
     pub fn deserialize(yaml: &str) -> Result<SakikoConfig, serde_yaml::Error> {
-        serde_yaml::from_str(yaml)
+        serde_yaml::from_str(yaml).map(SakikoConfig::prepare)
     }
 
     /// 从文件反序列化
     pub fn deserialize_from_file(file_path: &str) -> Result<SakikoConfig, serde_yaml::Error> {
-        serde_yaml::from_reader(std::fs::File::open(file_path).unwrap())
+        serde_yaml::from_reader(std::fs::File::open(file_path).unwrap()).map(SakikoConfig::prepare)
+    }
+
+    // 加载后按用户词典重建意图和知识库索引，声明敏感词过滤和情感分析时添加保留变量 sensitive 和 sentiment
+    fn prepare(mut self) -> SakikoConfig {
+        if self.dictionary.is_empty().not() {
+            let segmenter = self.dictionary.segmenter().clone();
+            self.intents.reindex(segmenter.clone());
+            self.faq.reindex(segmenter);
+        }
+        if self.sensitive.is_empty().not() && self.variables.get("sensitive").is_none() {
+            self.variables
+                .insert("sensitive".to_string(), VariableType::Int(0));
+        }
+        if self.sentiment.is_some() && self.variables.get("sentiment").is_none() {
+            self.variables
+                .insert("sentiment".to_string(), VariableType::Float(0.0));
+        }
+        self
     }
 
     /// 添加变量（测试用）
@@ -85,6 +341,11 @@ impl SakikoConfig {
             step_name.to_string(),
             Step {
                 description: description.clone(),
+                variants: Vec::new(),
+                policy: Policy::default(),
+                elements: Vec::new(),
+                menu: None,
+                form: None,
                 transaction: Vec::new(),
             },
         );
@@ -105,7 +366,13 @@ impl SakikoConfig {
             .transaction
             .push(Transaction {
                 pattern: pattern.to_string(),
+                fuzzy: None,
+                pinyin: false,
                 compares: compares.clone(),
+                intent: String::new(),
+                confidence: None,
+                extract: Extractions::new(),
+                label: String::new(),
                 step: next_step.to_string(),
                 operation: operation.clone(),
             });
@@ -126,6 +393,56 @@ impl SakikoConfig {
         &self.variables
     }
 
+    /// 获取营业日历
+    pub fn get_calendar(&self) -> &Calendar {
+        &self.calendar
+    }
+
+    /// 设置营业日历（测试用）
+    pub fn set_calendar(&mut self, calendar: Calendar) {
+        self.calendar = calendar;
+    }
+
+    /// 获取意图
+    pub fn get_intents(&self) -> &Intents {
+        &self.intents
+    }
+
+    /// 获取提取器
+    pub fn get_extractors(&self) -> &Extractors {
+        &self.extractors
+    }
+
+    /// 获取用户词典
+    pub fn get_dictionary(&self) -> &Dictionary {
+        &self.dictionary
+    }
+
+    /// 获取敏感词过滤器
+    pub fn get_sensitive(&self) -> &SensitiveFilter {
+        &self.sensitive
+    }
+
+    /// 获取情感分析器
+    pub fn get_sentiment(&self) -> Option<&Sentiment> {
+        self.sentiment.as_ref()
+    }
+
+    /// 获取知识库
+    pub fn get_faq(&self) -> &Faq {
+        &self.faq
+    }
+
+    /// 获取澄清设置
+    pub fn get_clarification(&self) -> Option<&Clarification> {
+        self.clarification.as_ref()
+    }
+
+    /// 规范化输入
+    pub fn normalize(&self, input: &str) -> String {
+        normalize::normalize(&self.normalize, input)
+    }
+
     /// 获取步骤
     pub fn get_step(&self, step_name: &str) -> Option<&Step> {
         self.steps.get(step_name)
@@ -153,6 +470,35 @@ impl SakikoConfig {
                     ));
                 }
             }
+            for option in step.menu.iter().flat_map(|menu| &menu.options) {
+                if self.steps.contains_key(&option.step).not() && option.step != "end" {
+                    errors.push(format!(
+                        "Step {} in menu of step {} not found",
+                        option.step, step_name
+                    ));
+                }
+            }
+            if let Some(form) = &step.form {
+                for next in std::iter::once(&form.step).chain(&form.fail) {
+                    if self.steps.contains_key(next).not() && next != "end" {
+                        errors.push(format!(
+                            "Step {} in form of step {} not found",
+                            next, step_name
+                        ));
+                    }
+                }
+            }
+        }
+
+        let step = self.sensitive.step();
+        if step.is_empty().not() && self.steps.contains_key(step).not() && step != "end" {
+            errors.push(format!("Step {} in sensitive words not found", step));
+        }
+        if let Some(sentiment) = &self.sentiment {
+            let step = sentiment.step();
+            if step.is_empty().not() && self.steps.contains_key(step).not() && step != "end" {
+                errors.push(format!("Step {} in sentiment not found", step));
+            }
         }
 
         if errors.is_empty() {
@@ -162,6 +508,117 @@ impl SakikoConfig {
         }
     }
 
+    /// 检测意图、澄清设置、规范化步骤、敏感词、情感分析、知识库和转移的匹配方式
+    pub fn check_matching(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+
+        if let Err(err) = self.intents.check() {
+            errors.push(err);
+        }
+        if let Err(err) = self.dictionary.check() {
+            errors.push(format!("Dictionary failed: {}", err));
+        }
+        if let Err(err) = self.extractors.check() {
+            errors.push(format!("Extractors failed: {}", err));
+        }
+        if let Err(err) = self.sensitive.check() {
+            errors.push(format!("Sensitive words failed: {}", err));
+        }
+        if self.sensitive.is_empty().not()
+            && matches!(self.variables.get("sensitive"), Some(VariableType::Int(_))).not()
+        {
+            errors.push("Variable sensitive is not Int".to_string());
+        }
+        if let Some(sentiment) = &self.sentiment {
+            if let Err(err) = sentiment.check() {
+                errors.push(format!("Sentiment failed: {}", err));
+            }
+            if matches!(self.variables.get("sentiment"), Some(VariableType::Float(_))).not() {
+                errors.push("Variable sentiment is not Float".to_string());
+            }
+        }
+        if let Err(err) = self.faq.check() {
+            errors.push(format!("FAQ failed: {}", err));
+        }
+        if let Some(clarification) = &self.clarification {
+            if let Err(err) = clarification.check(&self.variables) {
+                errors.push(format!("Clarification failed: {}", err));
+            }
+        }
+        for normalizer in &self.normalize {
+            if let Err(err) = normalizer.check() {
+                errors.push(format!("Normalizer failed: {}", err));
+            }
+        }
+        for (step_name, step) in &self.steps {
+            for transaction in &step.transaction {
+                if let Err(err) = self
+                    .extractors
+                    .check_extractions(&transaction.extract, &self.variables)
+                {
+                    errors.push(format!(
+                        "Extraction in transaction of step {} failed: {}",
+                        step_name, err
+                    ));
+                }
+                if let Some(fuzzy) = transaction.fuzzy {
+                    if transaction.pattern.is_empty() {
+                        errors.push(format!(
+                            "Fuzzy without pattern in transaction of step {}",
+                            step_name
+                        ));
+                    }
+                    if (fuzzy > 0.0 && fuzzy <= 1.0).not() {
+                        errors.push(format!(
+                            "Invalid fuzzy threshold {} in transaction of step {}",
+                            fuzzy, step_name
+                        ));
+                    }
+                }
+                if transaction.pinyin && transaction.pattern.is_empty() {
+                    errors.push(format!(
+                        "Pinyin without pattern in transaction of step {}",
+                        step_name
+                    ));
+                }
+                if transaction.pinyin && transaction.fuzzy.is_some() {
+                    errors.push(format!(
+                        "Both fuzzy and pinyin in transaction of step {}",
+                        step_name
+                    ));
+                }
+                if transaction.intent.is_empty() {
+                    if transaction.confidence.is_some() {
+                        errors.push(format!(
+                            "Confidence without intent in transaction of step {}",
+                            step_name
+                        ));
+                    }
+                    continue;
+                }
+                if self.intents.contains(&transaction.intent).not() {
+                    errors.push(format!(
+                        "Intent {} in transaction of step {} not found",
+                        transaction.intent, step_name
+                    ));
+                }
+                let confidence = transaction.confidence();
+                if (confidence > 0.0 && confidence <= 1.0).not() {
+                    errors.push(format!(
+                        "Invalid confidence {} in transaction of step {}",
+                        confidence, step_name
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
     /// 检测比较
     pub fn check_compares(&self) -> Result<(), String> {
         let mut errors = Vec::new();
@@ -192,6 +649,49 @@ impl SakikoConfig {
             if let Err(err) = step.description.check(&self.variables) {
                 errors.push(format!("Description of step {} failed: {}", step_name, err));
             }
+            if let Some(menu) = &step.menu {
+                if let Err(err) = menu.check() {
+                    errors.push(format!("Menu of step {} failed: {}", step_name, err));
+                }
+                if let Err(err) = menu.invalid.check(&self.variables) {
+                    errors.push(format!("Invalid prompt of step {} failed: {}", step_name, err));
+                }
+            }
+            if let Some(form) = &step.form {
+                if let Err(err) = form.check(&self.variables) {
+                    errors.push(format!("Form of step {} failed: {}", step_name, err));
+                }
+                if step.menu.is_some() {
+                    errors.push(format!("Step {} has both menu and form", step_name));
+                }
+            }
+            for (i, element) in step.elements.iter().enumerate() {
+                if let Err(err) = element.check(&self.variables) {
+                    errors.push(format!("Element {} of step {} failed: {}", i, step_name, err));
+                }
+            }
+            if step.variants.is_empty() {
+                continue;
+            }
+            if step.description.is_empty().not() {
+                errors.push(format!(
+                    "Step {} has both description and variants",
+                    step_name
+                ));
+            }
+            if step.variants.iter().all(|variant| variant.weight == 0) {
+                errors.push(format!("Variants of step {} have no positive weight", step_name));
+            }
+            for (i, variant) in step.variants.iter().enumerate() {
+                if variant.description.is_empty() {
+                    errors.push(format!("Variant {} of step {} is empty", i, step_name));
+                } else if let Err(err) = variant.description.check(&self.variables) {
+                    errors.push(format!(
+                        "Variant {} of step {} failed: {}",
+                        i, step_name, err
+                    ));
+                }
+            }
         }
 
         if errors.is_empty() {
@@ -214,6 +714,22 @@ impl SakikoConfig {
                     ));
                 }
             }
+            if let Some(form) = &step.form {
+                if let Err(err) = form.operation.check(&self.variables) {
+                    errors.push(format!(
+                        "Operation in form of step {} failed: {}",
+                        step_name, err
+                    ));
+                }
+            }
+            for option in step.menu.iter().flat_map(|menu| &menu.options) {
+                if let Err(err) = option.operation.check(&self.variables) {
+                    errors.push(format!(
+                        "Operation in menu option {} of step {} failed: {}",
+                        option.label, step_name, err
+                    ));
+                }
+            }
         }
 
         if errors.is_empty() {
@@ -248,9 +764,9 @@ mod tests {
         str_dic.insert("key".to_string(), "value".to_string());
         str_dic.insert("key2".to_string(), "value2".to_string());
         config.add_variable("str_dic", VariableType::StrDic(str_dic));
-        let mut output = Output("Start of the conversation".to_string(), Vec::new());
+        let mut output = Output::new("Start of the conversation".to_string(), Vec::new());
         config.add_step("start", &output);
-        output = Output("End of the conversation {}".to_string(), vec!["int1".to_string()]);
+        output = Output::new("End of the conversation {}".to_string(), vec!["int1".to_string()]);
         config.add_step("end", &output);
         let mut compares = Compares::new();
         compares.add(CompareItem {
@@ -262,12 +778,10 @@ mod tests {
             "goodbye",
             &compares,
             "end",
-            &Operations {
-                0: vec![
-                    Operation::Let("int".to_string(), VariableType::Int(2)),
-                    Operation::Add("int1".to_string(), "int1".to_string(), "int2".to_string()),
-                ],
-            },
+            &Operations(vec![
+                Operation::Let("int".to_string(), VariableType::Int(2)),
+                Operation::Add("int1".to_string(), "int1".to_string(), "int2".to_string()),
+            ]),
         );
         config
     }
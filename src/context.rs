@@ -0,0 +1,24 @@
+/// 运行时上下文
+/// 执行操作时需要的变量表以外的会话状态，包括随机数生成器、时钟、营业日历、分词器和知识库
+use ::rand_chacha::ChaCha8Rng;
+use ::std::sync::Arc;
+
+use crate::faq::Faq;
+use crate::segment::Segmenter;
+use crate::time::{Calendar, Clock, DateTime};
+
+/// 运行时上下文
+pub struct Context<'a> {
+    pub rng: &'a mut ChaCha8Rng,
+    pub clock: &'a dyn Clock,
+    pub calendar: &'a Calendar,
+    pub segmenter: Arc<Segmenter>,
+    pub faq: &'a Faq,
+}
+
+impl Context<'_> {
+    /// 当前本地时间
+    pub fn now(&self) -> Result<DateTime, String> {
+        self.calendar.local(self.clock)
+    }
+}
@@ -0,0 +1,343 @@
+/// Sakiko 的实体提取
+/// 从用户输入的任意位置提取实体（如手机号、邮箱、身份证号、日期和订单号），规范化后存入变量。
+/// 内置提取器提取前会将全角字符转为半角，自定义提取器由正则表达式和规范化步骤组成。
+use ::regex::Regex;
+use ::serde::{Deserialize, Serialize};
+use ::std::collections::HashMap;
+use std::ops::Not;
+
+use crate::normalize::{self, to_half_width, Normalizer};
+use crate::time::DateTime;
+use crate::variable::{VariableType, Variables};
+
+// 内置提取器名
+const BUILTIN: [&str; 5] = ["phone", "email", "id_card", "date", "order_id"];
+
+/// 自定义提取器
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Extractor {
+    pub pattern: String, // 正则表达式，有捕获组时取第一个捕获组，否则取整个匹配（必须）
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub normalize: Vec<Normalizer>, // 对提取结果的规范化步骤（可置空）
+}
+
+/// 提取器集合（序列化为自定义提取器名到提取器的映射），内置提取器不需要声明
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Default)]
+pub struct Extractors(pub HashMap<String, Extractor>);
+
+/// 提取规则，写作 “提取器 -> 变量”
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(try_from = "String", into = "String")]
+pub struct Extraction {
+    pub extractor: String, // 提取器名
+    pub variable: String,  // 存入的变量
+}
+
+impl TryFrom<String> for Extraction {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let (extractor, variable) = s.split_once("->").ok_or_else(|| {
+            format!(
+                "Invalid extraction {:?}, expected \"extractor -> variable\"",
+                s
+            )
+        })?;
+        Ok(Extraction {
+            extractor: extractor.trim().to_string(),
+            variable: variable.trim().to_string(),
+        })
+    }
+}
+
+impl From<Extraction> for String {
+    fn from(extraction: Extraction) -> Self {
+        format!("{} -> {}", extraction.extractor, extraction.variable)
+    }
+}
+
+// 提取规则可以写成一条或一个列表
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExtractionList {
+    One(Extraction),
+    Many(Vec<Extraction>),
+}
+
+/// 转移的提取规则集合
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Default)]
+#[serde(from = "ExtractionList", into = "Vec<Extraction>")]
+pub struct Extractions(pub Vec<Extraction>);
+
+impl From<ExtractionList> for Extractions {
+    fn from(list: ExtractionList) -> Self {
+        match list {
+            ExtractionList::One(extraction) => Extractions(vec![extraction]),
+            ExtractionList::Many(extractions) => Extractions(extractions),
+        }
+    }
+}
+
+impl From<Extractions> for Vec<Extraction> {
+    fn from(extractions: Extractions) -> Self {
+        extractions.0
+    }
+}
+
+impl std::ops::Deref for Extractions {
+    type Target = Vec<Extraction>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Extractions {
+    /// 创建空的提取规则集合
+    pub fn new() -> Self {
+        Extractions(Vec::new())
+    }
+
+    /// 是否没有提取规则
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// 查找第一个前后不紧邻 boundary 字符的匹配，返回第一个捕获组（没有时为整个匹配）
+fn find_isolated<'a>(re: &Regex, text: &'a str, boundary: fn(char) -> bool) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    for captures in re.captures_iter(text) {
+        let whole = captures.get(0).unwrap();
+        let before = text[..whole.start()].chars().next_back();
+        let after = text[whole.end()..].chars().next();
+        if before.is_some_and(boundary) || after.is_some_and(boundary) {
+            continue;
+        }
+        found.push(captures.get(1).unwrap_or(whole).as_str());
+    }
+    found
+}
+
+// 身份证号的校验码是否正确
+fn check_id_card(id: &str) -> bool {
+    const WEIGHTS: [u32; 17] = [7, 9, 10, 5, 8, 4, 2, 1, 6, 3, 7, 9, 10, 5, 8, 4, 2];
+    const CODES: [char; 11] = ['1', '0', 'X', '9', '8', '7', '6', '5', '4', '3', '2'];
+    let sum: u32 = id
+        .chars()
+        .zip(WEIGHTS)
+        .map(|(c, w)| c.to_digit(10).unwrap_or(0) * w)
+        .sum();
+    id.chars().nth(17) == Some(CODES[(sum % 11) as usize])
+}
+
+// 内置提取器，返回规范化后的实体
+fn extract_builtin(name: &str, input: &str) -> Option<String> {
+    let text: String = input.chars().map(to_half_width).collect();
+    match name {
+        // 中国大陆手机号，允许 +86 前缀和空格、短横线分隔，规范化为 11 位数字
+        "phone" => {
+            let re = Regex::new(r"(?:\+?86[ -]?)?(1[3-9]\d(?:[ -]?\d{4}){2})").unwrap();
+            find_isolated(&re, &text, |c| c.is_ascii_digit())
+                .first()
+                .map(|phone| phone.chars().filter(char::is_ascii_digit).collect())
+        }
+        // 邮箱地址，规范化为小写
+        "email" => {
+            let re =
+                Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}")
+                    .unwrap();
+            re.find(&text).map(|m| m.as_str().to_lowercase())
+        }
+        // 18 位居民身份证号，校验码必须正确，规范化为大写
+        "id_card" => {
+            let re = Regex::new(r"\d{17}[\dXx]").unwrap();
+            find_isolated(&re, &text, |c| c.is_ascii_alphanumeric())
+                .into_iter()
+                .map(str::to_uppercase)
+                .find(|id| check_id_card(id))
+        }
+        // 日期，如 2024-05-01、2024/5/1、2024年5月1日，日期必须有效，规范化为 YYYY-MM-DD
+        "date" => {
+            let re = Regex::new(r"(\d{4})\s*[-/.年]\s*(\d{1,2})\s*[-/.月]\s*(\d{1,2})\s*[日号]?")
+                .unwrap();
+            let date = re.captures_iter(&text).find_map(|captures| {
+                let date = format!("{}-{:0>2}-{:0>2}", &captures[1], &captures[2], &captures[3]);
+                DateTime::parse(&date).map(|_| date)
+            });
+            date
+        }
+        // 订单号，为至多 4 个字母的前缀加 8 到 24 位数字，规范化为大写
+        "order_id" => {
+            let re = Regex::new(r"[A-Za-z]{0,4}\d{8,24}").unwrap();
+            find_isolated(&re, &text, |c| c.is_ascii_alphanumeric())
+                .first()
+                .map(|id| id.to_uppercase())
+        }
+        _ => None,
+    }
+}
+
+impl Extractors {
+    /// 创建空的提取器集合
+    pub fn new() -> Self {
+        Extractors(HashMap::new())
+    }
+
+    /// 是否没有自定义提取器
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 是否包含提取器（内置或自定义）
+    pub fn contains(&self, name: &str) -> bool {
+        BUILTIN.contains(&name) || self.0.contains_key(name)
+    }
+
+    /// 从输入中提取实体，找不到时返回 None
+    pub fn extract(&self, name: &str, input: &str) -> Result<Option<String>, String> {
+        if BUILTIN.contains(&name) {
+            return Ok(extract_builtin(name, input));
+        }
+        let extractor = self
+            .0
+            .get(name)
+            .ok_or_else(|| format!("Extractor {} not found", name))?;
+        let re = Regex::new(&extractor.pattern).map_err(|_| "Invalid pattern")?;
+        Ok(re.captures(input).map(|captures| {
+            let value = captures.get(1).or(captures.get(0)).unwrap().as_str();
+            normalize::normalize(&extractor.normalize, value)
+        }))
+    }
+
+    /// 检查自定义提取器（检测用，会报告所有错误）
+    pub fn check(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        for (name, extractor) in &self.0 {
+            if BUILTIN.contains(&name.as_str()) {
+                errors.push(format!("Extractor {} shadows a built-in extractor", name));
+            }
+            if Regex::new(&extractor.pattern).is_err() {
+                errors.push(format!("Invalid pattern in extractor {}", name));
+            }
+            for normalizer in &extractor.normalize {
+                if let Err(err) = normalizer.check() {
+                    errors.push(format!("Normalizer in extractor {} failed: {}", name, err));
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            errors.sort();
+            Err(errors.join(", "))
+        }
+    }
+
+    /// 检查转移的提取规则，提取器必须存在，变量必须为 Str 类型
+    pub fn check_extractions(
+        &self,
+        extractions: &Extractions,
+        variables: &Variables,
+    ) -> Result<(), String> {
+        let mut errors = Vec::new();
+        for extraction in extractions.iter() {
+            if self.contains(&extraction.extractor).not() {
+                errors.push(format!("Extractor {} not found", extraction.extractor));
+            }
+            match variables.get(&extraction.variable) {
+                Some(VariableType::Str(_)) => {}
+                Some(_) => errors.push(format!("Variable {} is not Str", extraction.variable)),
+                None => errors.push(format!("Variable {} not found", extraction.variable)),
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试内置提取器
+    #[test]
+    fn test_builtin() {
+        let extractors = Extractors::new();
+        let extract = |name: &str, input: &str| extractors.extract(name, input).unwrap();
+        assert_eq!(
+            extract("phone", "我的电话是 +86 138-0013-8000，谢谢"),
+            Some("13800138000".to_string())
+        );
+        assert_eq!(
+            extract("phone", "手机１３９１２３４５６７８"),
+            Some("13912345678".to_string())
+        );
+        assert_eq!(extract("phone", "订单号 2024138001380001"), None);
+        assert_eq!(
+            extract("email", "发到 Sakiko.Togawa@Example.COM 吧"),
+            Some("sakiko.togawa@example.com".to_string())
+        );
+        assert_eq!(
+            extract("id_card", "身份证 11010519491231002x"),
+            Some("11010519491231002X".to_string())
+        );
+        assert_eq!(extract("id_card", "身份证 110105194912310021"), None);
+        assert_eq!(
+            extract("date", "预约2024年5月1日下午"),
+            Some("2024-05-01".to_string())
+        );
+        assert_eq!(extract("date", "2023/2/29"), None);
+        assert_eq!(
+            extract("order_id", "订单 so20240501001 没到"),
+            Some("SO20240501001".to_string())
+        );
+        assert_eq!(extract("order_id", "没有订单号"), None);
+    }
+
+    // 测试自定义提取器和提取规则
+    #[test]
+    fn test_custom() {
+        let yaml = r#"
+ticket:
+  pattern: (?i)工单\s*(t-?\d{6})
+  normalize:
+    - Uppercase
+    - StripPunctuation
+"#;
+        let extractors: Extractors = serde_yaml::from_str(yaml).unwrap();
+        assert!(extractors.check().is_ok());
+        assert_eq!(
+            extractors
+                .extract("ticket", "工单 t-123456 还没处理")
+                .unwrap(),
+            Some("T123456".to_string())
+        );
+        assert_eq!(extractors.extract("ticket", "你好").unwrap(), None);
+        assert!(extractors.extract("unknown", "你好").is_err());
+
+        let extractions: Extractions = serde_yaml::from_str("phone -> contact").unwrap();
+        assert_eq!(extractions.len(), 1);
+        assert_eq!(extractions[0].variable, "contact");
+        let extractions: Extractions =
+            serde_yaml::from_str("[phone -> contact, ticket->ticket_id]").unwrap();
+        assert_eq!(extractions[1].extractor, "ticket");
+        let yaml = serde_yaml::to_string(&extractions).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<Extractions>(&yaml).unwrap(),
+            extractions
+        );
+        assert!(serde_yaml::from_str::<Extractions>("phone").is_err());
+
+        let mut variables = Variables::new();
+        variables.insert("contact".to_string(), VariableType::Str(String::new()));
+        variables.insert("ticket_id".to_string(), VariableType::Int(0));
+        assert_eq!(
+            extractors.check_extractions(&extractions, &variables),
+            Err("Variable ticket_id is not Str".to_string())
+        );
+    }
+}
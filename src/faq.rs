@@ -0,0 +1,307 @@
+/// Sakiko 的知识库检索
+/// 知识库由问答条目组成，可以直接写在配置中，也可以从 CSV 或 YAML 文件加载，加载时为问题建立 BM25 索引。
+/// 检索时对输入分词（与意图识别相同，多字词补充其中的单字），按 BM25 得分排序，返回得分最高的条目的答案。
+/// 返回的得分为 BM25 得分除以该问题与自身的得分，范围为 0 到 1，输入包含问题的所有词时为 1，便于设置阈值。
+use std::ops::Not;
+
+use ::serde::{Deserialize, Serialize};
+use ::std::collections::{HashMap, HashSet};
+use ::std::sync::Arc;
+
+use crate::intent::tokenize;
+use crate::segment::Segmenter;
+
+// BM25 的词频饱和参数和长度归一化参数
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// 问答条目
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct FaqEntry {
+    pub question: String, // 问题
+    pub answer: String,   // 答案
+}
+
+/// 知识库来源
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Default)]
+pub struct FaqSource {
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<FaqEntry>, // 问答条目（可置空）
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>, // 知识库文件路径，.csv 文件需有 question 和 answer 列，.yaml 或 .yml 文件为条目列表（可置空）
+}
+
+/// 知识库（序列化为来源），加载时读取知识库文件并建立索引
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(try_from = "FaqSource", into = "FaqSource")]
+pub struct Faq {
+    source: FaqSource,
+    entries: Vec<FaqEntry>,                   // 问答条目和知识库文件中的条目
+    frequencies: Vec<HashMap<String, usize>>, // 各问题的词频
+    lengths: Vec<f64>,                        // 各问题的词数
+    average: f64,                             // 问题的平均词数
+    idf: HashMap<String, f64>,                // 逆文档频率
+    maximums: Vec<f64>,                       // 各问题与自身的得分，用于归一化
+    segmenter: Arc<Segmenter>,                // 分词器
+}
+
+// 索引由来源决定，只比较来源
+impl PartialEq for Faq {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Default for Faq {
+    fn default() -> Self {
+        Self::new(FaqSource::default(), Vec::new(), Segmenter::builtin())
+    }
+}
+
+impl TryFrom<FaqSource> for Faq {
+    type Error = String;
+
+    fn try_from(source: FaqSource) -> Result<Self, Self::Error> {
+        let mut entries = source.entries.clone();
+        for file in &source.files {
+            let content = std::fs::read_to_string(file)
+                .map_err(|err| format!("Failed to read FAQ {}: {}", file, err))?;
+            entries.extend(
+                parse_entries(file, &content)
+                    .map_err(|err| format!("Failed to parse FAQ {}: {}", file, err))?,
+            );
+        }
+        Ok(Self::new(source, entries, Segmenter::builtin()))
+    }
+}
+
+impl From<Faq> for FaqSource {
+    fn from(faq: Faq) -> Self {
+        faq.source
+    }
+}
+
+// 按扩展名解析知识库文件的内容
+fn parse_entries(file: &str, content: &str) -> Result<Vec<FaqEntry>, String> {
+    let extension = std::path::Path::new(file)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+    match extension.as_deref() {
+        Some("csv") => csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(content.as_bytes())
+            .deserialize()
+            .collect::<Result<Vec<FaqEntry>, _>>()
+            .map_err(|err| err.to_string()),
+        Some("yaml") | Some("yml") => serde_yaml::from_str(content).map_err(|err| err.to_string()),
+        _ => Err("Unsupported file type".to_string()),
+    }
+}
+
+impl Faq {
+    // 创建知识库并用分词器建立索引
+    fn new(source: FaqSource, entries: Vec<FaqEntry>, segmenter: Arc<Segmenter>) -> Faq {
+        let documents: Vec<Vec<String>> = entries
+            .iter()
+            .map(|entry| tokenize(&segmenter, &entry.question))
+            .collect();
+        let mut df: HashMap<&str, usize> = HashMap::new();
+        for tokens in &documents {
+            let seen: HashSet<&str> = tokens.iter().map(String::as_str).collect();
+            for token in seen {
+                *df.entry(token).or_default() += 1;
+            }
+        }
+        // BM25 的逆文档频率，加 1 避免出现在大多数问题中的词权重为负
+        let n = documents.len() as f64;
+        let idf: HashMap<String, f64> = df
+            .into_iter()
+            .map(|(token, df)| {
+                let df = df as f64;
+                (token.to_string(), (1.0 + (n - df + 0.5) / (df + 0.5)).ln())
+            })
+            .collect();
+        let frequencies: Vec<HashMap<String, usize>> = documents
+            .iter()
+            .map(|tokens| {
+                let mut frequency = HashMap::new();
+                for token in tokens {
+                    *frequency.entry(token.clone()).or_default() += 1;
+                }
+                frequency
+            })
+            .collect();
+        let lengths: Vec<f64> = documents.iter().map(|tokens| tokens.len() as f64).collect();
+        let average = if lengths.is_empty() {
+            0.0
+        } else {
+            lengths.iter().sum::<f64>() / n
+        };
+        let mut faq = Faq {
+            source,
+            entries,
+            frequencies,
+            lengths,
+            average,
+            idf,
+            maximums: Vec::new(),
+            segmenter,
+        };
+        faq.maximums = (0..faq.entries.len())
+            .map(|index| {
+                let tokens: Vec<&str> = faq.frequencies[index].keys().map(String::as_str).collect();
+                faq.bm25(index, &tokens)
+            })
+            .collect();
+        faq
+    }
+
+    /// 用新的分词器重建索引，用于加载用户词典后
+    pub fn reindex(&mut self, segmenter: Arc<Segmenter>) {
+        let source = std::mem::take(&mut self.source);
+        let entries = std::mem::take(&mut self.entries);
+        *self = Self::new(source, entries, segmenter);
+    }
+
+    // 问题对（已去重的）查询词的 BM25 得分
+    fn bm25(&self, index: usize, tokens: &[&str]) -> f64 {
+        let frequency = &self.frequencies[index];
+        let norm = K1 * (1.0 - B + B * self.lengths[index] / self.average.max(1.0));
+        tokens
+            .iter()
+            .filter_map(|token| {
+                let tf = *frequency.get(*token)? as f64;
+                Some(self.idf[*token] * tf * (K1 + 1.0) / (tf + norm))
+            })
+            .sum()
+    }
+
+    /// 是否没有声明知识库
+    pub fn is_empty(&self) -> bool {
+        self.source == FaqSource::default()
+    }
+
+    /// 检索与输入最相关的条目，返回答案和归一化的得分，没有相关条目时返回 None
+    /// 按 BM25 得分排序，得分相同时取靠前的条目
+    pub fn search(&self, input: &str) -> Option<(&str, f64)> {
+        let mut tokens = tokenize(&self.segmenter, input);
+        tokens.sort_unstable();
+        tokens.dedup();
+        let tokens: Vec<&str> = tokens.iter().map(String::as_str).collect();
+        let mut best: Option<(usize, f64)> = None;
+        for index in 0..self.entries.len() {
+            let score = self.bm25(index, &tokens);
+            if score > 0.0 && best.is_none_or(|(_, best)| score > best) {
+                best = Some((index, score));
+            }
+        }
+        best.map(|(index, score)| {
+            (
+                self.entries[index].answer.as_str(),
+                (score / self.maximums[index]).min(1.0),
+            )
+        })
+    }
+
+    /// 检查知识库（检测用，会报告所有错误）
+    pub fn check(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        let mut questions = HashSet::new();
+        for entry in &self.entries {
+            if tokenize(&self.segmenter, &entry.question).is_empty() {
+                errors.push(format!("Empty question {:?}", entry.question));
+            } else if questions.insert(entry.question.as_str()).not() {
+                errors.push(format!("Duplicate question {:?}", entry.question));
+            }
+            if entry.answer.trim().is_empty() {
+                errors.push(format!("Empty answer to {:?}", entry.question));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试知识库检索
+    #[test]
+    fn test_search() {
+        let yaml = r#"
+entries:
+  - question: 怎么申请退款
+    answer: 在订单页点击申请退款即可。
+  - question: 发货要多久
+    answer: 下单后 48 小时内发货。
+  - question: 支持哪些支付方式
+    answer: 支持微信、支付宝和银行卡。
+  - question: How do I reset my password?
+    answer: Click "Forgot password" on the login page.
+"#;
+        let faq: Faq = serde_yaml::from_str(yaml).unwrap();
+        assert!(faq.check().is_ok());
+
+        let (answer, score) = faq.search("我想退款，怎么申请？").unwrap();
+        assert_eq!(answer, "在订单页点击申请退款即可。");
+        assert_eq!(score, 1.0);
+        let (answer, score) = faq.search("多久能发货").unwrap();
+        assert_eq!(answer, "下单后 48 小时内发货。");
+        assert!(score > 0.5);
+        let (answer, _) = faq.search("reset PASSWORD").unwrap();
+        assert_eq!(answer, "Click \"Forgot password\" on the login page.");
+        // 只有个别字相同时得分较低，可以按阈值转人工
+        let (_, score) = faq.search("退货").unwrap();
+        assert!(score < 0.3);
+        assert_eq!(faq.search("你好"), None);
+
+        let yaml = serde_yaml::to_string(&faq).unwrap();
+        assert_eq!(serde_yaml::from_str::<Faq>(&yaml).unwrap(), faq);
+
+        let faq = Faq::default();
+        assert!(faq.is_empty());
+        assert_eq!(faq.search("退款"), None);
+
+        let yaml = r#"
+entries:
+  - { question: 退款, answer: 可以退 }
+  - { question: 退款, answer: "" }
+  - { question: "？", answer: 不知道 }
+"#;
+        let faq: Faq = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            faq.check(),
+            Err(
+                "Duplicate question \"退款\", Empty answer to \"退款\", Empty question \"？\""
+                    .to_string()
+            )
+        );
+        assert!(serde_yaml::from_str::<Faq>("files: [not_found.csv]").is_err());
+    }
+
+    // 测试知识库文件解析
+    #[test]
+    fn test_parse_entries() {
+        let csv = "question,answer\n怎么退款,\"在订单页申请，1-3 天到账\"\n发货要多久,48 小时内\n";
+        let entries = parse_entries("faq.CSV", csv).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].question, "怎么退款");
+        assert_eq!(entries[0].answer, "在订单页申请，1-3 天到账");
+        assert!(parse_entries("faq.csv", "question,reply\n退款,可以\n").is_err());
+
+        let yaml = "- question: 怎么退款\n  answer: 在订单页申请\n";
+        assert_eq!(
+            parse_entries("faq.yml", yaml).unwrap(),
+            vec![FaqEntry {
+                question: "怎么退款".to_string(),
+                answer: "在订单页申请".to_string(),
+            }]
+        );
+        assert!(parse_entries("faq.txt", yaml).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+/// Sakiko 的表单
+/// 表单由若干字段组成，会话依次提示用户填写每个字段，输入经过类型转换和校验后存入变量，
+/// 校验失败时给出重试提示，超过最大次数时跳转到失败步骤，全部填写完成后执行操作并跳转到下一步。
+use ::serde::{Deserialize, Serialize};
+use regex::Regex;
+
+use crate::number::{parse_integer_number, parse_number};
+use crate::operation::Operations;
+use crate::output::Output;
+use crate::variable::{VariableType, Variables};
+
+/// 校验器
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub enum Validator {
+    Range(f64, f64),     // 数值范围（闭区间）
+    Regex(String),       // 正则表达式（需完整匹配）
+    Choice(Vec<String>), // 可选值
+}
+
+/// 表单字段
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Field {
+    pub variable: String, // 目标变量（必须），类型决定输入的转换方式
+    pub prompt: Output,   // 提示（必须）
+    #[serde(default = "Vec::new")]
+    pub validators: Vec<Validator>, // 校验器（可置空）
+    #[serde(default = "Field::default_retry")]
+    pub retry: Output, // 校验失败时的提示（可置空）
+    #[serde(default = "u32::default")]
+    pub max_attempts: u32, // 最大尝试次数，0 为不限（可置空）
+}
+
+/// 表单
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Form {
+    pub fields: Vec<Field>, // 字段（必须）
+    pub step: String,       // 填写完成后的下一步（必须）
+    #[serde(default = "Operations::new")]
+    pub operation: Operations, // 填写完成后的操作（可置空）
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    pub fail: Option<String>, // 超过最大尝试次数后的下一步（有字段限制次数时必须）
+}
+
+impl Validator {
+    /// 校验输入，value 为转换后的值
+    pub fn validate(&self, input: &str, value: &VariableType) -> bool {
+        match self {
+            Validator::Range(min, max) => value
+                .to_float()
+                .is_some_and(|value| *min <= value && value <= *max),
+            Validator::Regex(pattern) => {
+                Regex::new(&format!("^(?:{})$", pattern)).is_ok_and(|re| re.is_match(input))
+            }
+            Validator::Choice(choices) => choices.iter().any(|choice| choice == input),
+        }
+    }
+
+    /// 检查校验器是否适用于变量（检测用）
+    pub fn check(&self, variable: &VariableType) -> Result<(), String> {
+        match self {
+            Validator::Range(min, max) => {
+                if !variable.is_number() {
+                    return Err("Range requires a number variable".to_string());
+                }
+                if min > max {
+                    return Err(format!("Invalid range: {} > {}", min, max));
+                }
+                Ok(())
+            }
+            Validator::Regex(pattern) => Regex::new(pattern)
+                .map(|_| ())
+                .map_err(|_| format!("Invalid regex: {}", pattern)),
+            Validator::Choice(choices) => {
+                if choices.is_empty() {
+                    Err("Empty choice list".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+impl Field {
+    // 默认的重试提示
+    fn default_retry() -> Output {
+        Output::new("输入无效，请重新输入。".to_string(), Vec::new())
+    }
+
+    /// 转换并校验输入，失败时返回 None
+    pub fn parse(&self, input: &str, variable: &VariableType) -> Option<VariableType> {
+        let input = input.trim();
+        let value = match variable {
+            VariableType::Int(_) => VariableType::Int(parse_integer_number(input)?),
+            VariableType::Float(_) => VariableType::Float(parse_number(input)?),
+            VariableType::Str(_) => VariableType::Str(input.to_string()),
+            _ => return None,
+        };
+        self.validators
+            .iter()
+            .all(|validator| validator.validate(input, &value))
+            .then_some(value)
+    }
+}
+
+impl Form {
+    /// 检查表单（检测用，会报告所有错误），步骤和操作由配置统一检查
+    pub fn check(&self, variables: &Variables) -> Result<(), String> {
+        let mut errors = Vec::new();
+        if self.fields.is_empty() {
+            errors.push("Form has no field".to_string());
+        }
+        for field in &self.fields {
+            match variables.get(&field.variable) {
+                Some(
+                    variable @ (VariableType::Int(_)
+                    | VariableType::Float(_)
+                    | VariableType::Str(_)),
+                ) => {
+                    for validator in &field.validators {
+                        if let Err(err) = validator.check(variable) {
+                            errors.push(format!("Field {}: {}", field.variable, err));
+                        }
+                    }
+                }
+                Some(_) => errors.push(format!("Field {}: Type mismatch", field.variable)),
+                None => errors.push(format!("Variable not found: {}", field.variable)),
+            }
+            for output in [&field.prompt, &field.retry] {
+                if let Err(err) = output.check(variables) {
+                    errors.push(format!("Field {}: {}", field.variable, err));
+                }
+            }
+            if field.max_attempts > 0 && self.fail.is_none() {
+                errors.push(format!(
+                    "Field {} limits attempts but form has no fail step",
+                    field.variable
+                ));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试字段的转换和校验
+    #[test]
+    fn test_field() {
+        let field = |validators| Field {
+            variable: "x".to_string(),
+            prompt: Output::default(),
+            validators,
+            retry: Field::default_retry(),
+            max_attempts: 0,
+        };
+
+        let age = field(vec![Validator::Range(1.0, 120.0)]);
+        assert_eq!(
+            age.parse(" 18 ", &VariableType::Int(0)),
+            Some(VariableType::Int(18))
+        );
+        assert_eq!(age.parse("0", &VariableType::Int(0)), None);
+        assert_eq!(age.parse("abc", &VariableType::Int(0)), None);
+        assert_eq!(
+            age.parse("十八", &VariableType::Int(0)),
+            Some(VariableType::Int(18))
+        );
+        assert_eq!(age.parse("18.5", &VariableType::Int(0)), None);
+        assert_eq!(
+            age.parse("１.５", &VariableType::Float(0.0)),
+            Some(VariableType::Float(1.5))
+        );
+        assert_eq!(
+            age.parse("1.5", &VariableType::Float(0.0)),
+            Some(VariableType::Float(1.5))
+        );
+
+        let phone = field(vec![Validator::Regex(r"1\d{10}".to_string())]);
+        let str = VariableType::Str(String::new());
+        assert!(phone.parse("13800000000", &str).is_some());
+        assert!(phone.parse("138000000001", &str).is_none());
+
+        let city = field(vec![Validator::Choice(vec![
+            "北京".to_string(),
+            "上海".to_string(),
+        ])]);
+        assert_eq!(
+            city.parse("上海", &str),
+            Some(VariableType::Str("上海".to_string()))
+        );
+        assert_eq!(city.parse("广州", &str), None);
+
+        // 检测
+        let mut variables = Variables::new();
+        variables.insert("x".to_string(), str.clone());
+        let form = Form {
+            fields: vec![age, phone],
+            step: "end".to_string(),
+            operation: Operations::new(),
+            fail: None,
+        };
+        assert!(form.check(&variables).is_err());
+        variables.insert("x".to_string(), VariableType::Int(0));
+        assert!(form.check(&variables).is_ok());
+        assert!(Validator::Regex("(".to_string()).check(&str).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+/// Sakiko 的模糊匹配
+/// 基于编辑距离（Levenshtein 距离）计算两个字符串的相似度，按字符（而非字节）计算，忽略大小写和首尾空白。
+/// 相似度为 1 - 编辑距离 / 较长字符串的长度，范围为 0 到 1，完全相同时为 1。
+/// 编辑距离，即将 a 变为 b 所需的最少插入、删除和替换次数
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // 只保留上一行，空间为 O(len(b))
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitute = diagonal + (x != y) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// 相似度，两个字符串都为空时为 1
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a = a.trim().to_lowercase();
+    let b = b.trim().to_lowercase();
+    let len = a.chars().count().max(b.chars().count());
+    if len == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / len as f64
+}
+
+/// 在候选中查找与目标最相似且相似度不低于阈值的一个，相似度相同时取字典序最小的，结果与候选的顺序无关
+pub fn closest<'a>(
+    candidates: impl IntoIterator<Item = &'a str>,
+    target: &str,
+    threshold: f64,
+) -> Option<(&'a str, f64)> {
+    let mut best: Option<(&str, f64)> = None;
+    for candidate in candidates {
+        let score = similarity(candidate, target);
+        if score >= threshold
+            && best.is_none_or(|(key, best)| score > best || (score == best && candidate < key))
+        {
+            best = Some((candidate, score));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试编辑距离和相似度
+    #[test]
+    fn test_similarity() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("算法设计与分析", "算法设计与分折"), 1);
+        assert_eq!(similarity(" Hello ", "hello"), 1.0);
+        assert_eq!(similarity("", ""), 1.0);
+        assert!((similarity("算法设计与分析", "算法设计与分折") - 6.0 / 7.0).abs() < 1e-9);
+
+        let keys = ["操作系统", "算法设计与分析", "编译原理与技术"];
+        assert_eq!(
+            closest(keys, "算法设计与分折", 0.8).map(|(key, _)| key),
+            Some("算法设计与分析")
+        );
+        assert_eq!(closest(keys, "篮球", 0.5), None);
+        // 相似度相同时取字典序最小的，与候选的顺序无关
+        for keys in [["abd", "abc"], ["abc", "abd"]] {
+            assert_eq!(closest(keys, "abx", 0.5).map(|(key, _)| key), Some("abc"));
+        }
+    }
+}
@@ -0,0 +1,263 @@
+/// Sakiko 的意图识别
+/// 意图由例句和关键词组成，加载时在本地建立 TF-IDF 索引，不依赖网络。
+/// 分词时英文和数字按单词切分，其余文字（如汉字）按词典分词，多字词再补充其中的单字。
+/// 输入与某个意图各例句的最大余弦相似度为该意图的得分，输入包含该意图的关键词时得分为 1。
+/// 关键词按分词结果匹配，只有输入中连续的若干词与关键词的分词结果相同时才算包含，避免跨词误匹配。
+use ::serde::{Deserialize, Serialize};
+use ::std::collections::HashMap;
+use ::std::sync::Arc;
+
+use crate::segment::Segmenter;
+
+/// 意图
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Intent {
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>, // 例句（可置空）
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>, // 关键词，忽略大小写（可置空）
+}
+
+// 词向量（已归一化）
+type Vector = HashMap<String, f64>;
+
+/// 意图集合（序列化为意图名到意图的映射），加载时建立索引
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(from = "HashMap<String, Intent>", into = "HashMap<String, Intent>")]
+pub struct Intents {
+    intents: HashMap<String, Intent>,
+    idf: HashMap<String, f64>,      // 逆文档频率
+    vectors: Vec<(String, Vector)>, // 各例句所属的意图和词向量
+    segmenter: Arc<Segmenter>,      // 分词器
+}
+
+// 索引由意图决定，只比较意图
+impl PartialEq for Intents {
+    fn eq(&self, other: &Self) -> bool {
+        self.intents == other.intents
+    }
+}
+
+impl Default for Intents {
+    fn default() -> Self {
+        Self::new(HashMap::new(), Segmenter::builtin())
+    }
+}
+
+impl From<HashMap<String, Intent>> for Intents {
+    fn from(intents: HashMap<String, Intent>) -> Self {
+        Self::new(intents, Segmenter::builtin())
+    }
+}
+
+impl From<Intents> for HashMap<String, Intent> {
+    fn from(intents: Intents) -> Self {
+        intents.intents
+    }
+}
+
+/// 分词，在分词结果的基础上补充多字词中的单字，使部分相同的词也能得分
+pub fn tokenize(segmenter: &Segmenter, text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for word in segmenter.segment(text) {
+        if word.chars().count() > 1 && !word.is_ascii() {
+            tokens.extend(word.chars().map(String::from));
+        }
+        tokens.push(word);
+    }
+    tokens
+}
+
+// 判断 words 中是否有连续的若干词与 keyword 相同
+fn contains_words(words: &[String], keyword: &[String]) -> bool {
+    !keyword.is_empty() && words.windows(keyword.len()).any(|window| window == keyword)
+}
+
+impl Intents {
+    /// 创建意图集合并用分词器建立索引
+    pub fn new(intents: HashMap<String, Intent>, segmenter: Arc<Segmenter>) -> Intents {
+        // 按意图名排列，保证索引与加载顺序无关
+        let mut names: Vec<&String> = intents.keys().collect();
+        names.sort_unstable();
+        let documents: Vec<(&String, Vec<String>)> = names
+            .into_iter()
+            .flat_map(|name| {
+                intents[name]
+                    .examples
+                    .iter()
+                    .map(|example| (name, tokenize(&segmenter, example)))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let mut df: HashMap<&str, usize> = HashMap::new();
+        for (_, tokens) in &documents {
+            let mut seen: Vec<&str> = tokens.iter().map(String::as_str).collect();
+            seen.sort_unstable();
+            seen.dedup();
+            for token in seen {
+                *df.entry(token).or_default() += 1;
+            }
+        }
+        // 平滑的逆文档频率，避免出现在所有例句中的词权重为 0
+        let n = documents.len() as f64;
+        let idf: HashMap<String, f64> = df
+            .into_iter()
+            .map(|(token, df)| {
+                (
+                    token.to_string(),
+                    ((n + 1.0) / (df as f64 + 1.0)).ln() + 1.0,
+                )
+            })
+            .collect();
+        let vectors = documents
+            .iter()
+            .map(|(name, tokens)| (name.to_string(), Self::vectorize(&idf, tokens)))
+            .collect();
+        Intents {
+            intents,
+            idf,
+            vectors,
+            segmenter,
+        }
+    }
+
+    /// 用新的分词器重建索引（加载用户词典后使用）
+    pub fn reindex(&mut self, segmenter: Arc<Segmenter>) {
+        *self = Self::new(std::mem::take(&mut self.intents), segmenter);
+    }
+
+    // 计算 TF-IDF 向量并归一化，索引中没有的词忽略
+    fn vectorize(idf: &HashMap<String, f64>, tokens: &[String]) -> Vector {
+        let mut vector = Vector::new();
+        for token in tokens {
+            if let Some(weight) = idf.get(token) {
+                *vector.entry(token.clone()).or_default() += weight;
+            }
+        }
+        let norm = vector.values().map(|x| x * x).sum::<f64>().sqrt();
+        if norm > 0.0 {
+            vector.values_mut().for_each(|x| *x /= norm);
+        }
+        vector
+    }
+
+    /// 是否没有意图
+    pub fn is_empty(&self) -> bool {
+        self.intents.is_empty()
+    }
+
+    /// 是否包含意图
+    pub fn contains(&self, name: &str) -> bool {
+        self.intents.contains_key(name)
+    }
+
+    /// 对输入打分，返回得分为正的意图，按得分从高到低排列（得分相同时按意图名排列）
+    pub fn classify(&self, input: &str) -> Vec<(&str, f64)> {
+        let mut scores: HashMap<&str, f64> = HashMap::new();
+        let vector = Self::vectorize(&self.idf, &tokenize(&self.segmenter, input));
+        for (name, example) in &self.vectors {
+            let score: f64 = vector
+                .iter()
+                .filter_map(|(token, x)| example.get(token).map(|y| x * y))
+                .sum();
+            let entry = scores.entry(name).or_default();
+            *entry = entry.max(score);
+        }
+        let words = self.segmenter.segment(input);
+        for (name, intent) in &self.intents {
+            if intent
+                .keywords
+                .iter()
+                .any(|keyword| contains_words(&words, &self.segmenter.segment(keyword)))
+            {
+                scores.insert(name, 1.0);
+            }
+        }
+        let mut scores: Vec<(&str, f64)> = scores
+            .into_iter()
+            .filter(|(_, score)| *score > 0.0)
+            .map(|(name, score)| (name, score.min(1.0)))
+            .collect();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        scores
+    }
+
+    /// 检查意图（检测用，会报告所有错误）
+    pub fn check(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        for (name, intent) in &self.intents {
+            if intent.examples.is_empty() && intent.keywords.is_empty() {
+                errors.push(format!("Intent {} has no example or keyword", name));
+            }
+            for example in &intent.examples {
+                if tokenize(&self.segmenter, example).is_empty() {
+                    errors.push(format!("Empty example in intent {}", name));
+                }
+            }
+            for keyword in &intent.keywords {
+                if self.segmenter.segment(keyword).is_empty() {
+                    errors.push(format!("Empty keyword in intent {}", name));
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试分词
+    #[test]
+    fn test_tokenize() {
+        let segmenter = Segmenter::builtin();
+        assert_eq!(
+            tokenize(&segmenter, "查订单 No.42"),
+            vec!["查", "订", "单", "订单", "no", "42"]
+        );
+        assert!(tokenize(&segmenter, "？！").is_empty());
+    }
+
+    // 测试意图打分
+    #[test]
+    fn test_classify() {
+        let yaml = r#"
+order:
+  examples:
+    - 我的订单到哪了
+    - 查一下订单状态
+    - where is my order
+refund:
+  examples:
+    - 我要退款
+    - 怎么申请退货
+  keywords:
+    - 退钱
+"#;
+        let intents: Intents = serde_yaml::from_str(yaml).unwrap();
+        assert!(intents.check().is_ok());
+
+        let scores = intents.classify("订单到哪里了");
+        assert_eq!(scores[0].0, "order");
+        assert!(scores[0].1 > 0.5);
+        let scores = intents.classify("Where's my ORDER?");
+        assert_eq!(scores[0].0, "order");
+        assert_eq!(intents.classify("能退钱吗")[0], ("refund", 1.0));
+        // 关键词按词匹配，“退钱包”分词为“退 / 钱包”，不包含关键词“退钱”
+        assert!(intents
+            .classify("怎么退钱包")
+            .iter()
+            .all(|(_, score)| *score < 1.0));
+        assert!(intents.classify("你好").is_empty());
+
+        let yaml = serde_yaml::to_string(&intents).unwrap();
+        assert_eq!(serde_yaml::from_str::<Intents>(&yaml).unwrap(), intents);
+        let empty: Intents = serde_yaml::from_str("empty: {}").unwrap();
+        assert!(empty.check().is_err());
+    }
+}
@@ -7,10 +7,27 @@ mod compare;
 pub mod sakiko;
 mod operation;
 mod output;
+mod template;
+mod form;
+mod intent;
+mod fuzzy;
+mod normalize;
+mod pinyin;
+mod segment;
+mod number;
+mod extract;
+mod relative;
+mod sensitive;
+mod sentiment;
+mod faq;
+pub mod message;
 pub mod check;
+pub mod time;
+mod context;
 
 pub use config::SakikoConfig;
-pub use sakiko::Session;
+pub use message::{Element, Message};
+pub use sakiko::{Session, SessionSnapshot};
 
 /// 加载配置文件
 /// 传入配置文件路径，返回智能指针封装的配置
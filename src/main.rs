@@ -1,17 +1,27 @@
 /// 同步版本的运行程序
 /// 通过标准输入输出进行交互
-/// 用法: cargo run --release -- <config file>
+/// 用法: cargo run --release -- <config file> [seed]
+/// 指定随机数种子时可以复现之前的对话
 use sakiko::load_config;
 use sakiko::Session;
 use std::env;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: {} <config file>", args[0]);
+    if args.len() != 2 && args.len() != 3 {
+        eprintln!("Usage: {} <config file> [seed]", args[0]);
         std::process::exit(1);
     }
     let config = load_config(&args[1]).unwrap();
-    let mut session = Session::new(config);
+    let mut session = match args.get(2) {
+        Some(seed) => match seed.parse() {
+            Ok(seed) => Session::with_seed(config, seed),
+            Err(_) => {
+                eprintln!("Usage: {} <config file> [seed]", args[0]);
+                std::process::exit(1);
+            }
+        },
+        None => Session::new(config),
+    };
     session.run_stdio().unwrap();
 }
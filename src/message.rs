@@ -0,0 +1,211 @@
+/// Sakiko 的富消息
+/// 消息由文本和若干结构化元素组成，元素包括快捷回复按钮、链接、图片和卡片。
+/// 配置中元素的各字段为模板（只能使用命名占位符），输出时渲染为字符串；不支持富消息的前端使用纯文本形式。
+use ::serde::{Deserialize, Serialize};
+
+use crate::output::Label;
+use crate::variable::Variables;
+
+/// 消息元素，T 为配置中的模板或渲染后的字符串
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub enum Element<T> {
+    // 快捷回复按钮，点击后将 payload 作为用户输入（缺省时为 label）
+    QuickReply {
+        label: T,
+        #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+        payload: Option<T>,
+    },
+    // 链接
+    Link {
+        label: T,
+        url: T,
+    },
+    // 图片
+    Image {
+        url: T,
+        #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+        alt: Option<T>,
+    },
+    // 卡片
+    Card {
+        title: T,
+        #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+        text: Option<T>,
+        #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+        image: Option<T>,
+        #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+        url: Option<T>,
+    },
+}
+
+/// 消息
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Message {
+    pub text: String, // 文本
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub elements: Vec<Element<String>>, // 结构化元素
+}
+
+// 转换可选字段
+fn map_option<T, U, E>(
+    x: &Option<T>,
+    f: &mut impl FnMut(&T) -> Result<U, E>,
+) -> Result<Option<U>, E> {
+    x.as_ref().map(f).transpose()
+}
+
+impl<T> Element<T> {
+    /// 转换元素的每个字段
+    pub fn try_map<U, E>(&self, mut f: impl FnMut(&T) -> Result<U, E>) -> Result<Element<U>, E> {
+        Ok(match self {
+            Element::QuickReply { label, payload } => Element::QuickReply {
+                label: f(label)?,
+                payload: map_option(payload, &mut f)?,
+            },
+            Element::Link { label, url } => Element::Link {
+                label: f(label)?,
+                url: f(url)?,
+            },
+            Element::Image { url, alt } => Element::Image {
+                url: f(url)?,
+                alt: map_option(alt, &mut f)?,
+            },
+            Element::Card {
+                title,
+                text,
+                image,
+                url,
+            } => Element::Card {
+                title: f(title)?,
+                text: map_option(text, &mut f)?,
+                image: map_option(image, &mut f)?,
+                url: map_option(url, &mut f)?,
+            },
+        })
+    }
+}
+
+impl Element<Label> {
+    /// 渲染元素，快捷回复的 payload 缺省时使用 label
+    pub fn fmt(&self, variables: &Variables) -> Result<Element<String>, &'static str> {
+        let element = self.try_map(|label| label.fmt(variables))?;
+        Ok(match element {
+            Element::QuickReply {
+                label,
+                payload: None,
+            } => Element::QuickReply {
+                payload: Some(label.clone()),
+                label,
+            },
+            element => element,
+        })
+    }
+
+    /// 检查元素的模板（检测用，会报告所有错误）
+    pub fn check(&self, variables: &Variables) -> Result<(), String> {
+        let mut errors = Vec::new();
+        let _ = self.try_map(|label| {
+            if let Err(err) = label.check(variables) {
+                errors.push(err);
+            }
+            Ok::<(), ()>(())
+        });
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+}
+
+impl Message {
+    /// 纯文本形式，快捷回复合并为一行，其余元素各占一行
+    pub fn to_plain(&self) -> String {
+        let mut lines = Vec::new();
+        if !self.text.is_empty() {
+            lines.push(self.text.clone());
+        }
+        let mut replies = Vec::new();
+        for element in &self.elements {
+            match element {
+                Element::QuickReply { label, .. } => replies.push(format!("[{}]", label)),
+                Element::Link { label, url } => lines.push(format!("{}: {}", label, url)),
+                Element::Image { url, alt } => match alt {
+                    Some(alt) => lines.push(format!("[图片: {}] {}", alt, url)),
+                    None => lines.push(format!("[图片] {}", url)),
+                },
+                Element::Card {
+                    title,
+                    text,
+                    image,
+                    url,
+                } => {
+                    lines.push(format!("【{}】", title));
+                    lines.extend(text.iter().cloned());
+                    lines.extend(image.iter().map(|image| format!("[图片] {}", image)));
+                    lines.extend(url.iter().cloned());
+                }
+            }
+        }
+        if !replies.is_empty() {
+            lines.push(replies.join(" "));
+        }
+        lines.join("\n")
+    }
+
+    /// JSON 形式（WebSocket 服务端使用）
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::variable::VariableType;
+
+    use super::*;
+
+    // 测试元素的渲染、检测和纯文本形式
+    #[test]
+    fn test_message() {
+        let yaml = r#"
+- !QuickReply
+  label: 查询订单
+- !Link
+  label: 订单 {order}
+  url: https://example.com/orders/{order}
+- !Card
+  title: "{name}"
+  image: https://example.com/{name}.png
+"#;
+        let elements: Vec<Element<Label>> = serde_yaml::from_str(yaml).unwrap();
+        let mut variables = Variables::new();
+        variables.insert("order".to_string(), VariableType::Int(42));
+        assert!(elements[0].check(&variables).is_ok());
+        assert!(elements[2].check(&variables).is_err());
+        variables.insert("name".to_string(), VariableType::Str("Sakiko".to_string()));
+        assert!(elements[2].check(&variables).is_ok());
+
+        let message = Message {
+            text: "您好".to_string(),
+            elements: elements
+                .iter()
+                .map(|element| element.fmt(&variables))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+        };
+        assert_eq!(
+            message.elements[0],
+            Element::QuickReply {
+                label: "查询订单".to_string(),
+                payload: Some("查询订单".to_string())
+            }
+        );
+        assert_eq!(
+            message.to_plain(),
+            "您好\n订单 42: https://example.com/orders/42\n【Sakiko】\n[图片] https://example.com/Sakiko.png\n[查询订单]"
+        );
+        let json: Message = serde_json::from_str(&message.to_json()).unwrap();
+        assert_eq!(json, message);
+    }
+}
@@ -0,0 +1,209 @@
+/// Sakiko 的输入规范化
+/// 匹配前按配置的顺序对用户输入进行规范化，包括大小写折叠、全角转半角、繁体转简体、去除标点、合并空白和同义词替换。
+/// 规范化只影响匹配，变量 input 仍为原始输入。
+use ::serde::{Deserialize, Serialize};
+use ::std::collections::HashMap;
+
+/// 规范化步骤
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub enum Normalizer {
+    Lowercase,                              // 大小写折叠
+    Uppercase,                              // 转为大写
+    HalfWidth,                              // 全角字符转为半角
+    Simplified,                             // 繁体字转为简体字（内置常用字表）
+    StripPunctuation,                       // 去除标点和符号（保留文字、数字和空白）
+    CollapseWhitespace,                     // 连续空白合并为一个空格，并去除首尾空白
+    Synonyms(HashMap<String, Vec<String>>), // 同义词替换，键为标准词，值为同义词
+}
+
+// 常用繁体字（按码位排列）
+const TRADITIONAL: &str = "\
+    來係個們傑傳債傷傾僅價儀億償優儲兌兒內兩冊別劃劇動務勝勞區協卻參問啓啟喚喬單嗎嘆\
+    噴嚇嚴國圍園圖團場塊壇壓壞壯壽夠夢夾奪奮婦嬰孫學實寧審寫寶將專尋對導屆層屬岡島峽\
+    嶺帥師帳帶幣幫幾庫廠廢廣廳張強彈彌彎彙後徑從復徵悅惡惱愛態慣憂憶應懷懸戀戰戲戶掃\
+    掛揮損搖搶撥撲擁擇擊擔據擠擬擴擾攔攜攝敗敵數斂斷時暈暢暫曆曬書會東條極構槍樁樂樓\
+    標樣樹橋機橫檔檢檯櫃權歎歐歡歲歷歸殘殺殼毀氣氫氾決沒況淚淺渦測湧湯準溝溫溼滅滯滲\
+    滾滿漁漢漲漿潔潛澤濃濕濟濤濫瀏灣災為烏無煙熱燈燒營燦燭爐爛爭爺爾牆牽犧狀狹猶獅獎\
+    獨獲獵獻現瑣瑪環瓊產畝畢畫異當瘋瘡療癒發皺盜盡監盤盧眾睜矯碩確碼磚礙礦祕祿禍禦禪\
+    禮稅種稱穀積穩穫窩窮窯竊競筆筍節範簡簽簾籃籠籤糧糾紀約紅紋納紐純紗紙級紛紡紮細終\
+    組結絕給絨統絲綁經綜綠維綱網綿緊緒線緣編緩緯練緻縣縫縮總績織繩繪繫繳續纖罈罰罵罷\
+    羅羥義習翹聖聞聯聰聲聳職聽肅脅腦腳膚膠膽臉臟臨臺與興舉舊艙艦艱艷芻莊莖華萬葉蒼蓋\
+    蔣蔥蕭薦薩藍藝藥蘆蘇蘊蘋蘭處虛號蝦螢蟲蠟蠶衆術衛衝衹裏補裝裡製複褲襪襯襲見規覓視\
+    親覺覽觀觸訂計訊訓託記訝訪設許訴詐評詞詠詢試詩詮話該詳誇誌認誕誘語誠誤說説誰課誼\
+    調談請諒論諧諷諾謀謎謙講謝謠謹證識譜譯議護譽讀變讓讚豐豬貓貝貞負財貢貧貨販貫責貴\
+    買貸費貼貿賀賄資賓賞賠賢賣賤賦質賬賭賴賺購賽贈贊贏贖趕趙趨跡蹤躍車軌軍軟軸較載輔\
+    輕輛輩輪輯輸輻轄轉轎辦辭辯農迴這週進運過達遙遜遞遠適遲遷選遺還邊邏郵鄉鄧鄭鄰醜醞\
+    醫醬釀釋釘針釣鈍鈔鈣鈴鉛鉤銀銅銘銳銷鋒鋪鋼錄錢錦錫錯鍋鍛鍵鍾鎊鎖鎮鏈鏡鐘鐲鐵鑑鑰\
+    長門閃閉開閒間閣閱闆闊闖關闡陝陣陰陳陸陽隊際隨險隱隸隻雖雙雛雜雞離難雲電霧靂靈靜\
+    鞏韋韓韻響頁頂項順須頌預頑頒頓頗領頭頰頸頻顆題額顏願類顧顫顯風颱颳颶飄飛飯飲飼飽\
+    飾餅養餓餘館饑饒饞馬駐駕駛騎騙騰驅驕驗驚驟驢骯髒體髮鬆鬍鬥鬧鬱魚魯鮮鯉鯊鯨鱷鳥鳳\
+    鴨鴿鵝鶴鷗鷹鹽麗麥麵麼黃點黨黴鼕齊齋齒齡齣龍龐龜";
+
+// 与 TRADITIONAL 逐字对应的简体字
+const SIMPLIFIED: &str = "\
+    来系个们杰传债伤倾仅价仪亿偿优储兑儿内两册别划剧动务胜劳区协却参问启启唤乔单吗叹\
+    喷吓严国围园图团场块坛压坏壮寿够梦夹夺奋妇婴孙学实宁审写宝将专寻对导届层属冈岛峡\
+    岭帅师账带币帮几库厂废广厅张强弹弥弯汇后径从复征悦恶恼爱态惯忧忆应怀悬恋战戏户扫\
+    挂挥损摇抢拨扑拥择击担据挤拟扩扰拦携摄败敌数敛断时晕畅暂历晒书会东条极构枪桩乐楼\
+    标样树桥机横档检台柜权叹欧欢岁历归残杀壳毁气氢泛决没况泪浅涡测涌汤准沟温湿灭滞渗\
+    滚满渔汉涨浆洁潜泽浓湿济涛滥浏湾灾为乌无烟热灯烧营灿烛炉烂争爷尔墙牵牺状狭犹狮奖\
+    独获猎献现琐玛环琼产亩毕画异当疯疮疗愈发皱盗尽监盘卢众睁矫硕确码砖碍矿秘禄祸御禅\
+    礼税种称谷积稳获窝穷窑窃竞笔笋节范简签帘篮笼签粮纠纪约红纹纳纽纯纱纸级纷纺扎细终\
+    组结绝给绒统丝绑经综绿维纲网绵紧绪线缘编缓纬练致县缝缩总绩织绳绘系缴续纤坛罚骂罢\
+    罗羟义习翘圣闻联聪声耸职听肃胁脑脚肤胶胆脸脏临台与兴举旧舱舰艰艳刍庄茎华万叶苍盖\
+    蒋葱萧荐萨蓝艺药芦苏蕴苹兰处虚号虾萤虫蜡蚕众术卫冲只里补装里制复裤袜衬袭见规觅视\
+    亲觉览观触订计讯训托记讶访设许诉诈评词咏询试诗诠话该详夸志认诞诱语诚误说说谁课谊\
+    调谈请谅论谐讽诺谋谜谦讲谢谣谨证识谱译议护誉读变让赞丰猪猫贝贞负财贡贫货贩贯责贵\
+    买贷费贴贸贺贿资宾赏赔贤卖贱赋质账赌赖赚购赛赠赞赢赎赶赵趋迹踪跃车轨军软轴较载辅\
+    轻辆辈轮辑输辐辖转轿办辞辩农回这周进运过达遥逊递远适迟迁选遗还边逻邮乡邓郑邻丑酝\
+    医酱酿释钉针钓钝钞钙铃铅钩银铜铭锐销锋铺钢录钱锦锡错锅锻键钟镑锁镇链镜钟镯铁鉴钥\
+    长门闪闭开闲间阁阅板阔闯关阐陕阵阴陈陆阳队际随险隐隶只虽双雏杂鸡离难云电雾雳灵静\
+    巩韦韩韵响页顶项顺须颂预顽颁顿颇领头颊颈频颗题额颜愿类顾颤显风台刮飓飘飞饭饮饲饱\
+    饰饼养饿余馆饥饶馋马驻驾驶骑骗腾驱骄验惊骤驴肮脏体发松胡斗闹郁鱼鲁鲜鲤鲨鲸鳄鸟凤\
+    鸭鸽鹅鹤鸥鹰盐丽麦面么黄点党霉冬齐斋齿龄出龙庞龟";
+
+// 繁体字转为简体字，不在字表中的字保持不变
+fn to_simplified(c: char) -> char {
+    TRADITIONAL
+        .chars()
+        .zip(SIMPLIFIED.chars())
+        .find(|(t, _)| *t == c)
+        .map_or(c, |(_, s)| s)
+}
+
+/// 全角字符转为半角，全角空格转为空格
+pub fn to_half_width(c: char) -> char {
+    match c {
+        '\u{3000}' => ' ',
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        _ => c,
+    }
+}
+
+impl Normalizer {
+    /// 对输入执行规范化步骤
+    pub fn apply(&self, input: &str) -> String {
+        match self {
+            Normalizer::Lowercase => input.to_lowercase(),
+            Normalizer::Uppercase => input.to_uppercase(),
+            Normalizer::HalfWidth => input.chars().map(to_half_width).collect(),
+            Normalizer::Simplified => input.chars().map(to_simplified).collect(),
+            Normalizer::StripPunctuation => input
+                .chars()
+                .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+                .collect(),
+            Normalizer::CollapseWhitespace => {
+                input.split_whitespace().collect::<Vec<_>>().join(" ")
+            }
+            Normalizer::Synonyms(synonyms) => {
+                // 较长的同义词优先替换，避免被其中较短的同义词截断
+                let mut words: Vec<(&str, &str)> = synonyms
+                    .iter()
+                    .flat_map(|(word, synonyms)| {
+                        synonyms
+                            .iter()
+                            .map(move |synonym| (synonym.as_str(), word.as_str()))
+                    })
+                    .collect();
+                words.sort_by(|a, b| {
+                    b.0.chars()
+                        .count()
+                        .cmp(&a.0.chars().count())
+                        .then(a.0.cmp(b.0))
+                });
+                let mut output = String::new();
+                let mut rest = input;
+                'outer: while let Some(c) = rest.chars().next() {
+                    for (synonym, word) in &words {
+                        if let Some(tail) = rest.strip_prefix(synonym) {
+                            output.push_str(word);
+                            rest = tail;
+                            continue 'outer;
+                        }
+                    }
+                    output.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+                output
+            }
+        }
+    }
+
+    /// 检查规范化步骤（检测用，会报告所有错误）
+    pub fn check(&self) -> Result<(), String> {
+        let Normalizer::Synonyms(synonyms) = self else {
+            return Ok(());
+        };
+        let mut errors = Vec::new();
+        let mut seen: HashMap<&str, &str> = HashMap::new();
+        for (word, list) in synonyms {
+            if list.is_empty() {
+                errors.push(format!("Word {} has no synonym", word));
+            }
+            for synonym in list {
+                if synonym.is_empty() {
+                    errors.push(format!("Empty synonym of word {}", word));
+                } else if let Some(other) = seen.insert(synonym, word) {
+                    if other != word {
+                        errors.push(format!(
+                            "Synonym {} belongs to both {} and {}",
+                            synonym,
+                            other.min(word),
+                            other.max(word)
+                        ));
+                    }
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            errors.sort();
+            Err(errors.join(", "))
+        }
+    }
+}
+
+/// 按顺序执行所有规范化步骤
+pub fn normalize(normalizers: &[Normalizer], input: &str) -> String {
+    normalizers
+        .iter()
+        .fold(input.to_string(), |input, normalizer| {
+            normalizer.apply(&input)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试各规范化步骤及其顺序
+    #[test]
+    fn test_normalize() {
+        assert_eq!(TRADITIONAL.chars().count(), SIMPLIFIED.chars().count());
+        let yaml = r#"
+- HalfWidth
+- Lowercase
+- Simplified
+- StripPunctuation
+- CollapseWhitespace
+- !Synonyms
+    物流: [快递, 快件]
+    订单: [单子]
+"#;
+        let normalizers: Vec<Normalizer> = serde_yaml::from_str(yaml).unwrap();
+        assert!(normalizers.iter().all(|n| n.check().is_ok()));
+        assert_eq!(
+            normalize(&normalizers, "  我的ＯＲＤＥＲ　單子，在哪裡？ "),
+            "我的order 订单在哪里"
+        );
+        assert_eq!(normalize(&normalizers, "查快遞"), "查物流");
+        assert_eq!(normalize(&normalizers[..1], "Ｈｉ！"), "Hi!");
+
+        let conflict: Normalizer = serde_yaml::from_str("!Synonyms {a: [x], b: [x]}").unwrap();
+        assert_eq!(
+            conflict.check(),
+            Err("Synonym x belongs to both a and b".to_string())
+        );
+    }
+}
@@ -0,0 +1,228 @@
+/// Sakiko 的数字解析
+/// 将用户输入的数字解析为数值，支持阿拉伯数字、全角数字、千位分隔符、中文数字（含大写数字）和数量级后缀。
+/// 如 “三十二”、“两百五”、“1.5万”、“１２”、“1,234”、“负三点五”、“3k” 等，不依赖外部库。
+use crate::normalize::to_half_width;
+
+// 中文数字，包括大写数字
+fn digit(c: char) -> Option<f64> {
+    let d = match c {
+        '零' | '〇' => 0,
+        '一' | '壹' | '幺' => 1,
+        '二' | '两' | '贰' => 2,
+        '三' | '叁' => 3,
+        '四' | '肆' => 4,
+        '五' | '伍' => 5,
+        '六' | '陆' => 6,
+        '七' | '柒' => 7,
+        '八' | '捌' => 8,
+        '九' | '玖' => 9,
+        _ => return None,
+    };
+    Some(d as f64)
+}
+
+// 数量级，十、百、千为小单位，万、亿为大单位，k 和 w 分别等同于千和万
+fn unit(c: char) -> Option<f64> {
+    match c {
+        '十' | '拾' => Some(1e1),
+        '百' | '佰' => Some(1e2),
+        '千' | '仟' | 'k' | 'K' => Some(1e3),
+        '万' | '萬' | 'w' | 'W' => Some(1e4),
+        '亿' | '億' => Some(1e8),
+        _ => None,
+    }
+}
+
+// 去除千位分隔符，分隔符必须每三位一个且位于整数部分
+fn strip_separators(s: &str) -> Option<String> {
+    if !s.contains(',') {
+        return Some(s.to_string());
+    }
+    let end = s
+        .find(|c: char| !c.is_ascii_digit() && c != ',')
+        .unwrap_or(s.len());
+    let (integer, rest) = s.split_at(end);
+    let groups: Vec<&str> = integer.split(',').collect();
+    let valid = (1..=3).contains(&groups[0].len())
+        && groups[1..].iter().all(|group| group.len() == 3)
+        && !rest.contains(',');
+    valid.then(|| groups.concat() + rest)
+}
+
+// 解析整数部分，可以是阿拉伯数字（可带小数）、逐位读出的中文数字或带数量级的中文数字
+fn parse_integer(s: &str) -> Option<f64> {
+    if s.is_empty() {
+        return None;
+    }
+    // 逐位读出，如 “二零二四”
+    if s.chars().all(|c| digit(c).is_some()) {
+        return s
+            .chars()
+            .try_fold(0.0, |value, c| digit(c).map(|d| value * 10.0 + d));
+    }
+    let mut total = 0.0; // 亿以上的部分
+    let mut wan = 0.0; // 万到亿之间的部分
+    let mut section = 0.0; // 万以下的部分
+    let mut current: Option<f64> = None; // 尚未乘以单位的数
+    let mut last_unit: Option<f64> = None; // 上一个单位，用于 “两百五” 这类省略末尾单位的写法
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        // 阿拉伯数字作为一个整体，如 “1.5万” 中的 1.5
+        let len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        if len > 0 {
+            if current.is_some() {
+                return None;
+            }
+            current = Some(rest[..len].parse().ok()?);
+            rest = &rest[len..];
+            continue;
+        }
+        rest = &rest[c.len_utf8()..];
+        if c == '零' || c == '〇' {
+            last_unit = None;
+        } else if let Some(d) = digit(c) {
+            if current.is_some() {
+                return None;
+            }
+            current = Some(d);
+        } else if let Some(u) = unit(c) {
+            let value = current.take();
+            if u < 1e4 {
+                section += value.unwrap_or(1.0) * u;
+            } else if u < 1e8 {
+                wan = (wan + section + value.unwrap_or(0.0)) * u;
+                section = 0.0;
+            } else {
+                total = (total + wan + section + value.unwrap_or(0.0)) * u;
+                wan = 0.0;
+                section = 0.0;
+            }
+            if value.is_none() && u >= 1e4 && total + wan == 0.0 {
+                return None;
+            }
+            last_unit = Some(u);
+        } else {
+            return None;
+        }
+    }
+    // 末尾的数紧跟在单位之后时省略了下一级单位，如 “一万五” 为 15000
+    let tail = match (current, last_unit) {
+        (Some(d), Some(u)) if u > 10.0 && d < 10.0 => d * u / 10.0,
+        (Some(d), _) => d,
+        (None, _) => 0.0,
+    };
+    Some(total + wan + section + tail)
+}
+
+/// 解析数字，无法解析时返回 None
+pub fn parse_number(s: &str) -> Option<f64> {
+    let s: String = s
+        .trim()
+        .chars()
+        .map(to_half_width)
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    // 先去掉符号再去掉千位分隔符，如 “-1,234”
+    let (sign, s) = match s.strip_prefix(['-', '负']) {
+        Some(s) => (-1.0, s),
+        None => (1.0, s.strip_prefix('+').unwrap_or(&s)),
+    };
+    if s.starts_with(['-', '+']) {
+        return None;
+    }
+    let s = strip_separators(s)?;
+    let s = s.as_str();
+    if let Ok(value) = s.parse::<f64>() {
+        return Some(sign * value).filter(|value| value.is_finite());
+    }
+    // 小数部分，如 “三点一四”
+    let (integer, fraction) = match s.split_once('点') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (s, None),
+    };
+    let mut value = parse_integer(integer)?;
+    if let Some(fraction) = fraction {
+        if fraction.is_empty() {
+            return None;
+        }
+        let mut scale = 0.1;
+        for c in fraction.chars() {
+            value += digit(c).or_else(|| c.to_digit(10).map(f64::from))? * scale;
+            scale /= 10.0;
+        }
+    }
+    Some(sign * value).filter(|value| value.is_finite())
+}
+
+/// 解析整数，值必须为整数且在 i32 范围内
+pub fn parse_integer_number(s: &str) -> Option<i32> {
+    parse_number(s)
+        .filter(|value| value.fract() == 0.0)
+        .filter(|value| *value >= i32::MIN as f64 && *value <= i32::MAX as f64)
+        .map(|value| value as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试数字解析
+    #[test]
+    fn test_parse_number() {
+        let cases = [
+            ("42", 42.0),
+            (" -3.5 ", -3.5),
+            ("１２", 12.0),
+            ("1,234,567", 1234567.0),
+            ("1，234.5", 1234.5),
+            ("-1,234", -1234.0),
+            ("负1,234", -1234.0),
+            ("+1,234", 1234.0),
+            ("十", 10.0),
+            ("十五", 15.0),
+            ("三十二", 32.0),
+            ("两百", 200.0),
+            ("两百五", 250.0),
+            ("一百零五", 105.0),
+            ("一千零二十", 1020.0),
+            ("一万五", 15000.0),
+            ("三千万", 3e7),
+            ("十二万三千四百五十六", 123456.0),
+            ("一亿零一", 100000001.0),
+            ("壹佰贰拾叁", 123.0),
+            ("二零二四", 2024.0),
+            ("1.5万", 15000.0),
+            ("2亿", 2e8),
+            ("3k", 3000.0),
+            ("12万3千", 123000.0),
+            ("负三点五", -3.5),
+            ("三点二五", 3.25),
+            ("零", 0.0),
+        ];
+        for (input, expected) in cases {
+            let value = parse_number(input).unwrap_or_else(|| panic!("{} failed", input));
+            assert!((value - expected).abs() < 1e-9, "{} = {}", input, value);
+        }
+        for input in [
+            "",
+            "abc",
+            "1,23",
+            "12,345,67",
+            "三三十",
+            "万",
+            "一点",
+            "1.2.3",
+            "inf",
+            "--5",
+            "-1,23",
+        ] {
+            assert_eq!(parse_number(input), None, "{}", input);
+        }
+
+        assert_eq!(parse_integer_number("三十二"), Some(32));
+        assert_eq!(parse_integer_number("1.5"), None);
+        assert_eq!(parse_integer_number("三百亿"), None);
+    }
+}
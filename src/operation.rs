@@ -3,16 +3,31 @@
 use ::rand::seq::SliceRandom;
 use ::rand::Rng;
 use ::serde::{Deserialize, Serialize};
+use std::ops::Not;
 
+use crate::context::Context;
+use crate::fuzzy;
+use crate::number::{parse_integer_number, parse_number};
+use crate::pinyin;
+use crate::relative;
+use crate::time::{DatePart, DateTime, TimeUnit};
 use crate::variable::{VariableType, Variables};
 
 /// 操作
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub enum Operation {
-    Add(String, String, String), // a = b + c
-    Sub(String, String, String), // a = b - c
-    Mul(String, String, String), // a = b * c
-    Div(String, String, String), // a = b / c
+    Add(String, String, String),        // a = b + c
+    Sub(String, String, String),        // a = b - c
+    Mul(String, String, String),        // a = b * c
+    Div(String, String, String),        // a = b / c
+    Mod(String, String, String),        // a = b % c
+    Idv(String, String, String),        // a = floor(b / c)
+    Fdv(String, String, String),        // a = float(b) / float(c)
+    Pow(String, String, String),        // a = b ^ c
+    Min(String, String, String),        // a = min(b, c)
+    Max(String, String, String),        // a = max(b, c)
+    Abs(String, String),                // a = abs(b)
+    Rou(String, String, i32, Rounding), // a = round(b, 精度)
 
     Get(String, String, String), // a = b[c]
     Set(String, String, String), // b[c] = a
@@ -21,27 +36,197 @@ pub enum Operation {
     Cpy(String, String),       // a = b
 
     Rnd(String, String, String), // a = random(b, c)
+    Rni(String, String, String), // a = random(b, c)，包含右边界
     Shu(String),                 // shuffle a
+    Pck(String, String),         // a = b.choose()
+    Wgt(String, String),         // a = b.choose_weighted()
 
     Qry(String, String, String), // a = b.query(c)
     Ins(String, String, String), // b.new(a, c)
 
     Inp(String), // a = input()
+
+    Cat(String, String, String),         // a = b + c（字符串拼接）
+    Trm(String, String),                 // a = b.trim()
+    Upp(String, String),                 // a = b.upper()
+    Low(String, String),                 // a = b.lower()
+    Sbs(String, String, String, String), // a = b[c..d]
+    Spl(String, String, String),         // a = b.split(c)
+    Joi(String, String, String),         // a = b.join(c)
+    Len(String, String),                 // a = b.len()
+    Seg(String, String),                 // a = segment(b)
+
+    Psh(String, String),                 // b.push(a)
+    Pop(String, String),                 // a = b.pop()
+    Rmv(String, String, String),         // a = b.remove(c)
+    Srt(String),                         // sort a
+    Has(String, String, String),         // a = b.contains(c)
+    Kys(String, String),                 // a = b.keys()
+    Vls(String, String),                 // a = b.values()
+    Del(String, String),                 // b.delete(a)
+    Fnd(String, String, String, String), // a = b.query(c), d = b.contains(c)
+    Fzq(String, String, String, String, f64), // a = b.query(closest(c)), d = 匹配的键
+    Pyq(String, String, String, String),      // a = b.query(pinyin(c)), d = 匹配的键
+    Faq(String, String, String),              // a = faq.search(c), b = 得分
+
+    Toi(String, String, Rounding), // a = int(b)
+    Tof(String, String),           // a = float(b)
+    Tos(String, String),           // a = str(b)
+    Prs(String, String, String),   // a = parse(b), c = 是否成功
+    Num(String, String, String),   // a = number(b), c = 是否成功
+
+    Now(String),                   // a = now()
+    Fmt(String, String, String),   // a = b.format(格式)
+    Dur(String, String, TimeUnit), // a = duration(b, 单位)
+    Dtp(String, String, DatePart), // a = b.part(组成部分)
+    Nxo(String, String),           // a = next_open(b)
+    Whn(String, String, String),   // a = when(b), c = 0 未找到 / 1 成功 / 2 有歧义
+}
+
+/// 取整方式
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum Rounding {
+    Round, // 四舍五入
+    Floor, // 向下取整
+    Ceil,  // 向上取整
+    Trunc, // 向零取整
 }
 
 /// 操作集合
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct Operations(pub Vec<Operation>);
 
+impl Rounding {
+    /// 按取整方式取整
+    pub fn apply(&self, value: f64) -> f64 {
+        match self {
+            Rounding::Round => value.round(),
+            Rounding::Floor => value.floor(),
+            Rounding::Ceil => value.ceil(),
+            Rounding::Trunc => value.trunc(),
+        }
+    }
+
+    /// 按取整方式取整并转换为整数，值不是有限数或超出 i32 范围时返回 None
+    pub fn apply_int(&self, value: f64) -> Option<i32> {
+        Some(self.apply(value))
+            .filter(|value| *value >= i32::MIN as f64 && *value <= i32::MAX as f64)
+            .map(|value| value as i32)
+    }
+}
+
+// 将字符串解析为整数，小数会按取整方式取整
+fn parse_int(s: &str, rounding: Rounding) -> Option<i32> {
+    let s = s.trim();
+    s.parse::<i32>().ok().or_else(|| {
+        s.parse::<f64>()
+            .ok()
+            .and_then(|f| rounding.apply_int(f))
+    })
+}
+
+// 将字符串解析为浮点数
+fn parse_float(s: &str) -> Option<f64> {
+    s.trim().parse::<f64>().ok()
+}
+
+// 检查数值运算的类型提升，有浮点数参与时结果必须为浮点数
+fn check_promotion(a: &VariableType, operands: &[&VariableType]) -> Result<(), String> {
+    if a.is_number().not() || operands.iter().any(|b| b.is_number().not()) {
+        return Err("Type mismatch".to_string());
+    }
+    if matches!(a, VariableType::Int(_))
+        && operands.iter().any(|b| matches!(b, VariableType::Float(_)))
+    {
+        return Err("Type mismatch".to_string());
+    }
+    Ok(())
+}
+
+// 检查日期时间运算的类型
+// 日期时间加减时长得到日期时间，日期时间相减得到时长，时长之间可以加减
+fn check_time(
+    operation: &Operation,
+    a: &VariableType,
+    b: &VariableType,
+    c: &VariableType,
+) -> Result<(), String> {
+    use VariableType::{DateTime as T, Duration as D};
+    match (operation, a, b, c) {
+        (Operation::Add(..), T(_), T(_), D(_))
+        | (Operation::Add(..), T(_), D(_), T(_))
+        | (Operation::Sub(..), T(_), T(_), D(_))
+        | (Operation::Sub(..), D(_), T(_), T(_))
+        | (Operation::Add(..) | Operation::Sub(..), D(_), D(_), D(_))
+        | (Operation::Min(..) | Operation::Max(..), T(_), T(_), T(_))
+        | (Operation::Min(..) | Operation::Max(..), D(_), D(_), D(_)) => Ok(()),
+        _ => Err("Type mismatch".to_string()),
+    }
+}
+
+// 计算日期时间运算
+fn time_arithmetic(
+    operation: &Operation,
+    b: &VariableType,
+    c: &VariableType,
+) -> Result<VariableType, String> {
+    use VariableType::{DateTime as T, Duration as D};
+    Ok(match (operation, b, c) {
+        (Operation::Add(..), T(b), D(c)) | (Operation::Add(..), D(c), T(b)) => T(DateTime(b.0 + c)),
+        (Operation::Sub(..), T(b), D(c)) => T(DateTime(b.0 - c)),
+        (Operation::Sub(..), T(b), T(c)) => D(b.0 - c.0),
+        (Operation::Add(..), D(b), D(c)) => D(b + c),
+        (Operation::Sub(..), D(b), D(c)) => D(b - c),
+        (Operation::Min(..), T(b), T(c)) => T(*b.min(c)),
+        (Operation::Max(..), T(b), T(c)) => T(*b.max(c)),
+        (Operation::Min(..), D(b), D(c)) => D(*b.min(c)),
+        (Operation::Max(..), D(b), D(c)) => D(*b.max(c)),
+        _ => return Err("Type mismatch".to_string()),
+    })
+}
+
+// 将数值运算结果存入变量，整数可以存入浮点数变量，反之不行，日期时间和时长必须类型一致
+fn assign_number(a: &mut VariableType, value: VariableType) -> Result<(), String> {
+    match (a, value) {
+        (VariableType::Int(a), VariableType::Int(value)) => *a = value,
+        (VariableType::Float(a), VariableType::Int(value)) => *a = value as f64,
+        (VariableType::Float(a), VariableType::Float(value)) => *a = value,
+        (VariableType::DateTime(a), VariableType::DateTime(value)) => *a = value,
+        (VariableType::Duration(a), VariableType::Duration(value)) => *a = value,
+        _ => return Err("Type mismatch".to_string()),
+    }
+    Ok(())
+}
+
+// 检查变量 a 的类型是否与数组 b 的元素类型相同，用于修改数组前的检查
+fn check_element_type(variables: &Variables, a: &str, b: &str) -> Result<(), String> {
+    let a = variables
+        .get(a)
+        .ok_or_else(|| format!("Variable {} not found", a))?;
+    let b = variables
+        .get(b)
+        .ok_or_else(|| format!("Variable {} not found", b))?;
+    match (a, b) {
+        (VariableType::Str(_), VariableType::StrVec(_))
+        | (VariableType::Int(_), VariableType::IntVec(_))
+        | (VariableType::Float(_), VariableType::FloatVec(_)) => Ok(()),
+        _ => Err("Type mismatch".to_string()),
+    }
+}
+
 impl Operation {
     /// 检查操作，类型匹配
     pub fn operation_check(&self, variables: &Variables) -> Result<(), String> {
         match self {
-            // 加减乘除
+            // 四则运算、取模、乘方、最值
             Operation::Add(a, b, c)
             | Operation::Sub(a, b, c)
             | Operation::Mul(a, b, c)
-            | Operation::Div(a, b, c) => {
+            | Operation::Div(a, b, c)
+            | Operation::Mod(a, b, c)
+            | Operation::Pow(a, b, c)
+            | Operation::Min(a, b, c)
+            | Operation::Max(a, b, c) => {
                 let a = variables
                     .get(a)
                     .ok_or_else(|| format!("Variable {} not found", a))?;
@@ -51,7 +236,52 @@ impl Operation {
                 let c = variables
                     .get(c)
                     .ok_or_else(|| format!("Variable {} not found", c))?;
-                if a.is_same_type(b) && a.is_same_type(c) && a.is_number() {
+                if a.is_time() || b.is_time() || c.is_time() {
+                    check_time(self, a, b, c)
+                } else {
+                    check_promotion(a, &[b, c])
+                }
+            }
+            // 整除、浮点除
+            Operation::Idv(a, b, c) | Operation::Fdv(a, b, c) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                match (self, a) {
+                    (Operation::Idv(_, _, _), VariableType::Int(_))
+                    | (Operation::Fdv(_, _, _), VariableType::Float(_))
+                        if b.is_number() && c.is_number() =>
+                    {
+                        Ok(())
+                    }
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 绝对值
+            Operation::Abs(a, b) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                check_promotion(a, &[b])
+            }
+            // 按精度取整
+            Operation::Rou(a, b, _, _) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                if a.is_number() && b.is_number() {
                     Ok(())
                 } else {
                     Err("Type mismatch".to_string())
@@ -115,7 +345,7 @@ impl Operation {
                 }
             }
             // 随机
-            Operation::Rnd(a, b, c) => {
+            Operation::Rnd(a, b, c) | Operation::Rni(a, b, c) => {
                 let b = variables
                     .get(b)
                     .ok_or_else(|| format!("Variable {} not found", b))?;
@@ -125,7 +355,7 @@ impl Operation {
                 let a = variables
                     .get(a)
                     .ok_or_else(|| format!("Variable {} not found", a))?;
-                if (*a).is_same_type(&b) && (*a).is_same_type(&c) {
+                if (*a).is_same_type(b) && (*a).is_same_type(c) {
                     match a {
                         VariableType::Int(_) => Ok(()),
                         VariableType::Float(_) => Ok(()),
@@ -147,6 +377,35 @@ impl Operation {
                     _ => Err("Type mismatch".to_string()),
                 }
             }
+            // 随机选取
+            Operation::Pck(a, b) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Str(_), VariableType::StrVec(_))
+                    | (VariableType::Int(_), VariableType::IntVec(_))
+                    | (VariableType::Float(_), VariableType::FloatVec(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 加权随机选取
+            Operation::Wgt(a, b) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Str(_), VariableType::IntDic(_))
+                    | (VariableType::Str(_), VariableType::FloatDic(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
             // 转换输入
             Operation::Inp(a) => {
                 let a = variables
@@ -177,247 +436,1672 @@ impl Operation {
                     _ => Err("Type mismatch".to_string()),
                 }
             }
-        }
-    }
-
-    /// 计算操作
-    pub fn calculate(&self, variables: &mut Variables) -> Result<(), String> {
-        match self {
-            // 加减乘除
-            Operation::Add(a, b, c)
-            | Operation::Sub(a, b, c)
-            | Operation::Mul(a, b, c)
-            | Operation::Div(a, b, c) => {
+            // 拼接
+            Operation::Cat(a, b, c) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
                 let b = variables
                     .get(b)
-                    .ok_or_else(|| format!("Variable {} not found", b))?
-                    .clone();
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
                 let c = variables
                     .get(c)
-                    .ok_or_else(|| format!("Variable {} not found", c))?
-                    .clone();
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                match (a, b, c) {
+                    (VariableType::Str(_), VariableType::Str(_), VariableType::Str(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 去空白、大小写转换
+            Operation::Trm(a, b) | Operation::Upp(a, b) | Operation::Low(a, b) => {
                 let a = variables
-                    .get_mut(a)
+                    .get(a)
                     .ok_or_else(|| format!("Variable {} not found", a))?;
-                match (a, b, c) {
-                    (VariableType::Int(a), VariableType::Int(b), VariableType::Int(c)) => {
-                        match self {
-                            Operation::Add(_, _, _) => *a = b + c,
-                            Operation::Sub(_, _, _) => *a = b - c,
-                            Operation::Mul(_, _, _) => *a = b * c,
-                            Operation::Div(_, _, _) => *a = b / c,
-                            _ => unreachable!(),
-                        }
-                    }
-                    (VariableType::Float(a), VariableType::Float(b), VariableType::Float(c)) => {
-                        match self {
-                            Operation::Add(_, _, _) => *a = b + c,
-                            Operation::Sub(_, _, _) => *a = b - c,
-                            Operation::Mul(_, _, _) => *a = b * c,
-                            Operation::Div(_, _, _) => *a = b / c,
-                            _ => unreachable!(),
-                        }
-                    }
-                    _ => return Err("Type mismatch".to_string()),
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Str(_), VariableType::Str(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
                 }
             }
-            // 获取
-            Operation::Get(a, b, c) => {
+            // 子串
+            Operation::Sbs(a, b, c, d) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
                 let b = variables
                     .get(b)
-                    .ok_or_else(|| format!("Variable {} not found", b))?
-                    .clone();
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
                 let c = variables
                     .get(c)
-                    .ok_or_else(|| format!("Variable {} not found", c))?
-                    .clone();
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                let d = variables
+                    .get(d)
+                    .ok_or_else(|| format!("Variable {} not found", d))?;
+                match (a, b, c, d) {
+                    (
+                        VariableType::Str(_),
+                        VariableType::Str(_),
+                        VariableType::Int(_),
+                        VariableType::Int(_),
+                    ) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 分割
+            Operation::Spl(a, b, c) => {
                 let a = variables
-                    .get_mut(a)
+                    .get(a)
                     .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
                 match (a, b, c) {
-                    (VariableType::Str(a), VariableType::StrVec(b), VariableType::Int(c)) => {
-                        *a = b[c as usize].clone();
-                    }
-                    (VariableType::Int(a), VariableType::IntVec(b), VariableType::Int(c)) => {
-                        *a = b[c as usize];
-                    }
-                    (VariableType::Float(a), VariableType::FloatVec(b), VariableType::Int(c)) => {
-                        *a = b[c as usize];
-                    }
-                    _ => return Err("Type mismatch".to_string()),
+                    (VariableType::StrVec(_), VariableType::Str(_), VariableType::Str(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
                 }
             }
-            // 设置
-            Operation::Set(a, b, c) => {
+            // 分词
+            Operation::Seg(a, b) => {
                 let a = variables
                     .get(a)
-                    .ok_or_else(|| format!("Variable {} not found", a))?
-                    .clone();
-                let c = variables
-                    .get(c)
-                    .ok_or_else(|| format!("Variable {} not found", c))?
-                    .clone();
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
                 let b = variables
-                    .get_mut(b)
+                    .get(b)
                     .ok_or_else(|| format!("Variable {} not found", b))?;
-                match (a, b, c) {
-                    (VariableType::Str(a), VariableType::StrVec(b), VariableType::Int(c)) => {
-                        b[c as usize] = a.clone();
-                    }
-                    (VariableType::Int(a), VariableType::IntVec(b), VariableType::Int(c)) => {
-                        b[c as usize] = a;
-                    }
-                    (VariableType::Float(a), VariableType::FloatVec(b), VariableType::Int(c)) => {
-                        b[c as usize] = a;
-                    }
-                    _ => return Err("Type mismatch".to_string()),
+                match (a, b) {
+                    (VariableType::StrVec(_), VariableType::Str(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
                 }
             }
-            // 赋值
-            Operation::Let(a, b) => {
+            // 连接
+            Operation::Joi(a, b, c) => {
                 let a = variables
-                    .get_mut(a)
+                    .get(a)
                     .ok_or_else(|| format!("Variable {} not found", a))?;
-                *a = b.clone();
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                match (a, c) {
+                    (VariableType::Str(_), VariableType::Str(_)) if b.is_vector() => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
             }
-            // 复制
-            Operation::Cpy(a, b) => {
+            // 长度
+            Operation::Len(a, b) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
                 let b = variables
                     .get(b)
-                    .ok_or_else(|| format!("Variable {} not found", b))?
-                    .clone();
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Int(_), VariableType::Str(_)) => Ok(()),
+                    (VariableType::Int(_), b) if b.is_vector() || b.is_dict() => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 压入、弹出
+            Operation::Psh(a, b) | Operation::Pop(a, b) => {
                 let a = variables
-                    .get_mut(a)
+                    .get(a)
                     .ok_or_else(|| format!("Variable {} not found", a))?;
-                *a = b;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Str(_), VariableType::StrVec(_))
+                    | (VariableType::Int(_), VariableType::IntVec(_))
+                    | (VariableType::Float(_), VariableType::FloatVec(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
             }
-            // 随机
-            Operation::Rnd(a, b, c) => {
+            // 按下标删除
+            Operation::Rmv(a, b, c) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
                 let b = variables
                     .get(b)
-                    .ok_or_else(|| format!("Variable {} not found", b))?
-                    .clone();
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
                 let c = variables
                     .get(c)
-                    .ok_or_else(|| format!("Variable {} not found", c))?
-                    .clone();
-                let a = variables
-                    .get_mut(a)
-                    .ok_or_else(|| format!("Variable {} not found", a))?;
-                let mut rng = ::rand::thread_rng();
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
                 match (a, b, c) {
-                    (VariableType::Int(a), VariableType::Int(b), VariableType::Int(c)) => {
-                        let l = std::cmp::min(b, c);
-                        let r = std::cmp::max(b, c);
-                        if l == r {
-                            *a = l;
-                        } else {
-                            *a = rng.gen_range(l..r);
-                        }
-                    }
-                    (VariableType::Float(a), VariableType::Float(b), VariableType::Float(c)) => {
-                        let l;
-                        let r;
-                        if b < c {
-                            l = b;
-                            r = c;
-                        } else {
-                            l = c;
-                            r = b;
-                        }
-                        if l == r {
-                            *a = l;
-                        } else {
-                            *a = rng.gen_range(l..r);
-                        }
+                    (VariableType::Str(_), VariableType::StrVec(_), VariableType::Int(_))
+                    | (VariableType::Int(_), VariableType::IntVec(_), VariableType::Int(_))
+                    | (VariableType::Float(_), VariableType::FloatVec(_), VariableType::Int(_)) => {
+                        Ok(())
                     }
-                    _ => return Err("Type mismatch".to_string()),
+                    _ => Err("Type mismatch".to_string()),
                 }
             }
-            // 洗牌
-            Operation::Shu(a) => {
+            // 排序
+            Operation::Srt(a) => {
                 let a = variables
-                    .get_mut(a)
+                    .get(a)
                     .ok_or_else(|| format!("Variable {} not found", a))?;
-                match a {
-                    VariableType::StrVec(a) => a.shuffle(&mut ::rand::thread_rng()),
-                    VariableType::IntVec(a) => a.shuffle(&mut ::rand::thread_rng()),
-                    VariableType::FloatVec(a) => a.shuffle(&mut ::rand::thread_rng()),
-                    _ => return Err("Type mismatch".to_string()),
+                if a.is_vector() {
+                    Ok(())
+                } else {
+                    Err("Type mismatch".to_string())
                 }
             }
-            // 转换输入
-            Operation::Inp(a) => {
-                let input = variables
+            // 包含
+            Operation::Has(a, b, c) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                match (a, b, c) {
+                    (VariableType::Int(_), VariableType::StrVec(_), VariableType::Str(_))
+                    | (VariableType::Int(_), VariableType::IntVec(_), VariableType::Int(_))
+                    | (VariableType::Int(_), VariableType::FloatVec(_), VariableType::Float(_)) => {
+                        Ok(())
+                    }
+                    (VariableType::Int(_), b, VariableType::Str(_)) if b.is_dict() => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 键
+            Operation::Kys(a, b) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::StrVec(_), b) if b.is_dict() => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 值
+            Operation::Vls(a, b) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::StrVec(_), VariableType::StrDic(_))
+                    | (VariableType::IntVec(_), VariableType::IntDic(_))
+                    | (VariableType::FloatVec(_), VariableType::FloatDic(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 删除键
+            Operation::Del(a, b) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Str(_), b) if b.is_dict() => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 带结果的查询
+            Operation::Fnd(a, b, c, d) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                let d = variables
+                    .get(d)
+                    .ok_or_else(|| format!("Variable {} not found", d))?;
+                match (a, b, c, d) {
+                    (
+                        VariableType::Str(_),
+                        VariableType::StrDic(_),
+                        VariableType::Str(_),
+                        VariableType::Int(_),
+                    )
+                    | (
+                        VariableType::Int(_),
+                        VariableType::IntDic(_),
+                        VariableType::Str(_),
+                        VariableType::Int(_),
+                    )
+                    | (
+                        VariableType::Float(_),
+                        VariableType::FloatDic(_),
+                        VariableType::Str(_),
+                        VariableType::Int(_),
+                    ) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 模糊查询
+            Operation::Fzq(a, b, c, d, threshold) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                let d = variables
+                    .get(d)
+                    .ok_or_else(|| format!("Variable {} not found", d))?;
+                if (*threshold > 0.0 && *threshold <= 1.0).not() {
+                    return Err(format!("Invalid threshold: {}", threshold));
+                }
+                match (a, b, c, d) {
+                    (
+                        VariableType::Str(_),
+                        VariableType::StrDic(_),
+                        VariableType::Str(_),
+                        VariableType::Str(_),
+                    )
+                    | (
+                        VariableType::Int(_),
+                        VariableType::IntDic(_),
+                        VariableType::Str(_),
+                        VariableType::Str(_),
+                    )
+                    | (
+                        VariableType::Float(_),
+                        VariableType::FloatDic(_),
+                        VariableType::Str(_),
+                        VariableType::Str(_),
+                    ) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 拼音查询
+            Operation::Pyq(a, b, c, d) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                let d = variables
+                    .get(d)
+                    .ok_or_else(|| format!("Variable {} not found", d))?;
+                match (a, b, c, d) {
+                    (
+                        VariableType::Str(_),
+                        VariableType::StrDic(_),
+                        VariableType::Str(_),
+                        VariableType::Str(_),
+                    )
+                    | (
+                        VariableType::Int(_),
+                        VariableType::IntDic(_),
+                        VariableType::Str(_),
+                        VariableType::Str(_),
+                    )
+                    | (
+                        VariableType::Float(_),
+                        VariableType::FloatDic(_),
+                        VariableType::Str(_),
+                        VariableType::Str(_),
+                    ) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 知识库检索
+            Operation::Faq(a, b, c) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                match (a, b, c) {
+                    (VariableType::Str(_), VariableType::Float(_), VariableType::Str(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 转换为整数
+            Operation::Toi(a, b, _) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Int(_), VariableType::Int(_))
+                    | (VariableType::Int(_), VariableType::Float(_))
+                    | (VariableType::Int(_), VariableType::Str(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 转换为浮点数
+            Operation::Tof(a, b) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Float(_), VariableType::Int(_))
+                    | (VariableType::Float(_), VariableType::Float(_))
+                    | (VariableType::Float(_), VariableType::Str(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 转换为字符串
+            Operation::Tos(a, b) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Str(_), VariableType::Int(_))
+                    | (VariableType::Str(_), VariableType::Float(_))
+                    | (VariableType::Str(_), VariableType::Str(_))
+                    | (VariableType::Str(_), VariableType::DateTime(_))
+                    | (VariableType::Str(_), VariableType::Duration(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 尝试解析
+            Operation::Prs(a, b, c) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                match (a, b, c) {
+                    (VariableType::Int(_), VariableType::Str(_), VariableType::Int(_))
+                    | (VariableType::Float(_), VariableType::Str(_), VariableType::Int(_))
+                    | (VariableType::DateTime(_), VariableType::Str(_), VariableType::Int(_)) => {
+                        Ok(())
+                    }
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 解析数字
+            Operation::Num(a, b, c) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                match (a, b, c) {
+                    (VariableType::Int(_), VariableType::Str(_), VariableType::Int(_))
+                    | (VariableType::Float(_), VariableType::Str(_), VariableType::Int(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 当前时间
+            Operation::Now(a) => {
+                match variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?
+                {
+                    VariableType::DateTime(_) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 格式化日期时间
+            Operation::Fmt(a, b, _) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Str(_), VariableType::DateTime(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 时长与整数转换
+            Operation::Dur(a, b, _) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Duration(_), VariableType::Int(_))
+                    | (VariableType::Int(_), VariableType::Duration(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 日期时间的组成部分
+            Operation::Dtp(a, b, _) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Int(_), VariableType::DateTime(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 下一次营业时间
+            Operation::Nxo(a, b) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::DateTime(_), VariableType::DateTime(_)) => Ok(()),
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+            // 解析日期时间表达
+            Operation::Whn(a, b, c) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                match (a, b, c) {
+                    (VariableType::DateTime(_), VariableType::Str(_), VariableType::Int(_)) => {
+                        Ok(())
+                    }
+                    _ => Err("Type mismatch".to_string()),
+                }
+            }
+        }
+    }
+
+    /// 计算操作，随机和时间操作使用运行时上下文
+    pub fn calculate(
+        &self,
+        variables: &mut Variables,
+        context: &mut Context,
+    ) -> Result<(), String> {
+        match self {
+            // 四则运算、取模、乘方、最值
+            // 两个整数运算结果为整数，有浮点数参与时结果为浮点数
+            Operation::Add(a, b, c)
+            | Operation::Sub(a, b, c)
+            | Operation::Mul(a, b, c)
+            | Operation::Div(a, b, c)
+            | Operation::Mod(a, b, c)
+            | Operation::Pow(a, b, c)
+            | Operation::Min(a, b, c)
+            | Operation::Max(a, b, c) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .clone();
+                let value = match (b, c) {
+                    (VariableType::Int(b), VariableType::Int(c)) => {
+                        if c == 0 && matches!(self, Operation::Div(..) | Operation::Mod(..)) {
+                            return Err("Division by zero".to_string());
+                        }
+                        if c < 0 && matches!(self, Operation::Pow(..)) {
+                            return Err("Negative exponent".to_string());
+                        }
+                        let value = match self {
+                            Operation::Add(_, _, _) => b.checked_add(c),
+                            Operation::Sub(_, _, _) => b.checked_sub(c),
+                            Operation::Mul(_, _, _) => b.checked_mul(c),
+                            Operation::Div(_, _, _) => b.checked_div(c),
+                            Operation::Mod(_, _, _) => b.checked_rem_euclid(c),
+                            Operation::Pow(_, _, _) => b.checked_pow(c as u32),
+                            Operation::Min(_, _, _) => Some(b.min(c)),
+                            Operation::Max(_, _, _) => Some(b.max(c)),
+                            _ => unreachable!(),
+                        };
+                        VariableType::Int(value.ok_or("Integer overflow")?)
+                    }
+                    (b, c) if b.is_time() || c.is_time() => time_arithmetic(self, &b, &c)?,
+                    (b, c) => {
+                        let b = b.to_float().ok_or("Type mismatch")?;
+                        let c = c.to_float().ok_or("Type mismatch")?;
+                        VariableType::Float(match self {
+                            Operation::Add(_, _, _) => b + c,
+                            Operation::Sub(_, _, _) => b - c,
+                            Operation::Mul(_, _, _) => b * c,
+                            Operation::Div(_, _, _) => b / c,
+                            Operation::Mod(_, _, _) => b.rem_euclid(c),
+                            Operation::Pow(_, _, _) => b.powf(c),
+                            Operation::Min(_, _, _) => b.min(c),
+                            Operation::Max(_, _, _) => b.max(c),
+                            _ => unreachable!(),
+                        })
+                    }
+                };
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                assign_number(a, value)?;
+            }
+            // 整除，向下取整
+            Operation::Idv(a, b, c) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .clone();
+                let value = match (b, c) {
+                    (VariableType::Int(b), VariableType::Int(c)) => {
+                        if c == 0 {
+                            return Err("Division by zero".to_string());
+                        }
+                        let q = b.checked_div(c).ok_or("Integer overflow")?;
+                        if b % c != 0 && (b < 0) != (c < 0) {
+                            q - 1
+                        } else {
+                            q
+                        }
+                    }
+                    (b, c) => {
+                        let b = b.to_float().ok_or("Type mismatch")?;
+                        let c = c.to_float().ok_or("Type mismatch")?;
+                        if c == 0.0 {
+                            return Err("Division by zero".to_string());
+                        }
+                        Rounding::Floor.apply_int(b / c).ok_or("Invalid number")?
+                    }
+                };
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match a {
+                    VariableType::Int(a) => *a = value,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 浮点除
+            Operation::Fdv(a, b, c) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .to_float()
+                    .ok_or("Type mismatch")?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .to_float()
+                    .ok_or("Type mismatch")?;
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match a {
+                    VariableType::Float(a) => *a = b / c,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 绝对值
+            Operation::Abs(a, b) => {
+                let value = match variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                {
+                    VariableType::Int(b) => {
+                        VariableType::Int(b.checked_abs().ok_or("Integer overflow")?)
+                    }
+                    VariableType::Float(b) => VariableType::Float(b.abs()),
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                assign_number(a, value)?;
+            }
+            // 按精度取整，精度为保留的小数位数，可以为负数
+            Operation::Rou(a, b, precision, rounding) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .to_float()
+                    .ok_or("Type mismatch")?;
+                let factor = 10f64.powi(*precision);
+                let value = rounding.apply(b * factor) / factor;
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match a {
+                    VariableType::Int(a) => *a = rounding.apply_int(value).ok_or("Invalid number")?,
+                    VariableType::Float(a) => *a = value,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 获取
+            Operation::Get(a, b, c) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b, c) {
+                    (VariableType::Str(a), VariableType::StrVec(b), VariableType::Int(c)) => {
+                        *a = b[c as usize].clone();
+                    }
+                    (VariableType::Int(a), VariableType::IntVec(b), VariableType::Int(c)) => {
+                        *a = b[c as usize];
+                    }
+                    (VariableType::Float(a), VariableType::FloatVec(b), VariableType::Int(c)) => {
+                        *a = b[c as usize];
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 设置
+            Operation::Set(a, b, c) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?
+                    .clone();
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .clone();
+                let b = variables
+                    .get_mut(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b, c) {
+                    (VariableType::Str(a), VariableType::StrVec(b), VariableType::Int(c)) => {
+                        b[c as usize] = a.clone();
+                    }
+                    (VariableType::Int(a), VariableType::IntVec(b), VariableType::Int(c)) => {
+                        b[c as usize] = a;
+                    }
+                    (VariableType::Float(a), VariableType::FloatVec(b), VariableType::Int(c)) => {
+                        b[c as usize] = a;
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 赋值
+            Operation::Let(a, b) => {
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                *a = b.clone();
+            }
+            // 复制
+            Operation::Cpy(a, b) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                *a = b;
+            }
+            // 随机
+            Operation::Rnd(a, b, c) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b, c) {
+                    (VariableType::Int(a), VariableType::Int(b), VariableType::Int(c)) => {
+                        let l = std::cmp::min(b, c);
+                        let r = std::cmp::max(b, c);
+                        if l == r {
+                            *a = l;
+                        } else {
+                            *a = context.rng.gen_range(l..r);
+                        }
+                    }
+                    (VariableType::Float(a), VariableType::Float(b), VariableType::Float(c)) => {
+                        let l;
+                        let r;
+                        if b < c {
+                            l = b;
+                            r = c;
+                        } else {
+                            l = c;
+                            r = b;
+                        }
+                        if l == r {
+                            *a = l;
+                        } else {
+                            *a = context.rng.gen_range(l..r);
+                        }
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 随机，包含右边界
+            Operation::Rni(a, b, c) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b, c) {
+                    (VariableType::Int(a), VariableType::Int(b), VariableType::Int(c)) => {
+                        *a = context.rng.gen_range(b.min(c)..=b.max(c));
+                    }
+                    (VariableType::Float(a), VariableType::Float(b), VariableType::Float(c)) => {
+                        // 边界不是有限数或区间过大时无法生成
+                        if !b.is_finite() || !c.is_finite() || !(c - b).is_finite() {
+                            return Err("Invalid number".to_string());
+                        }
+                        *a = context.rng.gen_range(b.min(c)..=b.max(c));
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 洗牌
+            Operation::Shu(a) => {
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match a {
+                    VariableType::StrVec(a) => a.shuffle(context.rng),
+                    VariableType::IntVec(a) => a.shuffle(context.rng),
+                    VariableType::FloatVec(a) => a.shuffle(context.rng),
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 随机选取
+            Operation::Pck(a, b) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let value = match b {
+                    VariableType::StrVec(b) => {
+                        b.choose(context.rng).cloned().map(VariableType::Str)
+                    }
+                    VariableType::IntVec(b) => {
+                        b.choose(context.rng).copied().map(VariableType::Int)
+                    }
+                    VariableType::FloatVec(b) => {
+                        b.choose(context.rng).copied().map(VariableType::Float)
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+                .ok_or("Empty vector")?;
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                if a.is_same_type(&value).not() {
+                    return Err("Type mismatch".to_string());
+                }
+                *a = value;
+            }
+            // 加权随机选取，权重不大于 0 的键不会被选中
+            Operation::Wgt(a, b) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let mut items = match b {
+                    VariableType::IntDic(b) => b
+                        .iter()
+                        .map(|(k, v)| (k.clone(), *v as f64))
+                        .collect::<Vec<(String, f64)>>(),
+                    VariableType::FloatDic(b) => b.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                // 按键排序，保证相同随机数下结果一致
+                items.retain(|(_, v)| *v > 0.0);
+                items.sort_by(|x, y| x.0.cmp(&y.0));
+                let key = items
+                    .choose_weighted(context.rng, |(_, v)| *v)
+                    .map_err(|_| "No positive weight")?
+                    .0
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match a {
+                    VariableType::Str(a) => *a = key,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 转换输入
+            Operation::Inp(a) => {
+                let input = variables
                     .get("input")
                     .ok_or("Variable input not found")?
                     .clone();
                 let a = variables
                     .get_mut(a)
                     .ok_or_else(|| format!("Variable {} not found", a))?;
-                match (a, input) {
-                    (VariableType::Str(a), VariableType::Str(b)) => *a = b.clone(),
-                    (VariableType::Int(a), VariableType::Str(b)) => {
-                        *a = b.parse().map_err(|_| "Invalid input")?
-                    }
-                    (VariableType::Float(a), VariableType::Str(b)) => {
-                        *a = b.parse().map_err(|_| "Invalid input")?
-                    }
+                match (a, input) {
+                    (VariableType::Str(a), VariableType::Str(b)) => *a = b.clone(),
+                    (VariableType::Int(a), VariableType::Str(b)) => {
+                        *a = parse_integer_number(&b).ok_or("Invalid input")?
+                    }
+                    (VariableType::Float(a), VariableType::Str(b)) => {
+                        *a = parse_number(&b).ok_or("Invalid input")?
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 查询
+            Operation::Qry(a, b, c) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b, c) {
+                    (VariableType::Str(a), VariableType::StrDic(b), VariableType::Str(c)) => {
+                        *a = b.get(&c).cloned().unwrap_or_else(|| "".to_string());
+                    }
+                    (VariableType::Int(a), VariableType::IntDic(b), VariableType::Str(c)) => {
+                        *a = *b.get(&c).unwrap_or(&0);
+                    }
+                    (VariableType::Float(a), VariableType::FloatDic(b), VariableType::Str(c)) => {
+                        *a = *b.get(&c).unwrap_or(&0.0);
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 插入
+            Operation::Ins(a, b, c) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?
+                    .clone();
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .clone();
+                let b = variables
+                    .get_mut(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b, c) {
+                    (VariableType::Str(a), VariableType::StrDic(b), VariableType::Str(c)) => {
+                        b.insert(c.clone(), a.clone());
+                    }
+                    (VariableType::Int(a), VariableType::IntDic(b), VariableType::Str(c)) => {
+                        b.insert(c.clone(), a);
+                    }
+                    (VariableType::Float(a), VariableType::FloatDic(b), VariableType::Str(c)) => {
+                        b.insert(c.clone(), a);
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 拼接
+            Operation::Cat(a, b, c) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b, c) {
+                    (VariableType::Str(a), VariableType::Str(b), VariableType::Str(c)) => {
+                        *a = b + &c;
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 去空白、大小写转换
+            Operation::Trm(a, b) | Operation::Upp(a, b) | Operation::Low(a, b) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b) {
+                    (VariableType::Str(a), VariableType::Str(b)) => match self {
+                        Operation::Trm(_, _) => *a = b.trim().to_string(),
+                        Operation::Upp(_, _) => *a = b.to_uppercase(),
+                        Operation::Low(_, _) => *a = b.to_lowercase(),
+                        _ => unreachable!(),
+                    },
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 子串，按字符计数，越界部分会被截断
+            Operation::Sbs(a, b, c, d) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .clone();
+                let d = variables
+                    .get(d)
+                    .ok_or_else(|| format!("Variable {} not found", d))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b, c, d) {
+                    (
+                        VariableType::Str(a),
+                        VariableType::Str(b),
+                        VariableType::Int(c),
+                        VariableType::Int(d),
+                    ) => {
+                        let l = c.max(0) as usize;
+                        let r = d.max(0) as usize;
+                        *a = if l < r {
+                            b.chars().skip(l).take(r - l).collect()
+                        } else {
+                            "".to_string()
+                        };
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 分割，分隔符为空时按空白分割
+            Operation::Spl(a, b, c) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b, c) {
+                    (VariableType::StrVec(a), VariableType::Str(b), VariableType::Str(c)) => {
+                        *a = if c.is_empty() {
+                            b.split_whitespace().map(|s| s.to_string()).collect()
+                        } else {
+                            b.split(c.as_str()).map(|s| s.to_string()).collect()
+                        };
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 分词，使用内置词典和用户词典
+            Operation::Seg(a, b) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b) {
+                    (VariableType::StrVec(a), VariableType::Str(b)) => {
+                        *a = context.segmenter.segment(&b);
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 连接
+            Operation::Joi(a, b, c) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let items = match b {
+                    VariableType::StrVec(b) => b,
+                    VariableType::IntVec(b) => b.iter().map(|i| i.to_string()).collect(),
+                    VariableType::FloatVec(b) => b.iter().map(|f| f.to_string()).collect(),
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                match (a, c) {
+                    (VariableType::Str(a), VariableType::Str(c)) => *a = items.join(&c),
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 长度，字符串按字符计数
+            Operation::Len(a, b) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let len = match b {
+                    VariableType::Str(b) => b.chars().count(),
+                    VariableType::StrVec(b) => b.len(),
+                    VariableType::IntVec(b) => b.len(),
+                    VariableType::FloatVec(b) => b.len(),
+                    VariableType::StrDic(b) => b.len(),
+                    VariableType::IntDic(b) => b.len(),
+                    VariableType::FloatDic(b) => b.len(),
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                match a {
+                    VariableType::Int(a) => *a = len as i32,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 压入
+            Operation::Psh(a, b) => {
+                let a = variables
+                    .get(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?
+                    .clone();
+                let b = variables
+                    .get_mut(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Str(a), VariableType::StrVec(b)) => b.push(a),
+                    (VariableType::Int(a), VariableType::IntVec(b)) => b.push(a),
+                    (VariableType::Float(a), VariableType::FloatVec(b)) => b.push(a),
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 弹出，先检查类型，类型不匹配时不修改数组
+            Operation::Pop(a, b) => {
+                check_element_type(variables, a, b)?;
+                let value = match variables.get_mut(b) {
+                    Some(VariableType::StrVec(b)) => b.pop().map(VariableType::Str),
+                    Some(VariableType::IntVec(b)) => b.pop().map(VariableType::Int),
+                    Some(VariableType::FloatVec(b)) => b.pop().map(VariableType::Float),
+                    _ => return Err("Type mismatch".to_string()),
+                }
+                .ok_or("Empty vector")?;
+                variables.insert(a.clone(), value);
+            }
+            // 按下标删除，先检查类型和下标，失败时不修改数组
+            Operation::Rmv(a, b, c) => {
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?
+                    .clone();
+                let c = match c {
+                    VariableType::Int(c) => c,
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                check_element_type(variables, a, b)?;
+                let b = variables
+                    .get_mut(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let len = match b {
+                    VariableType::StrVec(b) => b.len(),
+                    VariableType::IntVec(b) => b.len(),
+                    VariableType::FloatVec(b) => b.len(),
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                if c < 0 || c as usize >= len {
+                    return Err("Index out of bounds".to_string());
+                }
+                let value = match b {
+                    VariableType::StrVec(b) => VariableType::Str(b.remove(c as usize)),
+                    VariableType::IntVec(b) => VariableType::Int(b.remove(c as usize)),
+                    VariableType::FloatVec(b) => VariableType::Float(b.remove(c as usize)),
+                    _ => unreachable!(),
+                };
+                variables.insert(a.clone(), value);
+            }
+            // 排序（升序）
+            Operation::Srt(a) => {
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match a {
+                    VariableType::StrVec(a) => a.sort(),
+                    VariableType::IntVec(a) => a.sort(),
+                    VariableType::FloatVec(a) => a.sort_by(|x, y| x.total_cmp(y)),
                     _ => return Err("Type mismatch".to_string()),
                 }
             }
-            // 查询
-            Operation::Qry(a, b, c) => {
+            // 包含，结果为 1 或 0
+            Operation::Has(a, b, c) => {
                 let b = variables
                     .get(b)
-                    .ok_or_else(|| format!("Variable {} not found", b))?
-                    .clone();
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
                 let c = variables
                     .get(c)
-                    .ok_or_else(|| format!("Variable {} not found", c))?
-                    .clone();
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                let found = match (b, c) {
+                    (VariableType::StrVec(b), VariableType::Str(c)) => b.contains(c),
+                    (VariableType::IntVec(b), VariableType::Int(c)) => b.contains(c),
+                    (VariableType::FloatVec(b), VariableType::Float(c)) => b.contains(c),
+                    (VariableType::StrDic(b), VariableType::Str(c)) => b.contains_key(c),
+                    (VariableType::IntDic(b), VariableType::Str(c)) => b.contains_key(c),
+                    (VariableType::FloatDic(b), VariableType::Str(c)) => b.contains_key(c),
+                    _ => return Err("Type mismatch".to_string()),
+                };
                 let a = variables
                     .get_mut(a)
                     .ok_or_else(|| format!("Variable {} not found", a))?;
-                match (a, b, c) {
-                    (VariableType::Str(a), VariableType::StrDic(b), VariableType::Str(c)) => {
-                        *a = b.get(&c).cloned().unwrap_or_else(|| "".to_string());
+                match a {
+                    VariableType::Int(a) => *a = found as i32,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 键，按字典序排列
+            Operation::Kys(a, b) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let mut keys = match b {
+                    VariableType::StrDic(b) => b.keys().cloned().collect::<Vec<String>>(),
+                    VariableType::IntDic(b) => b.keys().cloned().collect(),
+                    VariableType::FloatDic(b) => b.keys().cloned().collect(),
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                keys.sort();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match a {
+                    VariableType::StrVec(a) => *a = keys,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 值，按键的字典序排列
+            Operation::Vls(a, b) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let values = match b {
+                    VariableType::StrDic(b) => {
+                        let mut items = b.iter().collect::<Vec<_>>();
+                        items.sort_by(|x, y| x.0.cmp(y.0));
+                        VariableType::StrVec(items.into_iter().map(|(_, v)| v.clone()).collect())
                     }
-                    (VariableType::Int(a), VariableType::IntDic(b), VariableType::Str(c)) => {
-                        *a = *b.get(&c).unwrap_or(&0);
+                    VariableType::IntDic(b) => {
+                        let mut items = b.iter().collect::<Vec<_>>();
+                        items.sort_by(|x, y| x.0.cmp(y.0));
+                        VariableType::IntVec(items.into_iter().map(|(_, v)| *v).collect())
                     }
-                    (VariableType::Float(a), VariableType::FloatDic(b), VariableType::Str(c)) => {
-                        *a = *b.get(&c).unwrap_or(&0.0);
+                    VariableType::FloatDic(b) => {
+                        let mut items = b.iter().collect::<Vec<_>>();
+                        items.sort_by(|x, y| x.0.cmp(y.0));
+                        VariableType::FloatVec(items.into_iter().map(|(_, v)| *v).collect())
                     }
                     _ => return Err("Type mismatch".to_string()),
+                };
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                if a.is_same_type(&values).not() {
+                    return Err("Type mismatch".to_string());
                 }
+                *a = values;
             }
-            // 插入
-            Operation::Ins(a, b, c) => {
+            // 删除键，键不存在时不做任何操作
+            Operation::Del(a, b) => {
                 let a = variables
                     .get(a)
                     .ok_or_else(|| format!("Variable {} not found", a))?
                     .clone();
+                let b = variables
+                    .get_mut(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                match (a, b) {
+                    (VariableType::Str(a), VariableType::StrDic(b)) => {
+                        b.remove(&a);
+                    }
+                    (VariableType::Str(a), VariableType::IntDic(b)) => {
+                        b.remove(&a);
+                    }
+                    (VariableType::Str(a), VariableType::FloatDic(b)) => {
+                        b.remove(&a);
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 带结果的查询，找到时 d 为 1，否则为 0 且 a 保持不变
+            Operation::Fnd(a, b, c, d) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                let value = match (b, c) {
+                    (VariableType::StrDic(b), VariableType::Str(c)) => {
+                        b.get(c).cloned().map(VariableType::Str)
+                    }
+                    (VariableType::IntDic(b), VariableType::Str(c)) => {
+                        b.get(c).copied().map(VariableType::Int)
+                    }
+                    (VariableType::FloatDic(b), VariableType::Str(c)) => {
+                        b.get(c).copied().map(VariableType::Float)
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                let found = value.is_some();
+                if let Some(value) = value {
+                    let a = variables
+                        .get_mut(a)
+                        .ok_or_else(|| format!("Variable {} not found", a))?;
+                    if a.is_same_type(&value).not() {
+                        return Err("Type mismatch".to_string());
+                    }
+                    *a = value;
+                }
+                let d = variables
+                    .get_mut(d)
+                    .ok_or_else(|| format!("Variable {} not found", d))?;
+                match d {
+                    VariableType::Int(d) => *d = found as i32,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 模糊查询，查找相似度不低于阈值的最相似的键，d 为匹配的键
+            // 找不到时 a 为空字符串或 0，d 为空字符串
+            Operation::Fzq(a, b, c, d, threshold) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
+                let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                let c = match c {
+                    VariableType::Str(c) => c,
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                let (value, key) = match b {
+                    VariableType::StrDic(b) => {
+                        match fuzzy::closest(b.keys().map(String::as_str), c, *threshold) {
+                            Some((key, _)) => (VariableType::Str(b[key].clone()), key.to_string()),
+                            None => (VariableType::Str(String::new()), String::new()),
+                        }
+                    }
+                    VariableType::IntDic(b) => {
+                        match fuzzy::closest(b.keys().map(String::as_str), c, *threshold) {
+                            Some((key, _)) => (VariableType::Int(b[key]), key.to_string()),
+                            None => (VariableType::Int(0), String::new()),
+                        }
+                    }
+                    VariableType::FloatDic(b) => {
+                        match fuzzy::closest(b.keys().map(String::as_str), c, *threshold) {
+                            Some((key, _)) => (VariableType::Float(b[key]), key.to_string()),
+                            None => (VariableType::Float(0.0), String::new()),
+                        }
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                if a.is_same_type(&value).not() {
+                    return Err("Type mismatch".to_string());
+                }
+                *a = value;
+                let d = variables
+                    .get_mut(d)
+                    .ok_or_else(|| format!("Variable {} not found", d))?;
+                match d {
+                    VariableType::Str(d) => *d = key,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 拼音查询，键本身、全拼或首字母与 c 相同即匹配，优先精确匹配，d 为匹配的键
+            // 找不到时 a 为空字符串或 0，d 为空字符串
+            Operation::Pyq(a, b, c, d) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?;
                 let c = variables
+                    .get(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                let c = match c {
+                    VariableType::Str(c) => c,
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                let (value, key) = match b {
+                    VariableType::StrDic(b) => {
+                        match pinyin::closest(b.keys().map(String::as_str), c) {
+                            Some(key) => (VariableType::Str(b[key].clone()), key.to_string()),
+                            None => (VariableType::Str(String::new()), String::new()),
+                        }
+                    }
+                    VariableType::IntDic(b) => {
+                        match pinyin::closest(b.keys().map(String::as_str), c) {
+                            Some(key) => (VariableType::Int(b[key]), key.to_string()),
+                            None => (VariableType::Int(0), String::new()),
+                        }
+                    }
+                    VariableType::FloatDic(b) => {
+                        match pinyin::closest(b.keys().map(String::as_str), c) {
+                            Some(key) => (VariableType::Float(b[key]), key.to_string()),
+                            None => (VariableType::Float(0.0), String::new()),
+                        }
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                if a.is_same_type(&value).not() {
+                    return Err("Type mismatch".to_string());
+                }
+                *a = value;
+                let d = variables
+                    .get_mut(d)
+                    .ok_or_else(|| format!("Variable {} not found", d))?;
+                match d {
+                    VariableType::Str(d) => *d = key,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 知识库检索，a 为得分最高的条目的答案，b 为归一化的得分
+            // 找不到时 a 为空字符串，b 为 0
+            Operation::Faq(a, b, c) => {
+                let (answer, score) = match variables
                     .get(c)
                     .ok_or_else(|| format!("Variable {} not found", c))?
-                    .clone();
+                {
+                    VariableType::Str(c) => context
+                        .faq
+                        .search(c)
+                        .map_or((String::new(), 0.0), |(answer, score)| {
+                            (answer.to_string(), score)
+                        }),
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match a {
+                    VariableType::Str(a) => *a = answer,
+                    _ => return Err("Type mismatch".to_string()),
+                }
                 let b = variables
                     .get_mut(b)
                     .ok_or_else(|| format!("Variable {} not found", b))?;
-                match (a, b, c) {
-                    (VariableType::Str(a), VariableType::StrDic(b), VariableType::Str(c)) => {
-                        b.insert(c.clone(), a.clone());
+                match b {
+                    VariableType::Float(b) => *b = score,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 转换为整数
+            Operation::Toi(a, b, rounding) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b) {
+                    (VariableType::Int(a), VariableType::Int(b)) => *a = b,
+                    (VariableType::Int(a), VariableType::Float(b)) => {
+                        *a = rounding.apply_int(b).ok_or("Invalid number")?
                     }
-                    (VariableType::Int(a), VariableType::IntDic(b), VariableType::Str(c)) => {
-                        b.insert(c.clone(), a.clone());
+                    (VariableType::Int(a), VariableType::Str(b)) => {
+                        *a = parse_int(&b, *rounding).ok_or("Invalid number")?
                     }
-                    (VariableType::Float(a), VariableType::FloatDic(b), VariableType::Str(c)) => {
-                        b.insert(c.clone(), a.clone());
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 转换为浮点数
+            Operation::Tof(a, b) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b) {
+                    (VariableType::Float(a), VariableType::Int(b)) => *a = b as f64,
+                    (VariableType::Float(a), VariableType::Float(b)) => *a = b,
+                    (VariableType::Float(a), VariableType::Str(b)) => {
+                        *a = parse_float(&b).ok_or("Invalid number")?
+                    }
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 转换为字符串，格式与输出一致
+            Operation::Tos(a, b) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b) {
+                    (VariableType::Str(a), b) if b.is_number() || b.is_time() => *a = b.to_string(),
+                    (VariableType::Str(a), VariableType::Str(b)) => *a = b,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 尝试解析，成功时 c 为 1，否则为 0 且 a 保持不变
+            Operation::Prs(a, b, c) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let b = match b {
+                    VariableType::Str(b) => b,
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let success = match a {
+                    VariableType::Int(a) => parse_int(&b, Rounding::Round).map(|b| *a = b),
+                    VariableType::Float(a) => parse_float(&b).map(|b| *a = b),
+                    VariableType::DateTime(a) => DateTime::parse(&b).map(|b| *a = b),
+                    _ => return Err("Type mismatch".to_string()),
+                }
+                .is_some();
+                let c = variables
+                    .get_mut(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                match c {
+                    VariableType::Int(c) => *c = success as i32,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 解析数字，支持中文数字、全角数字、千位分隔符和数量级后缀
+            Operation::Num(a, b, c) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let b = match b {
+                    VariableType::Str(b) => b,
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                let success = match a {
+                    VariableType::Int(a) => parse_integer_number(&b).map(|b| *a = b),
+                    VariableType::Float(a) => parse_number(&b).map(|b| *a = b),
+                    _ => return Err("Type mismatch".to_string()),
+                }
+                .is_some();
+                let c = variables
+                    .get_mut(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                match c {
+                    VariableType::Int(c) => *c = success as i32,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 当前时间
+            Operation::Now(a) => {
+                let now = context.now()?;
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match a {
+                    VariableType::DateTime(a) => *a = now,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 格式化日期时间
+            Operation::Fmt(a, b, pattern) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b) {
+                    (VariableType::Str(a), VariableType::DateTime(b)) => *a = b.format(pattern),
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 时长与整数转换，时长转换为整数时向零取整
+            Operation::Dur(a, b, unit) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b) {
+                    (VariableType::Duration(a), VariableType::Int(b)) => {
+                        *a = b as i64 * unit.seconds()
+                    }
+                    (VariableType::Int(a), VariableType::Duration(b)) => {
+                        *a = i32::try_from(b / unit.seconds()).map_err(|_| "Integer overflow")?
                     }
                     _ => return Err("Type mismatch".to_string()),
                 }
             }
+            // 日期时间的组成部分
+            Operation::Dtp(a, b, part) => {
+                let b = variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                    .clone();
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b) {
+                    (VariableType::Int(a), VariableType::DateTime(b)) => *a = b.part(*part) as i32,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 下一次营业时间，正在营业时为 b 本身
+            Operation::Nxo(a, b) => {
+                let b = match variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                {
+                    VariableType::DateTime(b) => *b,
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                let next = context.calendar.next_open(b)?;
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match a {
+                    VariableType::DateTime(a) => *a = next,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
+            // 解析日期时间表达，相对于当前时间，找不到时 c 为 0 且 a 保持不变
+            Operation::Whn(a, b, c) => {
+                let b = match variables
+                    .get(b)
+                    .ok_or_else(|| format!("Variable {} not found", b))?
+                {
+                    VariableType::Str(b) => relative::resolve(b, context.now()?),
+                    _ => return Err("Type mismatch".to_string()),
+                };
+                let a = variables
+                    .get_mut(a)
+                    .ok_or_else(|| format!("Variable {} not found", a))?;
+                match (a, b) {
+                    (VariableType::DateTime(a), Some(b)) => *a = b.time,
+                    (VariableType::DateTime(_), None) => {}
+                    _ => return Err("Type mismatch".to_string()),
+                }
+                let status = match b {
+                    Some(b) if b.ambiguous => 2,
+                    Some(_) => 1,
+                    None => 0,
+                };
+                let c = variables
+                    .get_mut(c)
+                    .ok_or_else(|| format!("Variable {} not found", c))?;
+                match c {
+                    VariableType::Int(c) => *c = status,
+                    _ => return Err("Type mismatch".to_string()),
+                }
+            }
         }
         Ok(())
     }
@@ -459,9 +2143,13 @@ impl Operations {
     }
 
     /// 计算操作集合
-    pub fn calculate(&self, variables: &mut Variables) -> Result<(), String> {
+    pub fn calculate(
+        &self,
+        variables: &mut Variables,
+        context: &mut Context,
+    ) -> Result<(), String> {
         for operation in &self.0 {
-            operation.calculate(variables)?;
+            operation.calculate(variables, context)?;
         }
         Ok(())
     }
@@ -469,8 +2157,31 @@ impl Operations {
 
 #[cfg(test)]
 mod tests {
+    use ::rand::SeedableRng;
+    use ::rand_chacha::ChaCha8Rng;
+
+    use ::std::sync::LazyLock;
+
+    use crate::faq::Faq;
+    use crate::segment::Segmenter;
+    use crate::time::{Calendar, FixedClock};
+
     use super::*;
 
+    // 空知识库
+    static EMPTY_FAQ: LazyLock<Faq> = LazyLock::new(Faq::default);
+
+    // 创建测试上下文，时钟固定为 1970-01-01 00:00:00，知识库为空
+    fn create_test_context<'a>(rng: &'a mut ChaCha8Rng, calendar: &'a Calendar) -> Context<'a> {
+        Context {
+            rng,
+            clock: &FixedClock(0),
+            calendar,
+            segmenter: Segmenter::builtin(),
+            faq: &EMPTY_FAQ,
+        }
+    }
+
     // 创建测试变量
     fn create_test_variables() -> Variables {
         let mut variables = Variables::new();
@@ -498,217 +2209,923 @@ mod tests {
                 dic
             }),
         );
-        variables
+        variables
+    }
+
+    // 测试操作检查
+    #[test]
+    fn test_operation_check() {
+        let variables = create_test_variables();
+
+        // Add
+        assert_eq!(
+            Operation::Add("int1".to_string(), "int1".to_string(), "int1".to_string())
+                .operation_check(&variables),
+            Ok(())
+        );
+        assert_eq!(
+            Operation::Add("int1".to_string(), "int1".to_string(), "str1".to_string())
+                .operation_check(&variables),
+            Err("Type mismatch".to_string())
+        );
+        // Sub
+        assert_eq!(
+            Operation::Sub("int1".to_string(), "int1".to_string(), "int1".to_string())
+                .operation_check(&variables),
+            Ok(())
+        );
+        assert_eq!(
+            Operation::Sub("int1".to_string(), "int1".to_string(), "str1".to_string())
+                .operation_check(&variables),
+            Err("Type mismatch".to_string())
+        );
+        // Mul
+        assert_eq!(
+            Operation::Mul("int1".to_string(), "int1".to_string(), "int1".to_string())
+                .operation_check(&variables),
+            Ok(())
+        );
+        assert_eq!(
+            Operation::Mul("int1".to_string(), "int1".to_string(), "str1".to_string())
+                .operation_check(&variables),
+            Err("Type mismatch".to_string())
+        );
+        // Div
+        assert_eq!(
+            Operation::Div("int1".to_string(), "int1".to_string(), "int1".to_string())
+                .operation_check(&variables),
+            Ok(())
+        );
+        assert_eq!(
+            Operation::Div("int1".to_string(), "int1".to_string(), "str1".to_string())
+                .operation_check(&variables),
+            Err("Type mismatch".to_string())
+        );
+        // Get
+        assert_eq!(
+            Operation::Get(
+                "str1".to_string(),
+                "str_vec".to_string(),
+                "int1".to_string()
+            )
+            .operation_check(&variables),
+            Ok(())
+        );
+        assert_eq!(
+            Operation::Get(
+                "int1".to_string(),
+                "str_vec".to_string(),
+                "int1".to_string()
+            )
+            .operation_check(&variables),
+            Err("Type mismatch".to_string())
+        );
+        // Set
+        assert_eq!(
+            Operation::Set(
+                "str1".to_string(),
+                "str_vec".to_string(),
+                "int1".to_string()
+            )
+            .operation_check(&variables),
+            Ok(())
+        );
+        assert_eq!(
+            Operation::Set(
+                "int1".to_string(),
+                "str_vec".to_string(),
+                "int1".to_string()
+            )
+            .operation_check(&variables),
+            Err("Type mismatch".to_string())
+        );
+        // Let
+        assert_eq!(
+            Operation::Let("int1".to_string(), VariableType::Int(1)).operation_check(&variables),
+            Ok(())
+        );
+        assert_eq!(
+            Operation::Let("int1".to_string(), VariableType::Str("a".to_string()))
+                .operation_check(&variables),
+            Err("Type mismatch".to_string())
+        );
+        // Cpy
+        assert_eq!(
+            Operation::Cpy("int1".to_string(), "int1".to_string()).operation_check(&variables),
+            Ok(())
+        );
+        assert_eq!(
+            Operation::Cpy("int1".to_string(), "str1".to_string()).operation_check(&variables),
+            Err("Type mismatch".to_string())
+        );
+    }
+
+    // 测试操作计算
+    #[test]
+    fn test_calculate() {
+        let mut variables = create_test_variables();
+        let (mut rng, calendar) = (ChaCha8Rng::seed_from_u64(0), Calendar::default());
+        let mut context = create_test_context(&mut rng, &calendar);
+
+        // Add
+        assert_eq!(
+            Operation::Add("int1".to_string(), "int1".to_string(), "int2".to_string())
+                .calculate(&mut variables, &mut context),
+            Ok(())
+        );
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(3)));
+        // Sub
+        assert_eq!(
+            Operation::Sub("int1".to_string(), "int1".to_string(), "int2".to_string())
+                .calculate(&mut variables, &mut context),
+            Ok(())
+        );
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(1)));
+        // Mul
+        assert_eq!(
+            Operation::Mul("int1".to_string(), "int1".to_string(), "int2".to_string())
+                .calculate(&mut variables, &mut context),
+            Ok(())
+        );
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(2)));
+        // Div
+        assert_eq!(
+            Operation::Div("int1".to_string(), "int1".to_string(), "int2".to_string())
+                .calculate(&mut variables, &mut context),
+            Ok(())
+        );
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(1)));
+        // Get
+        assert_eq!(
+            Operation::Get("str1".to_string(), "str_vec".to_string(), "ptr".to_string())
+                .calculate(&mut variables, &mut context),
+            Ok(())
+        );
+        assert_eq!(
+            variables.get("str1"),
+            Some(&VariableType::Str("b".to_string()))
+        );
+        // Set
+        assert_eq!(
+            Operation::Set("str2".to_string(), "str_vec".to_string(), "ptr".to_string())
+                .calculate(&mut variables, &mut context),
+            Ok(())
+        );
+        assert_eq!(
+            variables.get("str_vec"),
+            Some(&VariableType::StrVec(vec![
+                "a".to_string(),
+                "ab".to_string()
+            ]))
+        );
+        // Let
+        assert_eq!(
+            Operation::Let("int1".to_string(), VariableType::Int(10))
+                .calculate(&mut variables, &mut context),
+            Ok(())
+        );
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(10)));
+        // Cpy
+        assert_eq!(
+            Operation::Cpy("int1".to_string(), "int2".to_string())
+                .calculate(&mut variables, &mut context),
+            Ok(())
+        );
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(2)));
+        // Rnd
+        let _ = Operation::Add("int2".to_string(), "int1".to_string(), "int2".to_string())
+            .calculate(&mut variables, &mut context);
+        assert_eq!(
+            Operation::Rnd("int1".to_string(), "int1".to_string(), "int2".to_string())
+                .calculate(&mut variables, &mut context),
+            Ok(())
+        );
+        assert!(matches!(variables.get("int1"), Some(VariableType::Int(_))));
+        // Shu
+        assert_eq!(
+            Operation::Shu("str_vec".to_string()).calculate(&mut variables, &mut context),
+            Ok(())
+        );
+        // Qry
+        Operation::Let("str1".to_string(), VariableType::Str("a".to_string()))
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(
+            Operation::Qry(
+                "str1".to_string(),
+                "str_dic".to_string(),
+                "str1".to_string()
+            )
+            .calculate(&mut variables, &mut context),
+            Ok(())
+        );
+        assert_eq!(
+            variables.get("str1"),
+            Some(&VariableType::Str("b".to_string()))
+        );
+
+        print!("{:?}", variables);
+    }
+
+    // 测试字符串操作
+    #[test]
+    fn test_string_operation() {
+        let mut variables = create_test_variables();
+        let (mut rng, calendar) = (ChaCha8Rng::seed_from_u64(0), Calendar::default());
+        let mut context = create_test_context(&mut rng, &calendar);
+        variables.insert(
+            "name".to_string(),
+            VariableType::Str("  Sakiko Togawa ".to_string()),
+        );
+        variables.insert("sep".to_string(), VariableType::Str(",".to_string()));
+        variables.insert("empty".to_string(), VariableType::Str("".to_string()));
+
+        // 类型检查
+        assert_eq!(
+            Operation::Cat("str1".to_string(), "str1".to_string(), "int1".to_string())
+                .operation_check(&variables),
+            Err("Type mismatch".to_string())
+        );
+        assert_eq!(
+            Operation::Spl("str_vec".to_string(), "name".to_string(), "sep".to_string())
+                .operation_check(&variables),
+            Ok(())
+        );
+        assert_eq!(
+            Operation::Len("str1".to_string(), "name".to_string()).operation_check(&variables),
+            Err("Type mismatch".to_string())
+        );
+
+        // Seg
+        variables.insert(
+            "sentence".to_string(),
+            VariableType::Str("我想查询订单状态".to_string()),
+        );
+        let seg = Operation::Seg("str_vec".to_string(), "sentence".to_string());
+        assert!(seg.operation_check(&variables).is_ok());
+        seg.calculate(&mut variables, &mut context).unwrap();
+        assert_eq!(
+            variables.get("str_vec"),
+            Some(&VariableType::StrVec(
+                ["我", "想", "查询", "订单", "状态"].map(String::from).to_vec()
+            ))
+        );
+
+        // Trm + Upp + Cat
+        Operation::Trm("str1".to_string(), "name".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(
+            variables.get("str1"),
+            Some(&VariableType::Str("Sakiko Togawa".to_string()))
+        );
+        Operation::Upp("str2".to_string(), "str1".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        Operation::Cat("str2".to_string(), "str2".to_string(), "sep".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(
+            variables.get("str2"),
+            Some(&VariableType::Str("SAKIKO TOGAWA,".to_string()))
+        );
+        // Sbs
+        Operation::Let("int1".to_string(), VariableType::Int(7))
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        Operation::Let("int2".to_string(), VariableType::Int(100))
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        Operation::Sbs(
+            "str2".to_string(),
+            "str1".to_string(),
+            "int1".to_string(),
+            "int2".to_string(),
+        )
+        .calculate(&mut variables, &mut context)
+        .unwrap();
+        assert_eq!(
+            variables.get("str2"),
+            Some(&VariableType::Str("Togawa".to_string()))
+        );
+        // Spl + Joi + Len
+        Operation::Spl(
+            "str_vec".to_string(),
+            "name".to_string(),
+            "empty".to_string(),
+        )
+        .calculate(&mut variables, &mut context)
+        .unwrap();
+        assert_eq!(
+            variables.get("str_vec"),
+            Some(&VariableType::StrVec(vec![
+                "Sakiko".to_string(),
+                "Togawa".to_string()
+            ]))
+        );
+        Operation::Joi("str2".to_string(), "str_vec".to_string(), "sep".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(
+            variables.get("str2"),
+            Some(&VariableType::Str("Sakiko,Togawa".to_string()))
+        );
+        Operation::Let(
+            "str1".to_string(),
+            VariableType::Str("丰川祥子".to_string()),
+        )
+        .calculate(&mut variables, &mut context)
+        .unwrap();
+        Operation::Len("int1".to_string(), "str1".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(4)));
     }
 
-    // 测试操作检查
+    // 测试集合操作
     #[test]
-    fn test_operation_check() {
-        let variables = create_test_variables();
+    fn test_collection_operation() {
+        let mut variables = create_test_variables();
+        let (mut rng, calendar) = (ChaCha8Rng::seed_from_u64(0), Calendar::default());
+        let mut context = create_test_context(&mut rng, &calendar);
+        variables.insert("found".to_string(), VariableType::Int(0));
+        variables.insert("key".to_string(), VariableType::Str("a".to_string()));
 
-        // Add
+        // 类型检查
         assert_eq!(
-            Operation::Add("int1".to_string(), "int1".to_string(), "int1".to_string())
-                .operation_check(&variables),
-            Ok(())
-        );
-        assert_eq!(
-            Operation::Add("int1".to_string(), "int1".to_string(), "str1".to_string())
-                .operation_check(&variables),
+            Operation::Psh("str1".to_string(), "int_vec".to_string()).operation_check(&variables),
             Err("Type mismatch".to_string())
         );
-        // Sub
         assert_eq!(
-            Operation::Sub("int1".to_string(), "int1".to_string(), "int1".to_string())
-                .operation_check(&variables),
+            Operation::Has(
+                "found".to_string(),
+                "str_dic".to_string(),
+                "key".to_string()
+            )
+            .operation_check(&variables),
             Ok(())
         );
         assert_eq!(
-            Operation::Sub("int1".to_string(), "int1".to_string(), "str1".to_string())
+            Operation::Vls("int_vec".to_string(), "str_dic".to_string())
                 .operation_check(&variables),
             Err("Type mismatch".to_string())
         );
-        // Mul
+
+        // Psh + Srt + Pop + Rmv + Len
+        Operation::Let("int1".to_string(), VariableType::Int(0))
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        Operation::Psh("int1".to_string(), "int_vec".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        Operation::Srt("int_vec".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
         assert_eq!(
-            Operation::Mul("int1".to_string(), "int1".to_string(), "int1".to_string())
-                .operation_check(&variables),
-            Ok(())
+            variables.get("int_vec"),
+            Some(&VariableType::IntVec(vec![0, 1, 2, 3]))
         );
+        Operation::Pop("int2".to_string(), "int_vec".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int2"), Some(&VariableType::Int(3)));
+        Operation::Rmv("int2".to_string(), "int_vec".to_string(), "ptr".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int2"), Some(&VariableType::Int(1)));
         assert_eq!(
-            Operation::Mul("int1".to_string(), "int1".to_string(), "str1".to_string())
-                .operation_check(&variables),
+            Operation::Rmv(
+                "int2".to_string(),
+                "int_vec".to_string(),
+                "int_vec".to_string()
+            )
+            .calculate(&mut variables, &mut context),
             Err("Type mismatch".to_string())
         );
-        // Div
+        // 类型不匹配时不修改数组
+        for operation in [
+            Operation::Pop("str1".to_string(), "int_vec".to_string()),
+            Operation::Rmv("str1".to_string(), "int_vec".to_string(), "ptr".to_string()),
+        ] {
+            assert_eq!(
+                operation.calculate(&mut variables, &mut context),
+                Err("Type mismatch".to_string())
+            );
+        }
         assert_eq!(
-            Operation::Div("int1".to_string(), "int1".to_string(), "int1".to_string())
-                .operation_check(&variables),
-            Ok(())
+            variables.get("int_vec"),
+            Some(&VariableType::IntVec(vec![0, 2]))
         );
+        Operation::Len("int1".to_string(), "int_vec".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(2)));
+
+        // Has + Fnd + Del + Kys
+        Operation::Has(
+            "found".to_string(),
+            "str_dic".to_string(),
+            "key".to_string(),
+        )
+        .calculate(&mut variables, &mut context)
+        .unwrap();
+        assert_eq!(variables.get("found"), Some(&VariableType::Int(1)));
+        Operation::Fnd(
+            "str1".to_string(),
+            "str_dic".to_string(),
+            "key".to_string(),
+            "found".to_string(),
+        )
+        .calculate(&mut variables, &mut context)
+        .unwrap();
         assert_eq!(
-            Operation::Div("int1".to_string(), "int1".to_string(), "str1".to_string())
-                .operation_check(&variables),
-            Err("Type mismatch".to_string())
+            variables.get("str1"),
+            Some(&VariableType::Str("b".to_string()))
         );
-        // Get
+        Operation::Del("key".to_string(), "str_dic".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        Operation::Fnd(
+            "str1".to_string(),
+            "str_dic".to_string(),
+            "key".to_string(),
+            "found".to_string(),
+        )
+        .calculate(&mut variables, &mut context)
+        .unwrap();
+        assert_eq!(variables.get("found"), Some(&VariableType::Int(0)));
         assert_eq!(
-            Operation::Get(
-                "str1".to_string(),
-                "str_vec".to_string(),
-                "int1".to_string()
-            )
-            .operation_check(&variables),
-            Ok(())
+            variables.get("str1"),
+            Some(&VariableType::Str("b".to_string()))
+        );
+
+        // Fzq
+        let mut courses = std::collections::HashMap::new();
+        courses.insert("算法设计与分析".to_string(), "周一".to_string());
+        courses.insert("操作系统".to_string(), "周二".to_string());
+        variables.insert("courses".to_string(), VariableType::StrDic(courses));
+        variables.insert(
+            "course".to_string(),
+            VariableType::Str("算法设计与分折".to_string()),
+        );
+        variables.insert("matched".to_string(), VariableType::Str(String::new()));
+        let fzq = Operation::Fzq(
+            "str1".to_string(),
+            "courses".to_string(),
+            "course".to_string(),
+            "matched".to_string(),
+            0.8,
         );
+        assert!(fzq.operation_check(&variables).is_ok());
+        fzq.calculate(&mut variables, &mut context).unwrap();
         assert_eq!(
-            Operation::Get(
-                "int1".to_string(),
-                "str_vec".to_string(),
-                "int1".to_string()
-            )
-            .operation_check(&variables),
-            Err("Type mismatch".to_string())
+            variables.get("str1"),
+            Some(&VariableType::Str("周一".to_string()))
         );
-        // Set
         assert_eq!(
-            Operation::Set(
-                "str1".to_string(),
-                "str_vec".to_string(),
-                "int1".to_string()
-            )
-            .operation_check(&variables),
-            Ok(())
+            variables.get("matched"),
+            Some(&VariableType::Str("算法设计与分析".to_string()))
+        );
+        variables.insert("course".to_string(), VariableType::Str("篮球".to_string()));
+        fzq.calculate(&mut variables, &mut context).unwrap();
+        assert_eq!(variables.get("str1"), Some(&VariableType::Str(String::new())));
+        assert_eq!(variables.get("matched"), Some(&VariableType::Str(String::new())));
+
+        // Pyq
+        let pyq = Operation::Pyq(
+            "str1".to_string(),
+            "courses".to_string(),
+            "course".to_string(),
+            "matched".to_string(),
         );
+        assert!(pyq.operation_check(&variables).is_ok());
+        variables.insert("course".to_string(), VariableType::Str("caozuoxitong".to_string()));
+        pyq.calculate(&mut variables, &mut context).unwrap();
         assert_eq!(
-            Operation::Set(
-                "int1".to_string(),
-                "str_vec".to_string(),
-                "int1".to_string()
-            )
-            .operation_check(&variables),
-            Err("Type mismatch".to_string())
+            variables.get("str1"),
+            Some(&VariableType::Str("周二".to_string()))
         );
-        // Let
         assert_eq!(
-            Operation::Let("int1".to_string(), VariableType::Int(1)).operation_check(&variables),
-            Ok(())
+            variables.get("matched"),
+            Some(&VariableType::Str("操作系统".to_string()))
         );
+        variables.insert("course".to_string(), VariableType::Str("sfsjyfx".to_string()));
+        pyq.calculate(&mut variables, &mut context).unwrap();
         assert_eq!(
-            Operation::Let("int1".to_string(), VariableType::Str("a".to_string()))
-                .operation_check(&variables),
-            Err("Type mismatch".to_string())
+            variables.get("str1"),
+            Some(&VariableType::Str("周一".to_string()))
         );
-        // Cpy
+        variables.insert("course".to_string(), VariableType::Str("lanqiu".to_string()));
+        pyq.calculate(&mut variables, &mut context).unwrap();
+        assert_eq!(variables.get("str1"), Some(&VariableType::Str(String::new())));
+        assert_eq!(variables.get("matched"), Some(&VariableType::Str(String::new())));
+        Operation::Kys("str_vec".to_string(), "str_dic".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
         assert_eq!(
-            Operation::Cpy("int1".to_string(), "int1".to_string()).operation_check(&variables),
-            Ok(())
+            variables.get("str_vec"),
+            Some(&VariableType::StrVec(vec![]))
         );
         assert_eq!(
-            Operation::Cpy("int1".to_string(), "str1".to_string()).operation_check(&variables),
-            Err("Type mismatch".to_string())
+            Operation::Pop("str1".to_string(), "str_vec".to_string())
+                .calculate(&mut variables, &mut context),
+            Err("Empty vector".to_string())
         );
     }
 
-    // 测试操作计算
+    // 测试知识库检索
     #[test]
-    fn test_calculate() {
+    fn test_faq_operation() {
         let mut variables = create_test_variables();
+        let (mut rng, calendar) = (ChaCha8Rng::seed_from_u64(0), Calendar::default());
+        let faq: Faq = serde_yaml::from_str(
+            "entries:\n  - { question: 怎么申请退款, answer: 在订单页申请 }\n  - { question: 发货要多久, answer: 48 小时内 }",
+        )
+        .unwrap();
+        let mut context = Context {
+            faq: &faq,
+            ..create_test_context(&mut rng, &calendar)
+        };
+        variables.insert("score".to_string(), VariableType::Float(0.0));
+        let operation = Operation::Faq("str1".to_string(), "score".to_string(), "str2".to_string());
+        assert!(operation.operation_check(&variables).is_ok());
+        assert_eq!(
+            Operation::Faq("str1".to_string(), "int1".to_string(), "str2".to_string())
+                .operation_check(&variables),
+            Err("Type mismatch".to_string())
+        );
 
-        // Add
+        variables.insert("str2".to_string(), VariableType::Str("我要退款".to_string()));
+        operation.calculate(&mut variables, &mut context).unwrap();
         assert_eq!(
-            Operation::Add("int1".to_string(), "int1".to_string(), "int2".to_string())
-                .calculate(&mut variables),
-            Ok(())
+            variables.get("str1"),
+            Some(&VariableType::Str("在订单页申请".to_string()))
         );
-        assert_eq!(variables.get("int1"), Some(&VariableType::Int(3)));
-        // Sub
+        assert!(matches!(variables.get("score"), Some(VariableType::Float(score)) if *score > 0.0));
+        variables.insert("str2".to_string(), VariableType::Str("你好".to_string()));
+        operation.calculate(&mut variables, &mut context).unwrap();
+        assert_eq!(variables.get("str1"), Some(&VariableType::Str(String::new())));
+        assert_eq!(variables.get("score"), Some(&VariableType::Float(0.0)));
+    }
+
+    // 测试类型转换
+    #[test]
+    fn test_conversion_operation() {
+        let mut variables = create_test_variables();
+        let (mut rng, calendar) = (ChaCha8Rng::seed_from_u64(0), Calendar::default());
+        let mut context = create_test_context(&mut rng, &calendar);
+        variables.insert("ok".to_string(), VariableType::Int(0));
+        variables.insert("float3".to_string(), VariableType::Float(2.5));
+
+        // 类型检查
         assert_eq!(
-            Operation::Sub("int1".to_string(), "int1".to_string(), "int2".to_string())
-                .calculate(&mut variables),
-            Ok(())
+            Operation::Toi("float1".to_string(), "str1".to_string(), Rounding::Round)
+                .operation_check(&variables),
+            Err("Type mismatch".to_string())
         );
-        assert_eq!(variables.get("int1"), Some(&VariableType::Int(1)));
-        // Mul
         assert_eq!(
-            Operation::Mul("int1".to_string(), "int1".to_string(), "int2".to_string())
-                .calculate(&mut variables),
+            Operation::Prs("int1".to_string(), "str1".to_string(), "ok".to_string())
+                .operation_check(&variables),
             Ok(())
         );
+
+        // Toi
+        Operation::Toi("int1".to_string(), "float3".to_string(), Rounding::Floor)
+            .calculate(&mut variables, &mut context)
+            .unwrap();
         assert_eq!(variables.get("int1"), Some(&VariableType::Int(2)));
-        // Div
+        Operation::Toi("int1".to_string(), "float3".to_string(), Rounding::Ceil)
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(3)));
         assert_eq!(
-            Operation::Div("int1".to_string(), "int1".to_string(), "int2".to_string())
-                .calculate(&mut variables),
-            Ok(())
+            Operation::Toi("int1".to_string(), "str1".to_string(), Rounding::Round)
+                .calculate(&mut variables, &mut context),
+            Err("Invalid number".to_string())
         );
-        assert_eq!(variables.get("int1"), Some(&VariableType::Int(1)));
-        // Get
+        // 超出 i32 范围或不是有限数时报错，不截断
+        for value in [1e10, f64::NAN, f64::NEG_INFINITY] {
+            variables.insert("float3".to_string(), VariableType::Float(value));
+            assert_eq!(
+                Operation::Toi("int1".to_string(), "float3".to_string(), Rounding::Round)
+                    .calculate(&mut variables, &mut context),
+                Err("Invalid number".to_string())
+            );
+        }
+        variables.insert("str1".to_string(), VariableType::Str("3e10".to_string()));
         assert_eq!(
-            Operation::Get("str1".to_string(), "str_vec".to_string(), "ptr".to_string())
-                .calculate(&mut variables),
-            Ok(())
+            Operation::Toi("int1".to_string(), "str1".to_string(), Rounding::Round)
+                .calculate(&mut variables, &mut context),
+            Err("Invalid number".to_string())
         );
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(3)));
+        // Tos + Tof
+        Operation::Tos("str1".to_string(), "int1".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
         assert_eq!(
             variables.get("str1"),
-            Some(&VariableType::Str("b".to_string()))
+            Some(&VariableType::Str("3".to_string()))
         );
-        // Set
+        Operation::Tof("float1".to_string(), "str1".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("float1"), Some(&VariableType::Float(3.0)));
+        // Prs
+        Operation::Prs("int2".to_string(), "str2".to_string(), "ok".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("ok"), Some(&VariableType::Int(0)));
+        assert_eq!(variables.get("int2"), Some(&VariableType::Int(2)));
+        Operation::Prs("int2".to_string(), "str1".to_string(), "ok".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("ok"), Some(&VariableType::Int(1)));
+        assert_eq!(variables.get("int2"), Some(&VariableType::Int(3)));
+
+        // Num
+        variables.insert("amount".to_string(), VariableType::Str("1.5万".to_string()));
+        let num = Operation::Num("float1".to_string(), "amount".to_string(), "ok".to_string());
+        assert!(num.operation_check(&variables).is_ok());
+        num.calculate(&mut variables, &mut context).unwrap();
+        assert_eq!(variables.get("ok"), Some(&VariableType::Int(1)));
+        assert_eq!(variables.get("float1"), Some(&VariableType::Float(15000.0)));
+        variables.insert("amount".to_string(), VariableType::Str("两百五".to_string()));
+        Operation::Num("int2".to_string(), "amount".to_string(), "ok".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int2"), Some(&VariableType::Int(250)));
+        for amount in ["很多", "1.5"] {
+            variables.insert("amount".to_string(), VariableType::Str(amount.to_string()));
+            Operation::Num("int2".to_string(), "amount".to_string(), "ok".to_string())
+                .calculate(&mut variables, &mut context)
+                .unwrap();
+            assert_eq!(variables.get("ok"), Some(&VariableType::Int(0)));
+            assert_eq!(variables.get("int2"), Some(&VariableType::Int(250)));
+        }
+
+        // Inp
+        variables.insert("input".to_string(), VariableType::Str("三十二".to_string()));
+        Operation::Inp("int2".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int2"), Some(&VariableType::Int(32)));
+        variables.insert("input".to_string(), VariableType::Str("１２.５".to_string()));
+        Operation::Inp("float1".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("float1"), Some(&VariableType::Float(12.5)));
         assert_eq!(
-            Operation::Set("str2".to_string(), "str_vec".to_string(), "ptr".to_string())
-                .calculate(&mut variables),
+            Operation::Inp("int2".to_string()).calculate(&mut variables, &mut context),
+            Err("Invalid input".to_string())
+        );
+    }
+
+    // 测试扩展数值运算
+    #[test]
+    fn test_numeric_operation() {
+        let mut variables = create_test_variables();
+        let (mut rng, calendar) = (ChaCha8Rng::seed_from_u64(0), Calendar::default());
+        let mut context = create_test_context(&mut rng, &calendar);
+        variables.insert("neg".to_string(), VariableType::Int(-7));
+        variables.insert("zero".to_string(), VariableType::Int(0));
+        variables.insert("price".to_string(), VariableType::Float(12.345));
+
+        // 类型提升检查
+        assert_eq!(
+            Operation::Add(
+                "float1".to_string(),
+                "int1".to_string(),
+                "float2".to_string()
+            )
+            .operation_check(&variables),
             Ok(())
         );
         assert_eq!(
-            variables.get("str_vec"),
-            Some(&VariableType::StrVec(vec![
-                "a".to_string(),
-                "ab".to_string()
-            ]))
+            Operation::Add("int1".to_string(), "int1".to_string(), "float2".to_string())
+                .operation_check(&variables),
+            Err("Type mismatch".to_string())
         );
-        // Let
         assert_eq!(
-            Operation::Let("int1".to_string(), VariableType::Int(10)).calculate(&mut variables),
-            Ok(())
+            Operation::Idv("float1".to_string(), "int1".to_string(), "int2".to_string())
+                .operation_check(&variables),
+            Err("Type mismatch".to_string())
         );
-        assert_eq!(variables.get("int1"), Some(&VariableType::Int(10)));
-        // Cpy
+
+        // 混合运算
+        Operation::Mul(
+            "float1".to_string(),
+            "int2".to_string(),
+            "price".to_string(),
+        )
+        .calculate(&mut variables, &mut context)
+        .unwrap();
+        assert_eq!(variables.get("float1"), Some(&VariableType::Float(24.69)));
+        // Mod + Idv + Fdv
+        Operation::Mod("int1".to_string(), "neg".to_string(), "int2".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(1)));
+        Operation::Idv("int1".to_string(), "neg".to_string(), "int2".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(-4)));
+        variables.insert("big".to_string(), VariableType::Float(1e10));
+        variables.insert("inf".to_string(), VariableType::Float(f64::INFINITY));
+        for (b, c) in [("big", "int2"), ("inf", "inf")] {
+            assert_eq!(
+                Operation::Idv("int1".to_string(), b.to_string(), c.to_string())
+                    .calculate(&mut variables, &mut context),
+                Err("Invalid number".to_string())
+            );
+        }
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(-4)));
+        Operation::Fdv("float1".to_string(), "neg".to_string(), "int2".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("float1"), Some(&VariableType::Float(-3.5)));
         assert_eq!(
-            Operation::Cpy("int1".to_string(), "int2".to_string()).calculate(&mut variables),
-            Ok(())
+            Operation::Div("int1".to_string(), "int2".to_string(), "zero".to_string())
+                .calculate(&mut variables, &mut context),
+            Err("Division by zero".to_string())
         );
-        assert_eq!(variables.get("int1"), Some(&VariableType::Int(2)));
-        // Rnd
-        let _ = Operation::Add("int2".to_string(), "int1".to_string(), "int2".to_string())
-            .calculate(&mut variables);
+        // Pow + Min + Max + Abs
+        Operation::Pow("int1".to_string(), "int2".to_string(), "int2".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(4)));
+        Operation::Min("int1".to_string(), "int1".to_string(), "neg".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(-7)));
+        Operation::Max("int1".to_string(), "int1".to_string(), "zero".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(0)));
+        Operation::Abs("int1".to_string(), "neg".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(7)));
+        // Rou
+        Operation::Rou(
+            "float1".to_string(),
+            "price".to_string(),
+            2,
+            Rounding::Round,
+        )
+        .calculate(&mut variables, &mut context)
+        .unwrap();
+        assert_eq!(variables.get("float1"), Some(&VariableType::Float(12.35)));
+        Operation::Rou("int1".to_string(), "price".to_string(), -1, Rounding::Ceil)
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(20)));
+        variables.insert("price".to_string(), VariableType::Float(3e9));
         assert_eq!(
-            Operation::Rnd("int1".to_string(), "int1".to_string(), "int2".to_string())
-                .calculate(&mut variables),
-            Ok(())
+            Operation::Rou("int1".to_string(), "price".to_string(), 0, Rounding::Round)
+                .calculate(&mut variables, &mut context),
+            Err("Invalid number".to_string())
         );
-        assert!(matches!(variables.get("int1"), Some(VariableType::Int(_))));
-        // Shu
+    }
+
+    // 测试随机选取
+    #[test]
+    fn test_random_choice_operation() {
+        let mut variables = create_test_variables();
+        let (mut rng, calendar) = (ChaCha8Rng::seed_from_u64(0), Calendar::default());
+        let mut context = create_test_context(&mut rng, &calendar);
+        variables.insert(
+            "weight".to_string(),
+            VariableType::IntDic({
+                let mut dic = std::collections::HashMap::new();
+                dic.insert("a".to_string(), 0);
+                dic.insert("b".to_string(), 3);
+                dic
+            }),
+        );
+
+        // 类型检查
         assert_eq!(
-            Operation::Shu("str_vec".to_string()).calculate(&mut variables),
-            Ok(())
+            Operation::Pck("int1".to_string(), "str_vec".to_string()).operation_check(&variables),
+            Err("Type mismatch".to_string())
         );
-        // Qry
-        Operation::Let("str1".to_string(), VariableType::Str("a".to_string()))
-            .calculate(&mut variables)
-            .unwrap();
         assert_eq!(
-            Operation::Qry(
-                "str1".to_string(),
-                "str_dic".to_string(),
-                "str1".to_string()
-            )
-            .calculate(&mut variables),
-            Ok(())
+            Operation::Wgt("str1".to_string(), "str_dic".to_string()).operation_check(&variables),
+            Err("Type mismatch".to_string())
         );
+
+        // Rni 包含右边界
+        Operation::Rni("int1".to_string(), "int2".to_string(), "int2".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(2)));
+        variables.insert("max".to_string(), VariableType::Float(f64::MAX));
+        for bound in [f64::INFINITY, f64::NAN, -f64::MAX] {
+            variables.insert("bound".to_string(), VariableType::Float(bound));
+            assert_eq!(
+                Operation::Rni("float1".to_string(), "bound".to_string(), "max".to_string())
+                    .calculate(&mut variables, &mut context),
+                Err("Invalid number".to_string())
+            );
+        }
+        // Pck
+        Operation::Pck("int1".to_string(), "int_vec".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert!(matches!(
+            variables.get("int1"),
+            Some(VariableType::Int(1..=3))
+        ));
+        // Wgt 只会选中权重为正的键
+        Operation::Wgt("str1".to_string(), "weight".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
         assert_eq!(
             variables.get("str1"),
             Some(&VariableType::Str("b".to_string()))
         );
+    }
 
-        print!("{:?}", variables);
+    // 测试日期时间操作
+    #[test]
+    fn test_time_operation() {
+        let mut variables = create_test_variables();
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let calendar: Calendar =
+            serde_yaml::from_str("utc_offset: +08:00\nopening_hours:\n  Thu: [09:00-18:00]\n")
+                .unwrap();
+        let mut context = create_test_context(&mut rng, &calendar);
+        variables.insert("now".to_string(), VariableType::DateTime(DateTime(0)));
+        variables.insert("open".to_string(), VariableType::DateTime(DateTime(0)));
+        variables.insert("wait".to_string(), VariableType::Duration(0));
+
+        // 类型检查
+        assert_eq!(
+            Operation::Add("now".to_string(), "now".to_string(), "now".to_string())
+                .operation_check(&variables),
+            Err("Type mismatch".to_string())
+        );
+        assert_eq!(
+            Operation::Sub("wait".to_string(), "open".to_string(), "now".to_string())
+                .operation_check(&variables),
+            Ok(())
+        );
+
+        // Now 使用时钟和时区，1970-01-01 为周四
+        Operation::Now("now".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        Operation::Fmt(
+            "str1".to_string(),
+            "now".to_string(),
+            "%H:%M %a".to_string(),
+        )
+        .calculate(&mut variables, &mut context)
+        .unwrap();
+        assert_eq!(
+            variables.get("str1"),
+            Some(&VariableType::Str("08:00 周四".to_string()))
+        );
+        // Nxo + Sub + Dur
+        Operation::Nxo("open".to_string(), "now".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        Operation::Sub("wait".to_string(), "open".to_string(), "now".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("wait"), Some(&VariableType::Duration(3600)));
+        Operation::Dur("int1".to_string(), "wait".to_string(), TimeUnit::Minute)
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(60)));
+        // Add + Dtp
+        Operation::Add("now".to_string(), "now".to_string(), "wait".to_string())
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        Operation::Dtp("int1".to_string(), "now".to_string(), DatePart::Hour)
+            .calculate(&mut variables, &mut context)
+            .unwrap();
+        assert_eq!(variables.get("int1"), Some(&VariableType::Int(9)));
+        // Whn 相对于当前时间（1970-01-01 08:00）解析
+        let whn = |text: &str, variables: &mut Variables, context: &mut Context| {
+            variables.insert("str1".to_string(), VariableType::Str(text.to_string()));
+            Operation::Whn("open".to_string(), "str1".to_string(), "int1".to_string())
+                .calculate(variables, context)
+                .unwrap();
+            (variables.get("open").cloned(), variables.get("int1").cloned())
+        };
+        let time = |s: &str| Some(VariableType::DateTime(DateTime::parse(s).unwrap()));
+        assert_eq!(
+            whn("明天下午三点", &mut variables, &mut context),
+            (time("1970-01-02 15:00"), Some(VariableType::Int(1)))
+        );
+        assert_eq!(
+            whn("周五十点", &mut variables, &mut context),
+            (time("1970-01-02 10:00"), Some(VariableType::Int(2)))
+        );
+        assert_eq!(
+            whn("你好", &mut variables, &mut context),
+            (time("1970-01-02 10:00"), Some(VariableType::Int(0)))
+        );
     }
 }
@@ -1,63 +1,87 @@
 /// Sakiko 的输出
 /// 输出类包含了一个字符串模板和一个字符串数组，字符串数组中的字符串是变量名，字符串模板中的 {} 会被替换为变量的值（类似 format! 宏）。
+/// 模板中也可以用 {变量名} 直接引用变量，用 {:格式} 或 {变量名:格式} 指定格式，{{ 和 }} 表示字面的花括号。
+/// 模板还支持过滤器、条件和循环，加载时编译一次，语法见 template 模块。
+/// 一个步骤可以有多个带权重的候选输出，按选取策略使用会话的随机数生成器选择。
+use ::rand::seq::SliceRandom;
+use ::rand_chacha::ChaCha8Rng;
 use ::serde::{Deserialize, Serialize};
 
+use crate::template::Template;
 use crate::variable::Variables;
 
-/// 输出类
+/// 输出类（序列化为模板和变量数组）
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
-pub struct Output(pub  String, pub Vec<String>);
+#[serde(from = "(String, Vec<String>)", into = "(String, Vec<String>)")]
+pub struct Output {
+    template: String,
+    vars: Vec<String>,
+    compiled: Result<Template, String>, // 编译结果，错误留待检测时报告
+}
+
+impl From<(String, Vec<String>)> for Output {
+    fn from((template, vars): (String, Vec<String>)) -> Self {
+        Self::new(template, vars)
+    }
+}
+
+impl From<Output> for (String, Vec<String>) {
+    fn from(output: Output) -> Self {
+        (output.template, output.vars)
+    }
+}
 
 /// 输出类的默认实现（实现 yaml 文件中可以不写该字段）
 impl Default for Output {
     fn default() -> Self {
-        Self("".to_string(), Vec::new())
+        Self::new("".to_string(), Vec::new())
     }
 }
 
 impl Output {
-    /// 创建一个新的输出类
+    /// 创建一个新的输出类，同时编译模板
     pub fn new(name: String, vars: Vec<String>) -> Self {
-        Self(name, vars)
+        let compiled = Template::compile(&name);
+        Self {
+            template: name,
+            vars,
+            compiled,
+        }
     }
 
     /// 判断是否为空
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.template.is_empty()
     }
 
     /// 格式化输出
     pub fn fmt(&self, variables: &Variables) -> Result<String, &'static str> {
-        for var in &self.1 {
+        for var in &self.vars {
             if variables.get(var).is_none() {
                 return Err("Variable not found");
             }
         }
 
-        let vars = self.1.iter().map(|var| variables.get(var).unwrap().to_string()).collect::<Vec<String>>();
-
-        let mut result = String::new();
-        let mut format_iter = self.0.split("{}");
-
-        if let Some(f) = format_iter.next() {
-            result.push_str(f);
-        }
-
-        for (arg, var) in format_iter.zip(vars) {
-            result.push_str(&var);
-            result.push_str(arg);
-        }
-
-        Ok(result)
+        let template = self.compiled.as_ref().map_err(|_| "Invalid template")?;
+        template.render(&self.vars, variables)
     }
 
-    /// 检查变量是否存在（检测用，会报告所有不存在的变量）
+    /// 检查模板和变量（检测用，会报告所有错误）
     pub fn check(&self, variables: &Variables) -> Result<(), String> {
         let mut result = Vec::new();
-        for var in &self.1 {
-            if variables.get(var).is_none() {
-                result.push(format!("Variable not found: {}", var));
+        match &self.compiled {
+            Ok(template) => {
+                result.extend(template.check(&self.vars, variables));
+                // 位置占位符与变量数组的数量必须一致
+                if template.positional() != self.vars.len() {
+                    result.push(format!(
+                        "Placeholder count mismatch: {} placeholders, {} variables",
+                        template.positional(),
+                        self.vars.len()
+                    ));
+                }
             }
+            Err(err) => result.push(err.clone()),
         }
         if !result.is_empty() {
             return Err(result.join(", "));
@@ -66,6 +90,97 @@ impl Output {
     }
 }
 
+/// 单行模板（用于消息元素），只能使用命名占位符
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(from = "String", into = "String")]
+pub struct Label(Output);
+
+impl From<String> for Label {
+    fn from(template: String) -> Self {
+        Self(Output::new(template, Vec::new()))
+    }
+}
+
+impl From<Label> for String {
+    fn from(label: Label) -> Self {
+        label.0.template
+    }
+}
+
+impl Label {
+    /// 格式化输出
+    pub fn fmt(&self, variables: &Variables) -> Result<String, &'static str> {
+        self.0.fmt(variables)
+    }
+
+    /// 检查模板和变量（检测用，会报告所有错误）
+    pub fn check(&self, variables: &Variables) -> Result<(), String> {
+        self.0.check(variables)
+    }
+}
+
+/// 候选输出
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Variant {
+    pub description: Output, // 输出
+    #[serde(default = "Variant::default_weight")]
+    pub weight: u32, // 权重（可置空，默认为 1）
+}
+
+impl Variant {
+    // 默认权重
+    fn default_weight() -> u32 {
+        1
+    }
+}
+
+/// 候选输出的选取策略
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy, Default)]
+pub enum Policy {
+    #[default]
+    Random, // 按权重随机
+    RoundRobin, // 依次轮换，忽略权重
+    NoRepeat,   // 按权重随机，但不与上一次相同
+}
+
+impl Policy {
+    /// 是否为默认策略（序列化用）
+    pub fn is_default(&self) -> bool {
+        *self == Policy::default()
+    }
+
+    /// 选取候选输出，传入各候选的权重和上一次选取的序号，返回选取的序号
+    /// 权重均为 0 时返回 0（由检测报告）
+    pub fn choose(&self, weights: &[u32], last: Option<usize>, rng: &mut ChaCha8Rng) -> usize {
+        if weights.len() <= 1 {
+            return 0;
+        }
+        let positive = (0..weights.len())
+            .filter(|i| weights[*i] > 0)
+            .collect::<Vec<usize>>();
+        let candidates = match self {
+            Policy::RoundRobin => return last.map_or(0, |last| (last + 1) % weights.len()),
+            Policy::Random => positive,
+            Policy::NoRepeat => {
+                let fresh = positive
+                    .iter()
+                    .copied()
+                    .filter(|i| Some(*i) != last)
+                    .collect::<Vec<usize>>();
+                if fresh.is_empty() {
+                    positive
+                } else {
+                    fresh
+                }
+            }
+        };
+        candidates
+            .choose_weighted(rng, |i| weights[*i])
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::variable::{VariableType, Variables};
@@ -84,4 +199,66 @@ mod tests {
 
         assert_eq!(output.fmt(&variables).unwrap(), "1 + 1 = 2");
     }
-}
\ No newline at end of file
+
+    // 测试命名占位符、格式说明和转义
+    #[test]
+    fn test_fmt_spec() {
+        let mut variables = Variables::new();
+        variables.insert("name".to_string(), VariableType::Str("Sakiko".to_string()));
+        variables.insert("price".to_string(), VariableType::Float(-1234567.891));
+        variables.insert("count".to_string(), VariableType::Int(42));
+
+        let output = Output::new("{name}: {price:,.2} {{{}}}".to_string(), vec!["count".to_string()]);
+        assert_eq!(output.fmt(&variables).unwrap(), "Sakiko: -1,234,567.89 {42}");
+
+        let output = Output::new("[{count:05}] [{count:<4}] [{count:*^6}] [{name:.3}] [{name:>8}]".to_string(), Vec::new());
+        assert_eq!(output.fmt(&variables).unwrap(), "[00042] [42  ] [**42**] [Sak] [  Sakiko]");
+
+        let output = Output::new("{price:.0}".to_string(), Vec::new());
+        assert_eq!(output.fmt(&variables).unwrap(), "-1234568");
+
+        // 变量不足、模板非法
+        let output = Output::new("{} {}".to_string(), vec!["count".to_string()]);
+        assert_eq!(output.fmt(&variables), Err("Not enough variables"));
+        let output = Output::new("{count".to_string(), Vec::new());
+        assert_eq!(output.fmt(&variables), Err("Invalid template"));
+    }
+
+    // 测试模板检测
+    #[test]
+    fn test_check() {
+        let mut variables = Variables::new();
+        variables.insert("num1".to_string(), VariableType::Int(1));
+
+        assert!(Output::new("{} {num1:>4}".to_string(), vec!["num1".to_string()]).check(&variables).is_ok());
+        assert!(Output::new("{} {}".to_string(), vec!["num1".to_string()]).check(&variables).is_err());
+        assert!(Output::new("{}".to_string(), vec!["num1".to_string(), "num1".to_string()]).check(&variables).is_err());
+        assert!(Output::new("{num2}".to_string(), Vec::new()).check(&variables).is_err());
+        assert!(Output::new("{num1:x}".to_string(), Vec::new()).check(&variables).is_err());
+        assert!(Output::new("}".to_string(), Vec::new()).check(&variables).is_err());
+    }
+
+    // 测试候选输出的选取策略
+    #[test]
+    fn test_policy() {
+        use rand::SeedableRng;
+
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let weights = [1, 0, 3];
+
+        let mut last = None;
+        for _ in 0..20 {
+            let i = Policy::NoRepeat.choose(&weights, last, &mut rng);
+            assert_ne!(i, 1);
+            assert_ne!(Some(i), last);
+            last = Some(i);
+        }
+        for _ in 0..20 {
+            assert_ne!(Policy::Random.choose(&weights, None, &mut rng), 1);
+        }
+        assert_eq!(Policy::RoundRobin.choose(&weights, None, &mut rng), 0);
+        assert_eq!(Policy::RoundRobin.choose(&weights, Some(0), &mut rng), 1);
+        assert_eq!(Policy::RoundRobin.choose(&weights, Some(2), &mut rng), 0);
+        assert_eq!(Policy::Random.choose(&[0, 0], None, &mut rng), 0);
+    }
+}
@@ -0,0 +1,565 @@
+/// Sakiko 的拼音匹配
+/// 内置 GB2312 汉字的拼音表（每个字取一个常用读音，不带声调，ü 写作 v），不依赖网络。
+/// 开启拼音匹配后，中文文本除了与自身匹配，还与其全拼和首字母匹配，如“退出”可以用 tuichu 或 tc 匹配。
+/// 匹配时忽略大小写、空白和隔音符号（'），不在表中的字符保持不变。
+// 拼音表，每项为拼音后接该拼音的所有汉字，各项以空格分隔
+const PINYIN: &str = "\
+    a吖锕阿嗄啊 \
+    ai哀哎唉埃挨捱诶锿癌皑嗳矮蔼霭嗌嫒暧爱瑷砹碍艾隘 \
+    an安庵桉氨谙鞍鹌俺埯揞铵岸按暗案犴胺黯 \
+    ang肮昂盎 \
+    ao凹坳熬嗷廒敖獒翱聱螯遨鏖骜鳌拗媪袄傲奥岙懊澳鏊 \
+    ba八叭吧岜巴扒捌疤笆粑芭拔茇菝跋魃把钯靶坝灞爸罢耙霸鲅 \
+    bai掰白佰捭摆百柏拜稗败 \
+    ban扳搬斑班瘢癍般颁坂板版舨钣阪伴办半扮拌瓣绊 \
+    bang帮梆浜邦榜绑膀傍棒磅蒡蚌谤镑 \
+    bao剥勹包孢煲胞苞褒龅薄雹保堡宝葆褓饱鸨刨报抱暴爆豹趵鲍 \
+    bei卑悲杯碑陂鹎北倍备孛悖惫焙狈碚背蓓被褙贝辈邶鐾钡鞴呗 \
+    ben奔贲锛本畚苯坌笨 \
+    beng嘣崩绷甭堋泵甏蹦迸 \
+    bi逼荸鼻匕吡妣彼比秕俾笔舭鄙哔壁婢嬖币庇庳弊弼必愎敝毕毖毙滗濞狴璧畀痹碧筚箅篦臂芘\
+    荜蓖蔽薜襞跸辟避铋闭陛萆裨髀 \
+    bian煸砭笾编蝙边鞭鳊匾扁碥窆褊贬便卞变弁忭汴缏苄辨辩辫遍 \
+    biao彪杓标灬瘭膘镖镳飑飙飚骠髟婊表裱鳔 \
+    bie憋瘪鳖别蹩 \
+    bin傧宾彬斌槟滨濒玢缤豳镔摈殡膑髌鬓 \
+    bing兵冫冰丙柄炳禀秉邴饼并摒病 \
+    bo啵拨播波玻菠钵饽亳伯勃博帛搏檗泊渤礴箔脖膊舶踣铂驳鹁簸跛擘钹 \
+    bu卜晡逋钸不醭卟哺捕补埔埠布怖步瓿簿部钚 \
+    ca嚓擦礤 \
+    cai猜才材裁财彩睬踩采菜蔡 \
+    can参餐骖惭残蚕惨黪孱灿璨粲 \
+    cang仓伧沧舱苍藏 \
+    cao操糙嘈曹槽漕艚螬艹草 \
+    ce侧册厕恻测策 \
+    cen岑涔 \
+    ceng噌层曾蹭 \
+    cha叉喳插杈锸馇察搽查楂槎檫猹碴茬茶衩镲刹姹岔差汊诧 \
+    chai拆钗侪柴豺瘥虿 \
+    chan掺搀觇婵廛潺澶禅缠蝉蟾谗躔镡馋产冁蒇谄铲阐骣忏颤羼 \
+    chang伥娼昌猖菖阊鲳偿嫦尝常徜肠苌裳长厂场惝敞昶氅倡唱怅畅鬯 \
+    chao怊抄超钞嘲巢晁潮吵炒耖 \
+    che砗车扯坼屮彻掣撤澈 \
+    chen嗔抻琛郴宸尘忱晨沉臣谌辰陈碜榇衬谶趁龀 \
+    cheng撑柽称瞠蛏铛丞乘呈城埕塍惩成承晟枨橙澄程裎诚酲铖逞骋秤 \
+    chi吃哧嗤媸痴眵笞蚩螭魑鸱匙坻墀弛持池篪茌踟迟驰侈尺耻褫豉齿傺叱啻彳敕斥炽瘛翅赤饬 \
+    chong充冲忡憧舂艟茺崇虫宠铳 \
+    chou抽瘳仇俦帱惆愁畴稠筹绸踌酬雠丑瞅臭 \
+    chu出初樗刍厨橱滁蜍蹰躇锄除雏储憷杵楚楮础褚亍处怵搐畜矗绌触黜 \
+    chuai揣搋嘬膪踹 \
+    chuan巛川氚穿传椽舡船遄喘舛串钏 \
+    chuang疮窗幢床疒闯创怆 \
+    chui吹炊垂捶棰椎槌锤陲 \
+    chun春椿蝽唇淳纯莼醇鹑蠢 \
+    chuo戳踔啜辍辶绰龊 \
+    ci呲疵慈瓷磁祠粢糍茈茨词辞雌鹚此伺刺次赐 \
+    cong匆囱枞璁聪苁葱骢丛从淙琮 \
+    cou凑楱腠辏 \
+    cu粗徂殂促猝簇蔟蹙蹴酢醋 \
+    cuan撺汆蹿镩攒爨窜篡 \
+    cui催崔摧榱璀啐悴毳淬瘁粹翠脆萃 \
+    cun村皴存忖寸 \
+    cuo搓撮磋蹉嵯痤矬鹾脞厝挫措锉错 \
+    da哒嗒搭答耷褡妲怛沓瘩笪达靼鞑打大 \
+    dai呆呔傣歹逮代埭岱带待怠戴殆玳甙绐袋贷迨骀黛 \
+    dan丹儋单殚瘅眈箪耽聃郸掸胆但啖惮担疸赕旦氮淡澹萏蛋诞 \
+    dang当裆党挡谠凼宕档砀荡菪 \
+    dao刀刂叨忉氘倒导岛捣祷蹈到悼焘盗稻纛道 \
+    de得德锝的 \
+    deng噔灯登簦蹬镫戥等凳嶝瞪磴邓 \
+    di低嘀堤氐滴羝镝嫡敌涤狄笛籴翟荻觌迪底抵柢砥诋邸骶地娣帝弟棣睇碲第缔蒂谛递 \
+    dia嗲 \
+    dian巅掂滇癫颠典点碘踮佃坫垫奠店惦殿淀玷电甸癜簟钿阽靛 \
+    diao凋刁叼碉貂雕鲷吊掉调钓铞铫 \
+    die爹跌叠喋垤堞揲牒瓞碟耋蝶谍蹀迭鲽 \
+    ding丁仃叮玎町疔盯耵酊钉顶鼎啶定碇腚订铤锭 \
+    diu丢铥 \
+    dong东冬咚岽氡鸫懂董侗冻动垌峒恫栋洞硐胨胴 \
+    dou兜篼蔸都抖斗蚪陡痘窦豆逗 \
+    du嘟督椟毒渎牍犊独读髑黩堵睹笃赌妒度杜渡肚芏蠹镀 \
+    duan端短断椴段煅簖缎锻 \
+    dui堆兑对怼憝碓镦队 \
+    dun吨墩敦礅蹲盹趸囤沌炖盾砘遁钝顿 \
+    duo咄哆多掇裰夺踱铎哚垛朵缍躲剁堕惰柁舵跺 \
+    e婀屙俄娥峨莪蛾讹锇额鹅厄呃噩垩愕扼腭苊萼谔轭恶遏鄂锷阏颚饿鳄鹗 \
+    en恩蒽摁 \
+    er儿而鲕鸸尔洱珥耳迩铒饵二佴贰 \
+    fa发乏伐垡筏罚阀法砝珐 \
+    fan帆幡番翻蕃藩凡樊烦燔矾繁蘩蹯钒反返梵泛犯畈范贩饭 \
+    fang匚坊方枋芳邡钫妨房肪防鲂仿彷纺舫访放 \
+    fei啡妃扉绯菲蜚霏非飞鲱淝肥腓匪悱斐榧篚翡诽吠废怫沸狒痱砩肺芾费镄 \
+    fen分吩氛纷芬酚坟棼汾焚鼢粉份偾奋忿愤瀵粪鲼 \
+    feng丰封峰枫沣烽疯砜葑蜂酆锋风冯缝逢唪讽俸凤奉 \
+    fo佛 \
+    fou否缶 \
+    fu呋夫孵敷稃肤趺跗麸伏俘凫匐孚幅幞弗扶拂服桴氟浮涪祓福符绂绋艴芙苻罘茯莩菔蚨蜉蝠袱\
+    辐郛黻俯呒府抚拊斧滏甫脯腐腑辅釜黼付傅副咐复妇富父缚腹蝮覆讣负赋赙赴阜阝附馥驸鲋鳆 \
+    ga伽呷嘎旮噶尜钆尕尬 \
+    gai垓该赅陔改丐戤概溉盖钙 \
+    gan坩尴干杆柑泔甘疳矸竿肝苷酐感擀敢橄澉秆赶旰淦绀赣 \
+    gang冈刚岗杠纲缸罡肛钢港戆筻 \
+    gao槔皋睾篙糕羔膏高搞杲槁稿缟藁镐告诰郜锆 \
+    ge仡割咯哥圪戈搁歌疙纥胳袼鸽嗝塥搿格膈葛蛤铬镉阁隔革骼鬲哿舸个各虼硌 \
+    gei给 \
+    gen根跟哏艮亘茛 \
+    geng庚羹耕赓哽埂梗绠耿鲠更 \
+    gong供公功宫工弓恭攻肱蚣觥躬龚巩廾拱汞珙共贡 \
+    gou佝勾枸沟篝缑钩鞲岣狗笱苟垢够媾彀构觏诟购遘 \
+    gu估呱咕姑孤沽痼箍菇菰蛄觚轱辜酤钴鸪古嘏毂汩牯瞽罟股臌蛊诂谷骨鹄鹘鼓固崮故梏牿锢雇\
+    顾鲴 \
+    gua刮栝瓜胍鸹剐寡卦挂褂诖 \
+    guai乖拐怪 \
+    guan倌关官棺莞观鳏管馆冠惯掼涫灌盥罐贯鹳 \
+    guang光咣桄胱广犷逛 \
+    gui傀圭妫归瑰皈硅规闺鲑龟匦宄庋晷癸簋诡轨鬼刽刿柜桂桧炅炔贵跪鳜 \
+    gun丨滚磙绲衮辊鲧棍 \
+    guo呙埚崞聒蝈郭锅国帼掴虢馘果椁猓蜾裹过 \
+    ha哈铪 \
+    hai咳嗨孩还骸海胲醢亥害氦骇 \
+    han憨蚶酣顸鼾函含寒晗涵焓邗邯韩喊罕悍憾捍撖撼旱汉汗瀚焊翰菡颔 \
+    hang夯杭绗航颃沆 \
+    hao嚆蒿薅蚝嗥嚎壕毫濠豪貉好郝号昊浩灏皓耗颢 \
+    he呵喝嗬诃何劾合和曷核河涸盍盒禾翮荷蚵阂菏颌阖壑褐贺赫鹤 \
+    hei嘿黑 \
+    hen痕很狠恨 \
+    heng亨哼恒横珩桁蘅衡 \
+    hong哄烘薨訇轰宏弘泓洪红荭蕻虹讧闳鸿黉 \
+    hou侯喉猴瘊篌糇骺吼候厚后堠後逅鲎 \
+    hu乎呼唿忽惚滹烀猢虍轷囫壶弧斛槲湖煳狐瑚糊胡葫蝴觳醐鹕唬浒琥虎互冱岵怙户戽扈护沪瓠\
+    祜笏鹱 \
+    hua哗砉花华滑猾铧骅划化桦画话 \
+    huai徊怀槐淮踝坏 \
+    huan欢獾圜寰桓洹环缳萑郇锾鬟缓唤奂宦幻患换擐浣涣漶焕痪豢逭鲩 \
+    huang慌肓荒凰徨惶湟潢煌璜癀皇磺篁簧蝗蟥遑隍鳇黄幌恍晃谎 \
+    hui咴徽恢挥晖灰珲虺诙辉隳麾回洄茴蛔悔毁会卉哕喙彗恚惠慧晦汇烩秽绘缋荟蕙蟪讳浍诲贿 \
+    hun婚昏荤阍浑混馄魂溷诨 \
+    huo劐耠锪豁活钬伙夥火嚯惑或攉祸获藿蠖货镬霍 \
+    ji丌乩击剞叽咭唧圾基墼姬屐嵇机激犄玑畸畿矶积稽笄箕绩缉羁肌芨虮讥赍跻迹饥鸡齑亟佶即\
+    及吉嫉岌嵴急戢极棘楫殛汲疾瘠笈籍级脊蒺蕺藉辑集几己戟挤掎麂伎偈冀剂哜妓季寂寄彐忌悸技既暨\
+    洎济祭稷纪继芰荠蓟觊计记跽际霁骥髻鲚鲫 \
+    jia佳加嘉夹家枷浃珈痂笳茄葭袈跏迦镓恝戛荚蛱袷郏铗颊假岬甲瘕胛贾钾价嫁架稼驾 \
+    jian兼坚奸尖戋搛歼湔溅煎犍监笺缄缣肩艰菅蒹间鞯鲣鹣俭减剪囝戬拣捡枧柬检睑硷碱笕简翦茧\
+    裥謇谫趼蹇锏件健僭剑建楗槛毽涧渐牮箭腱舰荐见谏贱践踺鉴键饯 \
+    jiang僵姜将江浆疆礓缰茳豇奖桨耩蒋讲匠洚犟糨绛酱降 \
+    jiao交僬姣娇教椒浇湫焦矫礁胶艽茭蕉蛟跤郊骄鲛鹪嚼佼侥徼挢搅敫狡皎绞缴脚角铰饺剿叫噍峤\
+    窖轿较酵醮 \
+    jie喈嗟接揭疖皆街阶劫卩婕孑截拮捷杰桀洁睫碣竭结羯节讦诘秸颉鲒姐解介借届戒芥界疥蚧诫\
+    骱 \
+    jin今巾斤津筋衿襟金钅矜仅卺堇廑槿瑾紧谨锦馑噤妗尽劲晋浸烬禁缙荩觐赆近进靳 \
+    jing京兢惊旌晶泾睛粳精经腈茎荆菁鲸井儆刭憬景肼警阱净颈境婧弪径敬獍痉竞竟胫迳镜靓靖静 \
+    jiong冂扃炯窘迥 \
+    jiu啾揪究纠赳阄鬏鸠久九灸玖酒韭僦厩咎就救旧柩桕疚臼舅鹫 \
+    ju俱居拘掬椐狙琚疽苴裾趄锔雎鞠鞫驹局橘菊举咀榉榘沮矩莒踽龃倨具剧句屦巨惧拒据炬犋窭\
+    聚苣菹桔讵距踞遽醵钜锯飓 \
+    juan娟捐涓蠲镌鹃卷锩倦桊狷眷绢鄄隽 \
+    jue噘撅倔决劂厥噱孓崛抉掘攫桷橛爝爵獗珏矍绝蕨觉觖诀谲蹶镢 \
+    jun军君均皲筠菌钧麇俊峻捃浚竣郡骏 \
+    ka咔咖喀佧卡胩 \
+    kai开揩锎凯剀垲恺慨楷蒈铠锴忾 \
+    kan刊勘堪戡龛侃凵坎砍莰看瞰阚 \
+    kang康慷糠闶扛亢伉抗炕钪 \
+    kao尻拷栲烤考铐犒靠 \
+    ke钶嗑坷柯棵珂疴瞌磕科稞窠苛蝌轲颏颗髁壳可岢渴克刻客恪氪溘缂课锞骒 \
+    ken啃垦恳肯龈裉 \
+    keng吭坑铿 \
+    kong倥崆箜孔恐控空 \
+    kou抠眍芤口叩寇扣筘蔻 \
+    ku刳哭枯堀窟骷苦喾库绔裤酷 \
+    kua夸侉垮挎胯跨 \
+    kuai蒯侩哙块快筷脍郐狯 \
+    kuan宽髋款 \
+    kuang匡哐框筐诓狂诳夼况圹旷眶矿纩贶邝 \
+    kui亏岿悝盔窥喹夔奎揆暌睽葵蝰逵隗馗魁跬匮喟愦愧溃蒉篑聩馈 \
+    kun坤昆琨醌锟髡鲲悃捆阃困 \
+    kuo廓扩括蛞阔 \
+    la喇垃拉邋剌旯砬瘌腊蜡辣啦 \
+    lai崃徕来涞莱铼濑癞睐籁赉赖 \
+    lan兰婪岚拦斓栏澜篮蓝褴谰镧阑懒揽榄漤缆罱览滥烂 \
+    lang啷廊榔狼琅稂莨螂郎锒阆朗浪蒗 \
+    lao捞劳唠崂涝牢痨醪铹佬姥栳潦老铑烙耢酪 \
+    le嘞肋乐仂叻泐鳓了 \
+    lei勒擂嫘檑缧羸镭雷儡垒磊耒蕾诔泪类累酹 \
+    leng棱塄楞冷愣 \
+    li厘喱嫠梨漓犁狸璃离篱缡罹蓠藜蜊蠡骊鲡鹂黎黧俚哩娌李澧理礼逦醴里锂鲤鳢丽例俐俪傈利\
+    力励历厉吏呖唳坜戾枥栗沥溧猁疠疬痢砺砾立笠篥粒粝苈荔莅莉蛎詈跞轹郦隶栎雳 \
+    lian奁帘廉怜涟濂联臁莲蔹蠊裢连镰鲢敛琏脸裣恋楝殓潋炼练链 \
+    liang凉墚梁椋粮粱良量两俩魉亮晾谅踉辆 \
+    liao撩僚嘹寥寮燎獠疗缭聊辽鹩蓼钌尥廖撂料镣 \
+    lie咧冽列劣埒捩洌烈猎裂趔躐鬣 \
+    lin临啉嶙林淋琳瞵磷粼辚遴邻霖鳞麟凛懔檩吝蔺赁躏拎廪膦 \
+    ling伶凌呤囹岭柃棂泠灵玲瓴绫羚翎聆苓菱蛉酃铃陵零鲮龄领令另 \
+    liu溜熘刘旒榴流浏琉留瘤硫遛鎏镏馏骝柳绺锍六鹨 \
+    long咙栊泷珑癃砻窿笼聋胧茏隆龙垄垅拢陇 \
+    lou搂偻喽娄蒌楼耧蝼髅嵝篓漏瘘镂陋 \
+    lu噜撸卢垆庐栌泸炉胪舻芦轳颅鲈鸬卤掳橹氇虏镥鲁录戮渌漉潞璐禄簏赂路辂辘逯陆鹭鹿碌麓\
+    露 \
+    luan娈孪峦挛栾滦銮鸾脔卵乱 \
+    lun抡仑伦囵沦纶轮论 \
+    luo捋椤猡箩罗萝螺逻锣镙骡倮瘰蠃裸摞洛珞脶泺络荦落雒骆漯 \
+    lv榈闾驴侣吕屡履旅稆缕膂褛铝律氯滤率绿虑 \
+    lve掠略锊 \
+    ma妈嬷蚂吗蟆麻杩犸玛码马唛骂嘛 \
+    mai埋霾买荬劢卖脉迈麦 \
+    man颟瞒蛮谩鞔馒鳗满螨墁幔慢曼漫熳缦蔓镘 \
+    mang忙氓盲硭芒茫邙漭莽蟒 \
+    mao猫旄毛牦矛茅蝥蟊锚髦卯峁昴泖茆铆冒帽懋瑁瞀耄茂袤貌贸 \
+    me么 \
+    mei媒嵋枚梅楣没湄煤猸玫眉莓酶镅霉鹛每浼美镁妹媚寐昧袂魅 \
+    men们扪钔门懑焖闷 \
+    meng朦檬甍盟瞢礞艨萌虻黾勐懵猛艋蒙蜢蠓锰孟梦 \
+    mi咪眯弥猕祢糜縻蘼迷谜醚靡麋弭敉米脒芈冖嘧宓密幂汨秘糸泌蜜觅谧 \
+    mian宀棉眠绵免冕勉娩沔渑湎眄缅腼面 \
+    miao喵描瞄苗鹋杪淼渺眇秒缈藐邈妙庙缪 \
+    mie乜咩灭篾蔑蠛 \
+    min岷民玟珉缗苠悯愍抿敏泯皿闵闽鳘 \
+    ming冥名明暝溟瞑茗螟铭鸣酩命 \
+    miu谬 \
+    mo摸嫫摩摹模磨膜蘑谟馍魔麽抹墨寞末殁沫漠瘼秣耱茉莫蓦貊貘镆陌默 \
+    mou哞侔牟眸蛑谋鍪某 \
+    mu毪亩姆拇母牡仫募坶墓幕慕暮木沐牧目睦穆苜钼 \
+    na拿镎哪呐娜捺纳肭衲那钠 \
+    nai乃奶氖艿奈柰耐萘鼐 \
+    nan囡南喃楠男难腩蝻赧 \
+    nang囊囔馕攮曩 \
+    nao孬呶挠猱硇蛲铙垴恼瑙脑淖闹 \
+    ne讷呢 \
+    nei馁内 \
+    nen嫩恁 \
+    neng能 \
+    ni嗯妮倪坭尼怩泥猊铌霓鲵伲你拟旎匿昵溺睨腻逆 \
+    nian拈蔫年粘鲇鲶黏捻撵碾辇辗埝廿念 \
+    niang娘酿 \
+    niao嬲茑袅鸟尿脲 \
+    nie捏啮嗫孽涅聂臬蘖蹑镊镍陧颞 \
+    nin您 \
+    ning凝咛宁拧柠狞甯聍佞泞 \
+    niu妞牛忸扭狃纽钮 \
+    nong侬农哝浓脓弄 \
+    nou耨 \
+    nu奴孥胬驽努弩怒 \
+    nuan暖 \
+    nuo傩挪喏懦搦糯诺锘 \
+    nv女钕恧衄 \
+    nve疟虐 \
+    o喔噢哦 \
+    ou呕欧殴沤瓯讴鸥偶耦藕怄 \
+    pa啪葩趴杷爬琶筢帕怕 \
+    pai拍俳徘排牌哌派湃蒎 \
+    pan攀潘丬爿盘磐蟠蹒判叛拚泮畔盼袢襻 \
+    pang乓滂庞旁螃逄耪胖 \
+    pao抛脬匏咆庖狍袍跑泡炮疱 \
+    pei呸胚醅培裴赔锫陪佩帔旆沛辔配霈 \
+    pen喷湓盆 \
+    peng嘭怦抨澎烹砰彭朋棚硼篷膨蓬蟛鹏捧碰 \
+    pi丕劈匹噼坯批纰披砒邳铍霹啤枇毗琵疲皮罴脾蚍蜱貔郫陴埤鼙仳圮庀疋痞擗癖僻媲屁淠甓睥\
+    譬 \
+    pian偏犏篇翩胼谝蹁骈片骗 \
+    piao剽嘌漂缥螵飘嫖瓢殍瞟票 \
+    pie撇氕瞥丿苤 \
+    pin姘拼嫔贫频颦品榀牝聘 \
+    ping乒俜娉凭坪屏平枰瓶苹萍评鲆 \
+    po坡泼钋钷颇婆皤鄱叵笸珀破粕迫魄 \
+    pou剖掊裒 \
+    pu仆噗扑攴攵铺匍莆濮璞菩葡蒲镤朴圃普浦溥谱氆蹼镨曝瀑 \
+    qi七凄嘁妻戚期柒栖桤槭欹欺沏漆萋蹊亓俟其圻奇岐崎旗棋歧淇琦琪畦祁祈祺綦耆脐芪蕲蛴蜞\
+    骐骑鳍麒齐颀萁乞企启屺岂杞綮绮芑起器契弃憩气汔汽泣砌碛葺讫迄 \
+    qia掐葜恰洽髂 \
+    qian仟佥千岍悭愆扦搴牵签芊褰谦迁钎铅阡骞乾前掮潜箝荨虔钤钱钳黔浅缱肷谴遣倩堑慊椠欠歉\
+    芡茜嵌 \
+    qiang呛戕戗枪羌腔蜣跄锖锵镪墙嫱强樯蔷抢羟襁炝 \
+    qiao劁悄敲硗缲跷锹橇乔侨荞憔桥樵瞧翘谯鞒巧愀俏峭撬窍诮鞘 \
+    qie切且妾怯惬挈窃箧锲 \
+    qin亲侵衾钦勤嗪噙擒檎琴禽秦芩芹螓锓覃寝吣揿沁 \
+    qing倾卿圊氢清蜻轻青鲭情擎晴檠氰黥苘謦请顷庆箐磬罄 \
+    qiong銎琼穷穹筇茕蛩跫邛 \
+    qiu丘楸秋蚯邱鳅俅囚巯求泅犰球虬蝤裘赇逑遒酋鼽糗 \
+    qu区屈岖祛蛆蛐诎趋躯驱麴黢劬朐氍渠璩癯磲蕖蘧衢鸲取娶曲瞿蠼龋去觑趣阒 \
+    quan圈悛全拳权泉犬痊筌荃蜷诠辁醛铨颧鬈犭畎绻券劝 \
+    que缺阙瘸却悫榷确阕雀鹊 \
+    qun逡群裙 \
+    ran然燃蚺髯冉染苒 \
+    rang嚷瓤禳穰壤攘让 \
+    rao娆荛饶桡扰绕 \
+    re惹热 \
+    ren人亻仁壬忍稔荏仞任刃妊纫葚衽认轫韧饪 \
+    reng扔仍 \
+    ri日 \
+    rong容嵘戎榕溶熔狨绒肜茸荣蓉蝾融冗 \
+    rou揉柔糅蹂鞣肉 \
+    ru儒嚅如孺濡茹薷蠕襦铷颥乳汝辱入洳溽缛蓐褥 \
+    ruan朊软阮 \
+    rui蕤蕊枘瑞睿芮蚋锐 \
+    run润闰 \
+    ruo偌弱箬若 \
+    sa仨撒洒卅脎萨飒 \
+    sai噻塞腮鳃赛 \
+    san三叁毵糁伞馓散 \
+    sang丧桑嗓搡磉颡 \
+    sao搔缫臊骚鳋埽嫂扫瘙 \
+    se啬涩瑟穑色铯 \
+    sen森 \
+    seng僧 \
+    sha杀沙煞痧砂纱莎裟铩鲨啥傻唼厦歃霎 \
+    shai筛晒 \
+    shan删姗山彡杉扇潸煽珊舢芟苫衫跚钐膻闪陕剡善嬗擅汕疝缮膳蟮讪鄯骟赡鳝 \
+    shang伤商墒殇熵觞垧晌赏上尚绱 \
+    shao捎梢烧稍筲艄蛸勺芍苕韶少劭哨潲绍邵 \
+    she奢猞赊畲佘舌蛇厍射慑摄歙涉滠社舍设赦麝 \
+    shei谁 \
+    shen伸呻深申砷绅莘诜身神哂婶审沈娠什渖矧谂慎椹渗甚肾胂蜃 \
+    sheng升声牲生甥笙绳省眚剩圣嵊胜盛 \
+    shi酾失尸师施湿狮蓍虱诗鲺十埘实拾时炻石莳蚀识食饣鲥使史始屎矢豕驶世事仕侍势嗜噬士室\
+    市式弑恃拭是柿氏示礻筮舐螫视誓试谥贳轼适逝释铈饰 \
+    shou收守手扌艏首兽受售寿授狩瘦绶 \
+    shu书倏叔姝抒摅枢梳殊殳毹淑疏纾菽舒蔬输塾孰熟秫赎属暑曙署薯蜀黍鼠墅庶恕戍数术束树沭\
+    漱澍竖腧述 \
+    shua刷唰耍 \
+    shuai摔衰甩帅蟀 \
+    shuan拴栓闩涮 \
+    shuang双孀霜爽 \
+    shui水氵睡税 \
+    shun吮瞬舜顺 \
+    shuo说妁搠朔槊烁硕蒴铄 \
+    si丝厮厶司咝嘶思撕斯澌私缌蛳锶鸶死似纟兕嗣四姒寺巳汜泗祀笥耜肆饲驷 \
+    song凇崧嵩忪松淞菘怂悚竦耸宋讼诵送颂 \
+    sou嗖搜溲艘螋锼飕馊叟嗾擞瞍薮嗽 \
+    su稣苏酥俗僳嗉塑夙宿愫涑溯簌粟素肃蔌觫诉谡速 \
+    suan狻酸算蒜 \
+    sui濉眭睢荽虽绥遂隋随髓岁燧碎祟穗谇邃隧 \
+    sun孙狲荪飧损榫笋隼 \
+    suo唆嗦娑挲桫梭睃嗍缩羧蓑唢所琐索锁 \
+    ta他塌她它趿铊塔獭拓挞榻溻鳎踏蹋遢闼 \
+    tai胎苔台抬炱薹跆邰鲐太态汰泰肽酞钛 \
+    tan坍摊滩瘫贪坛弹昙檀潭痰谈谭郯锬坦忐毯袒叹探炭碳钽 \
+    tang汤羰趟铴镗唐堂塘搪棠樘溏瑭糖膛螗螳醣饧倘傥帑淌耥躺烫 \
+    tao掏涛滔绦韬饕啕桃洮淘萄逃陶鼗讨套 \
+    te忑忒特铽慝 \
+    teng滕疼腾藤誊 \
+    ti剔梯踢锑啼提绨缇荑蹄醍题鹈体倜剃嚏屉悌惕替涕裼逖 \
+    tian天添填恬甜田畋阗忝殄腆舔掭 \
+    tiao佻祧条笤蜩迢髫鲦龆挑窕眺粜跳 \
+    tie帖萜贴铁餮 \
+    ting厅听汀烃亭停婷庭廷莛葶蜓霆挺梃艇 \
+    tong嗵通仝佟僮同彤桐潼瞳砼童茼酮铜捅桶筒统恸痛 \
+    tou偷亠头投骰钭透 \
+    tu凸秃突图屠徒涂荼菟途酴吐土钍兔堍 \
+    tuan湍团抟疃彖 \
+    tui推颓腿煺蜕褪退 \
+    tun吞暾屯臀豚饨氽 \
+    tuo乇托拖脱驮佗坨砣跎酡陀沱沲驼鸵橐鼍妥庹椭唾柝箨 \
+    wa哇娲挖洼蛙娃佤瓦腽袜 \
+    wai歪崴外 \
+    wan剜弯湾蜿豌丸完烷玩纨芄顽婉宛惋挽晚琬畹皖碗绾脘菀万腕 \
+    wang尢汪亡王往惘枉网罔辋魍妄忘旺望 \
+    wei偎危威巍微煨葳薇逶隈为唯囗围圩嵬帏帷惟桅沩涠潍维违闱韦伟伪委娓尾洧炜猥玮痿纬艉苇\
+    萎诿韪鲔位卫味喂尉慰未渭猬畏胃蔚谓軎魏 \
+    wen温瘟文璺纹蚊闻阌雯刎吻稳紊汶问 \
+    weng嗡翁蓊瓮蕹 \
+    wo倭挝涡窝莴蜗我卧幄握斡沃渥肟硪龌 \
+    wu乌呜圬屋巫污诬邬钨吴吾唔庑无梧毋浯牾芜蜈鼯五仵伍侮午妩忤怃捂武舞迕鹉兀务勿坞婺寤\
+    悟戊晤杌焐物痦芴误鋈阢雾骛鹜 \
+    xi僖兮吸唏嘻夕奚嬉希息悉惜昔晰曦析樨欷汐浠淅溪烯熄熙熹牺犀皙矽硒稀穸粞羲翕膝舾菥蜥\
+    蟋西醯锡鼷习媳席檄袭觋隰喜屣徙洗玺禧葸蓰铣戏禊系细舄郄郗螅阋隙饩 \
+    xia瞎虾侠匣峡暇柙狎狭瑕硖辖遐霞黠下吓夏罅 \
+    xian仙先掀暹氙祆籼跹酰锨鲜咸娴嫌弦涎痫舷衔贤闲鹇显燹猃筅藓蚬跣险县宪岘献现线羡腺限陷\
+    霰纤莶冼苋馅 \
+    xiang乡厢湘相箱缃芗葙襄镶香骧庠祥翔详享响想飨饷鲞像向巷橡蟓象项 \
+    xiao削哓哮嚣宵枭枵消潇硝箫绡萧逍销霄骁魈崤淆小晓筱啸孝效校笑肖 \
+    xie些楔歇蝎偕勰协挟携撷斜缬胁谐邪鞋写亵卸屑廨懈械榍榭泄泻渫瀣燮獬绁薤蟹谢躞邂 \
+    xin心忄忻新昕欣歆芯薪辛鑫锌馨信囟衅 \
+    xing兴惺星猩腥刑型形硎荥邢擤醒姓幸性悻杏荇行陉 \
+    xiong兄凶匈汹胸芎熊雄 \
+    xiu休修咻庥羞貅馐髹鸺朽嗅岫秀绣袖锈溴 \
+    xu吁戌嘘墟盱胥虚需须顼徐栩糈许诩醑勖叙婿序恤旭洫溆煦絮绪续蓄酗蓿 \
+    xuan儇喧宣揎暄煊萱谖轩悬旋漩玄痃璇选癣楦泫渲炫眩碹绚铉镟 \
+    xue薛靴学泶穴踅雪鳕血谑 \
+    xun勋埙峋曛熏獯窨薰醺寻巡循恂旬洵浔荀询驯鲟巽徇殉汛训讯迅逊蕈 \
+    ya丫压哑押垭鸦桠鸭伢崖涯牙岈琊睚芽蚜衙痖雅轧亚娅揠氩砑讶迓呀 \
+    yan咽嫣崦淹湮烟焉胭菸鄢阉严埏恹腌妍岩延檐沿炎盐研筵芫蜒言讠闫阎颜俨偃兖厣奄掩演琰眼\
+    罨衍郾魇鼹厌唁堰宴彦晏滟焰焱燕砚艳谚谳赝酽雁餍验 \
+    yang央殃泱秧鞅鸯佯徉扬杨洋炀烊疡羊蛘阳仰养氧痒怏恙样漾 \
+    yao吆夭妖幺腰邀姚尧徭摇爻珧瑶窑繇肴谣轺遥鳐咬崾杳窈舀曜耀药要钥鹞 \
+    ye噎掖椰耶揶爷铘也冶野业叶夜晔曳液烨腋谒邺靥页 \
+    yi一伊依医咿噫壹揖漪猗衣衤铱黟仪咦圯夷姨宜嶷彝怡沂疑痍移胰诒贻迤饴眙遗酏颐乙以倚已\
+    旖椅矣舣苡蚁钇义亦亿佚佾刈劓呓埸奕屹峄异弈弋役忆怿悒意懿抑挹易殪毅溢熠疫瘗癔益绎缢羿翊翌\
+    翳翼肄臆艺薏蜴裔议译诣谊轶逸邑镒镱驿 \
+    yin喑因堙姻氤洇茵荫铟阴音殷吟垠夤寅淫狺鄞银霪尹廴引吲瘾蚓隐饮印胤茚 \
+    ying嘤婴应撄樱瑛璎缨罂膺英莺鹦鹰嬴楹滢潆瀛盈茔荧莹萤营萦蓥蝇赢迎影瘿郢颍颖媵映硬 \
+    yo哟唷 \
+    yong佣墉壅庸慵拥痈臃邕镛雍饔鳙喁俑勇咏恿永泳涌甬蛹踊用 \
+    you优呦幽忧悠攸尤柚油游犹猷由疣莸蚰蝣邮莜铀鱿卣友有牖莠酉铕黝佑侑又右囿宥幼诱蚴釉鼬 \
+    yu於淤瘀纡迂于余俞妤娱嵛愉愚揄榆欤渔渝狳瑜盂禺窬竽腴臾舁舆萸虞蝓觎谀逾隅雩馀鱼与予\
+    伛俣圄圉宇屿庾瘐禹窳羽语雨龉喻域妪寓峪御愈昱欲毓浴煜燠狱玉聿肀育芋蓣蜮裕誉谕豫遇郁钰阈预\
+    饫驭鬻鹆鹬 \
+    yuan冤渊眢鸢鸳箢元原员园圆垣塬媛援橼沅源爰猿缘螈袁辕鼋远怨愿掾瑗苑院垸 \
+    yue曰约刖岳悦月钺樾瀹粤越跃阅龠 \
+    yun晕氲云匀昀纭耘芸郧允殒狁陨孕恽愠蕴运郓酝韫韵熨 \
+    za匝咂拶杂砸咋 \
+    zai哉栽灾甾宰崽载再在 \
+    zan簪糌咱昝趱暂瓒赞錾 \
+    zang脏臧赃驵奘葬 \
+    zao糟遭凿早枣澡藻蚤唣噪灶燥皂躁造 \
+    ze则啧帻择泽笮箦舴责赜迮仄昃 \
+    zei贼 \
+    zen怎谮 \
+    zeng增憎缯罾甑赠锃 \
+    zha吒哳扎揸渣齄札炸铡闸眨砟乍咤柞榨蚱诈栅痄 \
+    zhai摘斋宅窄债寨瘵砦 \
+    zhan旃毡沾瞻詹谵展崭搌斩盏占战栈湛站绽蘸 \
+    zhang嫜张彰樟漳獐璋章蟑鄣仉掌涨丈仗嶂帐幛杖瘴胀账障 \
+    zhao招昭朝钊找沼爪兆召棹照笊罩肇诏赵 \
+    zhe蜇遮哲折摺磔蛰谪辄辙者锗褶赭柘浙蔗这鹧着 \
+    zhen侦斟桢榛溱珍甄真砧祯箴缜胗臻蓁贞针浈枕畛疹稹诊轸圳振朕赈镇阵震鸩 \
+    zheng争峥征徵怔挣狰症睁筝蒸诤钲铮拯整帧政正证郑 \
+    zhi之卮吱掷支枝枳栀汁知祗织肢胝脂芝蜘觯侄值埴执摭桎植殖直絷职跖踯只咫址夂指旨止祉纸\
+    芷趾轵酯黹制帙帜彘志忮挚智治滞炙痔痣秩稚窒置膣至致蛭豸质贽踬轾郅峙栉陟雉骘鸷 \
+    zhong中忠盅终舯螽衷钟锺冢种肿踵仲众重 \
+    zhou周州洲粥舟诌妯碡啁轴帚肘咒宙昼皱籀纣绉胄荮酎骤 \
+    zhu侏朱株槠橥洙潴猪珠茱蛛诛诸邾铢烛瘃竹竺舳躅逐丶主嘱拄渚煮瞩麈伫住助杼柱注炷疰祝筑\
+    箸翥著苎蛀贮铸驻 \
+    zhua抓 \
+    zhuai拽 \
+    zhuan专砖颛转啭撰篆赚馔 \
+    zhuang妆庄桩装壮撞状 \
+    zhui隹追锥骓坠惴缀缒赘 \
+    zhun窀肫谆准 \
+    zhuo倬拙捉桌涿卓啄擢斫浊浞濯灼焯琢禚茁诼酌镯 \
+    zi咨姿孜兹孳嵫淄滋缁觜訾谘赀资趑辎锱髭鲻龇仔姊子梓滓秭籽紫耔笫字恣渍眦自 \
+    zong宗棕综腙踪鬃偬总粽纵 \
+    zou诹邹驺鄹陬鲰走奏揍 \
+    zu族足镞俎祖组诅阻租卒 \
+    zuan躜钻纂缵攥 \
+    zui嘴最罪蕞醉 \
+    zun尊樽遵鳟撙 \
+    zuo昨佐左作做坐座怍祚胙阼唑";
+
+// 查找汉字的拼音，不在表中时返回 None
+fn syllable(c: char) -> Option<&'static str> {
+    PINYIN.split(' ').find_map(|entry| {
+        let split = entry.find(|c: char| !c.is_ascii_lowercase())?;
+        entry[split..].contains(c).then(|| &entry[..split])
+    })
+}
+
+// 去除空白和隔音符号并转为小写
+fn clean(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_whitespace() && *c != '\'')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 全拼，如“退出”为 tuichu
+pub fn to_pinyin(text: &str) -> String {
+    clean(text)
+        .chars()
+        .map(|c| syllable(c).map_or_else(|| c.to_string(), str::to_string))
+        .collect()
+}
+
+/// 首字母，如“退出”为 tc
+pub fn to_initials(text: &str) -> String {
+    clean(text)
+        .chars()
+        .map(|c| syllable(c).and_then(|s| s.chars().next()).unwrap_or(c))
+        .collect()
+}
+
+/// 输入是否与文本本身、全拼或首字母相同，返回匹配的得分
+/// 与文本本身或全拼相同时得分为 1，与首字母相同时得分为 0.9（首字母更容易重复）
+pub fn matches(text: &str, input: &str) -> Option<f64> {
+    let input = clean(input);
+    if input.is_empty() {
+        return None;
+    }
+    if clean(text) == input || to_pinyin(text) == input {
+        Some(1.0)
+    } else if to_initials(text) == input {
+        Some(0.9)
+    } else {
+        None
+    }
+}
+
+/// 在候选中查找与输入拼音匹配的一个，与输入完全相同的候选优先，得分相同时取字典序最小的
+pub fn closest<'a>(candidates: impl IntoIterator<Item = &'a str>, input: &str) -> Option<&'a str> {
+    let mut best: Option<(&str, f64)> = None;
+    for candidate in candidates {
+        if candidate == input {
+            return Some(candidate);
+        }
+        let Some(score) = matches(candidate, input) else {
+            continue;
+        };
+        if best.is_none_or(|(key, best)| score > best || (score == best && candidate < key)) {
+            best = Some((candidate, score));
+        }
+    }
+    best.map(|(candidate, _)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::collections::HashMap;
+
+    // 测试拼音表
+    #[test]
+    fn test_table() {
+        let mut seen: HashMap<char, &str> = HashMap::new();
+        for entry in PINYIN.split(' ') {
+            let split = entry.find(|c: char| !c.is_ascii_lowercase()).unwrap();
+            let (syllable, chars) = entry.split_at(split);
+            assert!(!syllable.is_empty());
+            for c in chars.chars() {
+                assert!(seen.insert(c, syllable).is_none(), "{} appears twice", c);
+            }
+        }
+        assert_eq!(seen.len(), 6763);
+
+        // 抽查音节交界处和多音字
+        let cases = [
+            ('吃', "chi"),
+            ('持', "chi"),
+            ('赤', "chi"),
+            ('充', "chong"),
+            ('仇', "chou"),
+            ('丑', "chou"),
+            ('出', "chu"),
+            ('尬', "ga"),
+            ('劝', "quan"),
+            ('耍', "shua"),
+            ('命', "ming"),
+            ('谬', "miu"),
+            ('谁', "shei"),
+            ('水', "shui"),
+            ('氆', "pu"),
+            ('什', "shen"),
+        ];
+        for (c, syllable) in cases {
+            assert_eq!(seen[&c], syllable, "{}", c);
+        }
+    }
+
+    // 测试全拼、首字母和匹配
+    #[test]
+    fn test_pinyin() {
+        assert_eq!(to_pinyin("退出"), "tuichu");
+        assert_eq!(to_initials("退出"), "tc");
+        assert_eq!(to_pinyin("查询 No.1"), "chaxunno.1");
+        assert_eq!(to_pinyin("绿色"), "lvse");
+        assert_eq!(matches("退出", "tuichu"), Some(1.0));
+        assert_eq!(matches("退出", "Tui Chu"), Some(1.0));
+        assert_eq!(matches("西安", "xi'an"), Some(1.0));
+        assert_eq!(matches("退出", "TC"), Some(0.9));
+        assert_eq!(matches("退出", "退出"), Some(1.0));
+        assert_eq!(matches("退出", "tui"), None);
+        assert_eq!(matches("退出", " "), None);
+
+        let keys = ["操作系统", "算法设计与分析", "数据结构"];
+        assert_eq!(closest(keys, "caozuoxitong"), Some("操作系统"));
+        assert_eq!(closest(keys, "sjjg"), Some("数据结构"));
+        assert_eq!(closest(keys, "bianyi"), None);
+        assert_eq!(closest(["退出", "tc"], "tc"), Some("tc"));
+    }
+}
@@ -0,0 +1,518 @@
+/// Sakiko 的相对时间解析
+/// 从用户输入的任意位置找出日期和时间表达，如 “明天下午三点”、“下周一”、“5月1号上午十点半”、“两小时后”、“tomorrow 3pm”，
+/// 相对于当前时间解析为日期时间，并报告是否有歧义（如 “三点” 未指明上午还是下午）。
+use ::regex::{Captures, Regex};
+use ::std::sync::OnceLock;
+
+use crate::normalize::to_half_width;
+use crate::number::parse_integer_number;
+use crate::time::{DatePart, DateTime};
+
+/// 解析结果
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Resolution {
+    pub time: DateTime,  // 解析出的日期时间，有歧义时为最可能的一个
+    pub ambiguous: bool, // 是否有歧义
+}
+
+// 时段
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Period {
+    Dawn,      // 凌晨
+    Morning,   // 早上、上午、am
+    Noon,      // 中午
+    Afternoon, // 下午、pm
+    Evening,   // 傍晚、晚上
+}
+
+// 日期表达
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum DateSpec {
+    Offset(i64),                     // 相对今天的天数，如 “明天”
+    Weekday(Option<i64>, i64),       // 相对本周的周数（未指明时为最近的一天）和星期（0 为周一）
+    MonthDay(Option<i64>, i64, i64), // 年（可省略）、月、日
+    Day(i64),                        // 只有日，如 “15号”
+}
+
+// 输入中找到的表达
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Part {
+    Date(DateSpec),
+    Period(Period),
+    Time(i64, i64),             // 时和分
+    Clock(i64, i64),            // 24 小时制的时和分，如 “3pm”、“noon”
+    DateAndPeriod(i64, Period), // 如 “明晚”
+    After(i64),                 // 若干秒之后，如 “两小时后”
+}
+
+// 中文数字或阿拉伯数字
+const NUMBER: &str = r"(?:\d{1,4}|[零〇一二两三四五六七八九十]{1,3})";
+
+// 时长中的数和单位，时长可以连写，如 “1小时30分钟后”
+fn duration() -> &'static Regex {
+    static DURATION: OnceLock<Regex> = OnceLock::new();
+    DURATION.get_or_init(|| Regex::new(&format!(r"({}|半)个?(天|小时|钟头|分钟)", NUMBER)).unwrap())
+}
+
+// 由匹配结果得到表达
+type Parse = fn(&Captures) -> Option<Part>;
+
+// 各类表达的正则表达式，只匹配开头，构建一次后共享
+fn rules() -> &'static [(Regex, Parse)] {
+    static RULES: OnceLock<Vec<(Regex, Parse)>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        let rules: Vec<(String, Parse)> = vec![
+            (
+                format!(r"(?:(?:{}|半)个?(?:天|小时|钟头|分钟)\s*)+(?:后|之后|以后)", NUMBER),
+                |c| {
+                    let mut seconds = 0.0;
+                    for c in duration().captures_iter(&c[0]) {
+                        let n = if &c[1] == "半" { 0.5 } else { number(&c[1])? as f64 };
+                        let unit = match &c[2] {
+                            "天" => 86400.0,
+                            "分钟" => 60.0,
+                            _ => 3600.0,
+                        };
+                        seconds += n * unit;
+                    }
+                    Some(Part::After(seconds as i64))
+                },
+            ),
+            (
+                r"\bin\s+(\d{1,4}|an?|one|two|three)\s+(day|hour|minute|min)s?\b".to_string(),
+                |c| {
+                    let n = match &c[1] {
+                        "a" | "an" | "one" => 1,
+                        "two" => 2,
+                        "three" => 3,
+                        n => n.parse().ok()?,
+                    };
+                    let unit = match &c[2] {
+                        "day" => 86400,
+                        "hour" => 3600,
+                        _ => 60,
+                    };
+                    Some(Part::After(n * unit))
+                },
+            ),
+            (
+                r"大后天|后天|明天|明日|今天|今日|昨天|前天|\btomorrow\b|\btoday\b|\byesterday\b"
+                    .to_string(),
+                |c| {
+                    let offset = match &c[0] {
+                        "大后天" => 3,
+                        "后天" => 2,
+                        "明天" | "明日" | "tomorrow" => 1,
+                        "昨天" | "yesterday" => -1,
+                        "前天" => -2,
+                        _ => 0,
+                    };
+                    Some(Part::Date(DateSpec::Offset(offset)))
+                },
+            ),
+            (
+                r"明早|明晚|今早|今晚|\btonight\b".to_string(),
+                |c| {
+                    let offset = if c[0].starts_with('明') { 1 } else { 0 };
+                    let period = if c[0].ends_with('早') {
+                        Period::Morning
+                    } else {
+                        Period::Evening
+                    };
+                    Some(Part::DateAndPeriod(offset, period))
+                },
+            ),
+            (
+                r"(下下个?|下个?|这个?|本|上个?)?(?:周|星期|礼拜)([一二三四五六日天1-7])".to_string(),
+                |c| {
+                    let week = c.get(1).map(|w| match w.as_str().trim_end_matches('个') {
+                        "下下" => 2,
+                        "下" => 1,
+                        "上" => -1,
+                        _ => 0,
+                    });
+                    let day = match &c[2] {
+                        "日" | "天" => 7,
+                        d => number(d)?,
+                    };
+                    Some(Part::Date(DateSpec::Weekday(week, day - 1)))
+                },
+            ),
+            (
+                r"\b(?:(next|this)\s+)?(monday|tuesday|wednesday|thursday|friday|saturday|sunday)\b"
+                    .to_string(),
+                |c| {
+                    let week = c.get(1).map(|w| if w.as_str() == "next" { 1 } else { 0 });
+                    let day = [
+                        "monday",
+                        "tuesday",
+                        "wednesday",
+                        "thursday",
+                        "friday",
+                        "saturday",
+                        "sunday",
+                    ]
+                    .iter()
+                    .position(|d| *d == &c[2])? as i64;
+                    Some(Part::Date(DateSpec::Weekday(week, day)))
+                },
+            ),
+            (
+                format!(r"(?:(\d{{4}})\s*年\s*)?({})\s*月\s*({})\s*[日号]?", NUMBER, NUMBER),
+                |c| {
+                    let year = c.get(1).and_then(|y| y.as_str().parse().ok());
+                    Some(Part::Date(DateSpec::MonthDay(year, number(&c[2])?, number(&c[3])?)))
+                },
+            ),
+            (
+                r"(\d{4})[-/.](\d{1,2})[-/.](\d{1,2})".to_string(),
+                |c| {
+                    Some(Part::Date(DateSpec::MonthDay(
+                        c[1].parse().ok(),
+                        c[2].parse().ok()?,
+                        c[3].parse().ok()?,
+                    )))
+                },
+            ),
+            (
+                format!(r"({})\s*号", NUMBER),
+                |c| Some(Part::Date(DateSpec::Day(number(&c[1])?))),
+            ),
+            (
+                r"凌晨|早上|早晨|清晨|上午|中午|下午|午后|傍晚|晚上|夜里|夜间|\bmorning\b|\bafternoon\b|\bevening\b"
+                    .to_string(),
+                |c| {
+                    let period = match &c[0] {
+                        "凌晨" => Period::Dawn,
+                        "中午" => Period::Noon,
+                        "下午" | "午后" | "afternoon" => Period::Afternoon,
+                        "傍晚" | "晚上" | "夜里" | "夜间" | "evening" => Period::Evening,
+                        _ => Period::Morning,
+                    };
+                    Some(Part::Period(period))
+                },
+            ),
+            (
+                format!(
+                    r"({})\s*[点點时](?:\s*(半)|\s*(一刻)|\s*(三刻)|\s*({})\s*分?)?",
+                    NUMBER, NUMBER
+                ),
+                |c| {
+                    let minute = if c.get(2).is_some() {
+                        30
+                    } else if c.get(3).is_some() {
+                        15
+                    } else if c.get(4).is_some() {
+                        45
+                    } else if let Some(m) = c.get(5) {
+                        number(m.as_str())?
+                    } else {
+                        0
+                    };
+                    Some(Part::Time(number(&c[1])?, minute))
+                },
+            ),
+            (
+                r"(\d{1,2}):(\d{2})".to_string(),
+                |c| Some(Part::Time(c[1].parse().ok()?, c[2].parse().ok()?)),
+            ),
+            (
+                r"(\d{1,2})(?::(\d{2}))?\s*(am|pm)\b".to_string(),
+                |c| {
+                    let hour: i64 = c[1].parse().ok()?;
+                    let minute = c.get(2).map_or(Some(0), |m| m.as_str().parse().ok())?;
+                    if !(1..=12).contains(&hour) {
+                        return None;
+                    }
+                    // am 和 pm 直接换算为 24 小时制
+                    let hour = match (&c[3], hour) {
+                        ("am", 12) => 0,
+                        ("pm", 12) => 12,
+                        ("pm", hour) => hour + 12,
+                        (_, hour) => hour,
+                    };
+                    Some(Part::Clock(hour, minute))
+                },
+            ),
+            (
+                r"\b(noon|midnight)\b".to_string(),
+                |c| Some(Part::Clock(if &c[1] == "noon" { 12 } else { 0 }, 0)),
+            ),
+        ];
+        rules
+            .into_iter()
+            .map(|(pattern, parse)| (Regex::new(&format!("^(?:{})", pattern)).unwrap(), parse))
+            .collect()
+    })
+}
+
+// 解析数字
+fn number(s: &str) -> Option<i64> {
+    parse_integer_number(s).map(i64::from)
+}
+
+// 从左到右找出输入中的表达及其位置，同一位置有多个表达时取最长的
+// 英文单词和数字中间不会开始新的表达，如 “x3pm” 中的 “3pm”
+fn find_parts(text: &str) -> Vec<(usize, &str, Part)> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    while let Some(c) = text[start..].chars().next() {
+        let rest = &text[start..];
+        let longest = rules()
+            .iter()
+            .filter_map(|(re, parse)| {
+                let captures = re.captures(rest)?;
+                Some((captures.get(0)?.end(), parse(&captures)?))
+            })
+            .fold(
+                None,
+                |longest: Option<(usize, Part)>, (end, part)| match longest {
+                    Some((longest_end, _)) if longest_end >= end => longest,
+                    _ => Some((end, part)),
+                },
+            );
+        match longest {
+            Some((end, part)) if end > 0 => {
+                parts.push((start, &rest[..end], part));
+                start += end;
+            }
+            _ if c.is_ascii_alphanumeric() => {
+                start += rest
+                    .find(|c: char| !c.is_ascii_alphanumeric())
+                    .unwrap_or(rest.len());
+            }
+            _ => start += c.len_utf8(),
+        }
+    }
+    parts
+}
+
+// 按时段换算为 24 小时制，晚上 12 点及以后算作次日凌晨
+fn adjust_hour(hour: i64, period: Period) -> i64 {
+    match (period, hour) {
+        (Period::Dawn, 12) => 0,
+        (Period::Noon, 1..=5) | (Period::Afternoon, 1..=11) | (Period::Evening, 5..=12) => {
+            hour + 12
+        }
+        (Period::Evening, 0..=4) => hour + 24,
+        _ => hour,
+    }
+}
+
+// 时段的默认时刻
+fn default_hour(period: Period) -> i64 {
+    match period {
+        Period::Dawn => 0,
+        Period::Morning => 9,
+        Period::Noon => 12,
+        Period::Afternoon => 14,
+        Period::Evening => 19,
+    }
+}
+
+// 解析日期为距 1970-01-01 的天数，返回天数和是否有歧义
+fn resolve_date(spec: DateSpec, today: DateTime) -> Option<(i64, bool)> {
+    let days = today.days();
+    // 用 DateTime::parse 校验日期是否有效
+    let date = |y: i64, m: i64, d: i64| {
+        DateTime::parse(&format!("{:04}-{:02}-{:02}", y, m, d)).map(|date| date.days())
+    };
+    let (year, month) = (today.part(DatePart::Year), today.part(DatePart::Month));
+    match spec {
+        DateSpec::Offset(offset) => Some((days + offset, false)),
+        DateSpec::Weekday(week, day) => {
+            if !(0..7).contains(&day) {
+                return None;
+            }
+            let weekday = today.part(DatePart::Weekday) - 1;
+            match week {
+                Some(week) => Some((days - weekday + week * 7 + day, false)),
+                // 未指明哪一周时取最近的一天，恰好是今天时有歧义
+                None => {
+                    let ahead = (day - weekday).rem_euclid(7);
+                    Some((days + ahead, ahead == 0))
+                }
+            }
+        }
+        DateSpec::MonthDay(Some(y), m, d) => Some((date(y, m, d)?, false)),
+        // 未指明年份时取今天及以后最近的一天
+        DateSpec::MonthDay(None, m, d) => {
+            let this = date(year, m, d);
+            match this {
+                Some(this) if this >= days => Some((this, false)),
+                _ => Some((date(year + 1, m, d)?, false)),
+            }
+        }
+        // 只有日时取本月或下个月
+        DateSpec::Day(d) => match date(year, month, d) {
+            Some(this) if this >= days => Some((this, false)),
+            _ => {
+                let (year, month) = if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+                Some((date(year, month, d)?, false))
+            }
+        },
+    }
+}
+
+/// 从输入中找出日期和时间表达，相对于当前时间 now 解析，找不到时返回 None
+pub fn resolve(input: &str, now: DateTime) -> Option<Resolution> {
+    let text: String = input
+        .chars()
+        .map(to_half_width)
+        .collect::<String>()
+        .to_lowercase();
+    let parts = find_parts(&text);
+    // 下标为 index 和 index + 1 的表达是否相邻（中间只有空白）且其中有日期或时段
+    let dated = |index: usize| match (parts.get(index), parts.get(index + 1)) {
+        (Some(&(start, matched, a)), Some(&(next, _, b))) => {
+            text[start + matched.len()..next].trim().is_empty()
+                && [a, b].iter().any(|part| {
+                    matches!(
+                        part,
+                        Part::Date(_) | Part::Period(_) | Part::DateAndPeriod(..)
+                    )
+                })
+        }
+        _ => false,
+    };
+    let mut dates = Vec::new();
+    let mut periods = Vec::new();
+    let mut times = Vec::new();
+    let mut afters = Vec::new();
+    for (index, &(_, matched, part)) in parts.iter().enumerate() {
+        match part {
+            Part::Date(spec) => dates.push(resolve_date(spec, now)?),
+            Part::Period(period) => periods.push(period),
+            // “一点” 单独出现时通常表示 “一些”，如 “快一点”，与日期或时段相邻时才是时刻
+            Part::Time(1, 0)
+                if matched == "一点"
+                    && !dated(index)
+                    && !index.checked_sub(1).is_some_and(dated) => {}
+            Part::Time(hour, minute) if hour <= 24 && minute < 60 => {
+                times.push((hour, minute, false))
+            }
+            Part::Clock(hour, minute) if hour < 24 && minute < 60 => {
+                times.push((hour, minute, true))
+            }
+            Part::Time(..) | Part::Clock(..) => return None,
+            Part::DateAndPeriod(offset, period) => {
+                dates.push((now.days() + offset, false));
+                periods.push(period);
+            }
+            Part::After(seconds) => afters.push(seconds),
+        }
+    }
+    // 出现多个不同的日期、时段、时刻或时长时有歧义，取第一个
+    let mut ambiguous = dates.windows(2).any(|w| w[0].0 != w[1].0)
+        || periods.windows(2).any(|w| w[0] != w[1])
+        || times.windows(2).any(|w| w[0] != w[1])
+        || afters.windows(2).any(|w| w[0] != w[1]);
+    if let Some(&seconds) = afters.first() {
+        ambiguous |= !dates.is_empty() || !times.is_empty();
+        return Some(Resolution {
+            time: DateTime(now.0.checked_add(seconds)?),
+            ambiguous,
+        });
+    }
+    if dates.is_empty() && periods.is_empty() && times.is_empty() {
+        return None;
+    }
+    let date = dates.first().copied();
+    ambiguous |= date.is_some_and(|(_, ambiguous)| ambiguous);
+    let period = periods.first().copied();
+    let (hours, minute) = match (times.first(), period) {
+        (Some(&(hour, minute, true)), _) => (vec![hour], minute),
+        (Some(&(hour, minute, _)), Some(period)) => (vec![adjust_hour(hour, period)], minute),
+        // 未指明时段时 1 到 11 点有歧义，7 点以前按下午计算
+        (Some(&(hour, minute, _)), None) if (1..12).contains(&hour) => {
+            ambiguous = true;
+            if hour < 7 {
+                (vec![hour + 12], minute)
+            } else {
+                (vec![hour, hour + 12], minute)
+            }
+        }
+        (Some(&(hour, minute, _)), None) => (vec![hour], minute),
+        // 只有时段时取默认时刻，有歧义
+        (None, Some(period)) => {
+            ambiguous = true;
+            (vec![default_hour(period)], 0)
+        }
+        (None, None) => (vec![0], 0),
+    };
+    let hour = hours[0];
+    let at = |days: i64, hour: i64| DateTime(days * 86400 + hour * 3600 + minute * 60);
+    let time = match date {
+        Some((days, _)) => at(days, hour),
+        // 未指明日期时取今天及以后最近的时刻
+        None => hours
+            .iter()
+            .map(|&hour| at(now.days(), hour))
+            .find(|time| *time >= now)
+            .unwrap_or_else(|| at(now.days() + 1, hour)),
+    };
+    Some(Resolution { time, ambiguous })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试相对时间解析，当前时间为 2024-05-15 13:20（周三）
+    #[test]
+    fn test_resolve() {
+        let now = DateTime::parse("2024-05-15 13:20").unwrap();
+        let cases = [
+            ("明天下午三点", "2024-05-16 15:00:00", false),
+            ("后天上午十点半", "2024-05-17 10:30:00", false),
+            ("今晚8点", "2024-05-15 20:00:00", false),
+            ("明早七点一刻", "2024-05-16 07:15:00", false),
+            ("下周一", "2024-05-20 00:00:00", false),
+            ("这周五下午", "2024-05-17 14:00:00", true),
+            ("周三", "2024-05-15 00:00:00", true),
+            ("周五十点", "2024-05-17 10:00:00", true),
+            ("星期日晚上九点", "2024-05-19 21:00:00", false),
+            ("5月1号", "2025-05-01 00:00:00", false),
+            ("2024年6月1日 14:30", "2024-06-01 14:30:00", false),
+            ("20号中午12点", "2024-05-20 12:00:00", false),
+            ("三点", "2024-05-15 15:00:00", true),
+            ("八点", "2024-05-15 20:00:00", true),
+            ("明天三点", "2024-05-16 15:00:00", true),
+            ("十二点", "2024-05-16 12:00:00", false),
+            ("两小时后", "2024-05-15 15:20:00", false),
+            ("半小时之后", "2024-05-15 13:50:00", false),
+            ("3天后", "2024-05-18 13:20:00", false),
+            ("1小时30分钟后", "2024-05-15 14:50:00", false),
+            ("一天 两个小时以后", "2024-05-16 15:20:00", false),
+            ("两小时后或者三小时后", "2024-05-15 15:20:00", true),
+            ("明天下午一点", "2024-05-16 13:00:00", false),
+            ("明天一点", "2024-05-16 13:00:00", true),
+            ("一点 明天", "2024-05-16 13:00:00", true),
+            ("明天或者后天", "2024-05-16 00:00:00", true),
+            ("tomorrow 3pm", "2024-05-16 15:00:00", false),
+            ("next Monday at 9:30am", "2024-05-20 09:30:00", false),
+            ("in 2 hours", "2024-05-15 15:20:00", false),
+            ("ｔｏｍｏｒｒｏｗ ｎｏｏｎ", "2024-05-16 12:00:00", false),
+            ("周五晚上十二点", "2024-05-18 00:00:00", false),
+        ];
+        for (input, expected, ambiguous) in cases {
+            let resolution = resolve(input, now).unwrap_or_else(|| panic!("{} failed", input));
+            assert_eq!(resolution.time.to_string(), expected, "{}", input);
+            assert_eq!(resolution.ambiguous, ambiguous, "{}", input);
+        }
+        for input in [
+            "你好",
+            "快一点",
+            "2月30号",
+            "25点",
+            "慢一点走",
+            "in 9999999999999999 days",
+        ] {
+            assert_eq!(resolve(input, now), None, "{}", input);
+        }
+    }
+}
@@ -3,9 +3,21 @@
 /// 使用 Arc 来共享配置，减少内存占用，同时避免多线程中的数据竞争
 /// 支持同步和异步的 IO 操作
 /// 为标准输入输出提供了直接的支持
-use crate::config::SakikoConfig;
-use crate::variable::{Variables, VariableType};
+use crate::config::{choose_label, numbered, SakikoConfig, Transaction};
+use crate::context::Context;
+use crate::extract::Extractors;
+use crate::form::Form;
+use crate::fuzzy;
+use crate::message::Message;
+use crate::operation::Operations;
+use crate::pinyin;
+use crate::time::{Clock, SystemClock};
+use crate::variable::{VariableType, Variables};
+use ::rand::{Rng, SeedableRng};
+use ::rand_chacha::ChaCha8Rng;
+use ::serde::{Deserialize, Serialize};
 use regex::Regex;
+use std::collections::HashMap;
 use std::ops::Not;
 use std::sync::Arc;
 use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
@@ -16,6 +28,46 @@ pub struct Session {
     config: Arc<SakikoConfig>,
     variables: Variables,
     now_step: String,
+    seed: u64,
+    rng: ChaCha8Rng,
+    clock: Arc<dyn Clock>,
+    variants: HashMap<String, usize>, // 各步骤上一次选取的候选输出
+    reprompt: bool,                   // 上一次菜单输入无效
+    form: FormState,                  // 表单的填写进度
+    clarify: ClarifyState,            // 等待用户澄清的转移
+}
+
+/// 表单的填写进度
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Default)]
+struct FormState {
+    field: usize,  // 当前字段
+    attempts: u32, // 当前字段失败的次数
+    retry: bool,   // 上一次输入校验失败
+}
+
+/// 等待用户澄清的转移
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Default)]
+struct ClarifyState {
+    candidates: Vec<usize>, // 候选转移在步骤中的序号
+    input: String,          // 引起歧义的输入
+}
+
+/// 会话快照，用于保存和恢复会话状态
+/// 包含随机数种子和随机数生成器的位置，恢复后的随机结果与原会话一致
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct SessionSnapshot {
+    now_step: String,
+    variables: Variables,
+    seed: u64,
+    rng_position: String, // u128 以字符串保存，避免 yaml 不支持
+    #[serde(default = "HashMap::new")]
+    variants: HashMap<String, usize>,
+    #[serde(default = "bool::default")]
+    reprompt: bool,
+    #[serde(default = "FormState::default")]
+    form: FormState,
+    #[serde(default = "ClarifyState::default")]
+    clarify: ClarifyState,
 }
 
 // 判断是否为正则表达式
@@ -23,19 +75,143 @@ fn is_regex(pattern: &str) -> bool {
     Regex::new(pattern).is_ok()
 }
 
+// 判断输入是否匹配匹配字符串或意图，返回匹配的得分
+// 精确匹配和正则表达式的得分为 1，模糊匹配的得分为相似度，拼音匹配的得分见 pinyin::matches，意图的得分为识别的得分
+fn match_pattern(
+    transaction: &Transaction,
+    input: &str,
+    scores: &[(&str, f64)],
+) -> Result<Option<f64>, String> {
+    if !transaction.pattern.is_empty() {
+        if let Some(threshold) = transaction.fuzzy {
+            let score = fuzzy::similarity(&transaction.pattern, input);
+            if score >= threshold {
+                return Ok(Some(score));
+            }
+        } else if transaction.pinyin {
+            if let Some(score) = pinyin::matches(&transaction.pattern, input) {
+                return Ok(Some(score));
+            }
+        } else if is_regex(&transaction.pattern) {
+            let re = Regex::new(&transaction.pattern).map_err(|_| "Invalid pattern")?;
+            if re.is_match(input) {
+                return Ok(Some(1.0));
+            }
+        } else if transaction.pattern == input {
+            return Ok(Some(1.0));
+        }
+    }
+    if transaction.intent.is_empty() {
+        return Ok(None);
+    }
+    Ok(scores
+        .iter()
+        .find(|(name, score)| *name == transaction.intent && *score >= transaction.confidence())
+        .map(|(_, score)| *score))
+}
+
+// 判断输入是否匹配转移，匹配字符串和意图满足其一即可，返回匹配的得分
+// 声明了提取规则时，原始输入中还必须能提取到所有实体；只声明提取规则时得分为 1
+fn match_transaction(
+    transaction: &Transaction,
+    input: &str,
+    raw: &str,
+    scores: &[(&str, f64)],
+    extractors: &Extractors,
+) -> Result<Option<f64>, String> {
+    let score = if transaction.pattern.is_empty() && transaction.intent.is_empty() {
+        (!transaction.extract.is_empty()).then_some(1.0)
+    } else {
+        match_pattern(transaction, input, scores)?
+    };
+    if score.is_none() {
+        return Ok(None);
+    }
+    for extraction in transaction.extract.iter() {
+        if extractors.extract(&extraction.extractor, raw)?.is_none() {
+            return Ok(None);
+        }
+    }
+    Ok(score)
+}
+
 impl Session {
-    /// 创建一个新的会话，传入配置
+    /// 创建一个新的会话，传入配置，随机数种子随机生成
     pub fn new(config: Arc<SakikoConfig>) -> Session {
+        Session::with_seed(config, ::rand::thread_rng().gen())
+    }
+
+    /// 创建一个新的会话，传入配置和随机数种子
+    /// 相同的种子和相同的输入会得到相同的对话
+    pub fn with_seed(config: Arc<SakikoConfig>, seed: u64) -> Session {
         let mut tmp = Session {
             variables: config.get_variables().clone(),
             config: Arc::clone(&config),
             now_step: config.get_start_step().to_string(),
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            clock: Arc::new(SystemClock),
+            variants: HashMap::new(),
+            reprompt: false,
+            form: FormState::default(),
+            clarify: ClarifyState::default(),
         };
         tmp.variables
             .insert("input".to_string(), VariableType::Str("".to_string()));
+        tmp.choose_variant();
         tmp
     }
 
+    /// 从快照恢复会话，传入配置和快照
+    pub fn restore(
+        config: Arc<SakikoConfig>,
+        snapshot: &SessionSnapshot,
+    ) -> Result<Session, String> {
+        let mut rng = ChaCha8Rng::seed_from_u64(snapshot.seed);
+        rng.set_word_pos(
+            snapshot
+                .rng_position
+                .parse()
+                .map_err(|_| "Invalid rng position")?,
+        );
+        Ok(Session {
+            config,
+            variables: snapshot.variables.clone(),
+            now_step: snapshot.now_step.clone(),
+            seed: snapshot.seed,
+            rng,
+            clock: Arc::new(SystemClock),
+            variants: snapshot.variants.clone(),
+            reprompt: snapshot.reprompt,
+            form: snapshot.form.clone(),
+            clarify: snapshot.clarify.clone(),
+        })
+    }
+
+    /// 获取会话快照
+    pub fn snapshot(&self) -> SessionSnapshot {
+        SessionSnapshot {
+            now_step: self.now_step.clone(),
+            variables: self.variables.clone(),
+            seed: self.seed,
+            rng_position: self.rng.get_word_pos().to_string(),
+            variants: self.variants.clone(),
+            reprompt: self.reprompt,
+            form: self.form.clone(),
+            clarify: self.clarify.clone(),
+        }
+    }
+
+    /// 获取随机数种子
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// 替换时钟，默认为系统时钟（可用于测试）
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
     /// 获取机器人名
     pub fn get_bot_name(&self) -> &str {
         self.config.get_bot_name()
@@ -46,20 +222,68 @@ impl Session {
         self.now_step == "end"
     }
 
-    /// 输出
+    /// 输出（纯文本形式）
     pub fn output(&self) -> Result<String, &'static str> {
+        Ok(self.message()?.to_plain())
+    }
+
+    /// 输出（富消息形式），菜单步骤会附上带序号的选项，输入无效时先给出提示
+    /// 表单步骤在第一个字段前输出描述，之后输出当前字段的提示
+    /// 等待澄清时只输出询问和带序号的候选转移
+    pub fn message(&self) -> Result<Message, &'static str> {
         let step = self.config.get_step(&self.now_step).ok_or("Invalid step")?;
-        Ok(step.description.fmt(&self.variables)?)
+        let index = self.variants.get(&self.now_step).copied().unwrap_or(0);
+        let mut lines = vec![step.get_description(index).fmt(&self.variables)?];
+        if let Some(menu) = &step.menu {
+            if self.reprompt {
+                lines.insert(0, menu.invalid.fmt(&self.variables)?);
+            }
+            lines.push(menu.prompt());
+        }
+        if let Some(form) = &step.form {
+            let field = form.fields.get(self.form.field).ok_or("Invalid form")?;
+            if self.form.retry {
+                lines = vec![field.retry.fmt(&self.variables)?];
+            } else if self.form.field > 0 {
+                lines.clear();
+            }
+            lines.push(field.prompt.fmt(&self.variables)?);
+        }
+        if let Some(clarification) = self.config.get_clarification() {
+            if !self.clarify.candidates.is_empty() {
+                let labels = self
+                    .clarify
+                    .candidates
+                    .iter()
+                    .filter_map(|&i| step.transaction.get(i))
+                    .map(|transaction| transaction.label());
+                lines = vec![clarification.prompt.fmt(&self.variables)?, numbered(labels)];
+            }
+        }
+        lines.retain(|line| line.is_empty().not());
+        // 遮盖输出中的敏感词
+        let sensitive = self.config.get_sensitive();
+        let text = sensitive.mask(&lines.join("\n"));
+        let elements = step
+            .elements
+            .iter()
+            .map(|element| {
+                element
+                    .fmt(&self.variables)?
+                    .try_map(|field| Ok::<_, &'static str>(sensitive.mask(field)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Message { text, elements })
     }
 
     /// 处理空输入情况，即可以自动跳转的情况
     pub fn handle_empty_input(&mut self) -> Result<bool, String> {
-        let step = self.config.get_step(&self.now_step).ok_or("Invalid step")?;
+        let config = Arc::clone(&self.config);
+        let step = config.get_step(&self.now_step).ok_or("Invalid step")?;
         for transaction in &step.transaction {
-            if transaction.pattern == "" && transaction.compares.calc(&self.variables)? {
+            if transaction.is_empty() && self.check_transaction(transaction)? {
                 // Empty pattern
-                transaction.operation.calculate(&mut self.variables)?;
-                self.now_step = transaction.step.clone();
+                self.apply_transaction(transaction)?;
                 return Ok(true);
             }
         }
@@ -72,17 +296,17 @@ impl Session {
             if self.now_step == "end" {
                 break;
             }
-            let step = self.config.get_step(&self.now_step).ok_or("Invalid step")?;
-            if step.description.is_empty().not() {
+            let config = Arc::clone(&self.config);
+            let step = config.get_step(&self.now_step).ok_or("Invalid step")?;
+            if step.is_silent().not() {
                 break;
             }
 
             let mut found_valid_transaction = false;
             for transaction in &step.transaction {
-                if transaction.pattern == "" && transaction.compares.calc(&self.variables)? {
+                if transaction.is_empty() && self.check_transaction(transaction)? {
                     // Empty pattern
-                    transaction.operation.calculate(&mut self.variables)?;
-                    self.now_step = transaction.step.clone();
+                    self.apply_transaction(transaction)?;
                     found_valid_transaction = true;
                     break;
                 }
@@ -96,38 +320,239 @@ impl Session {
     }
 
     /// 处理输入
-    pub fn handle_input(&mut self, input: &str) -> Result<(), String> {
-        let step = self.config.get_step(&self.now_step).ok_or("Invalid step")?;
+    /// 变量 input 为原始输入，匹配时使用规范化后的输入，表单使用原始输入
+    pub fn handle_input(&mut self, raw: &str) -> Result<(), String> {
+        let config = Arc::clone(&self.config);
+        let step = config.get_step(&self.now_step).ok_or("Invalid step")?;
         self.variables
-            .insert("input".to_string(), VariableType::Str(input.to_string()));
-        for transaction in &step.transaction {
-            if transaction.pattern == "" {
-                // Empty pattern
-                continue;
+            .insert("input".to_string(), VariableType::Str(raw.to_string()));
+        let input = &config.normalize(raw);
+        self.reprompt = false;
+        // 更新会话的情感得分
+        let mut escalate = None;
+        if let Some(sentiment) = config.get_sentiment() {
+            let score = match self.variables.get("sentiment") {
+                Some(VariableType::Float(score)) => sentiment.update(*score, raw),
+                _ => sentiment.update(0.0, raw),
+            };
+            self.variables
+                .insert("sentiment".to_string(), VariableType::Float(score));
+            escalate = sentiment.escalate(score);
+        }
+        // 检测敏感词，声明了跳转步骤时直接跳转
+        let sensitive = config.get_sensitive();
+        if sensitive.is_empty().not() {
+            let flagged = sensitive.contains(raw) || sensitive.contains(input);
+            self.variables
+                .insert("sensitive".to_string(), VariableType::Int(flagged as i32));
+            if flagged && sensitive.step().is_empty().not() {
+                return self.apply(&Operations::new(), sensitive.step());
             }
-            if is_regex(&transaction.pattern) {
-                let re = Regex::new(&transaction.pattern).map_err(|_| "Invalid pattern")?;
-                if re.is_match(input) && transaction.compares.calc(&self.variables)? {
-                    transaction.operation.calculate(&mut self.variables)?;
-                    self.now_step = transaction.step.clone();
-                    return Ok(());
-                }
-            } else {
-                if transaction.pattern == input && transaction.compares.calc(&self.variables)? {
-                    transaction.operation.calculate(&mut self.variables)?;
-                    self.now_step = transaction.step.clone();
+        }
+        // 情感得分达到阈值时跳转，并重置得分以免反复跳转
+        if let Some(step) = escalate {
+            self.variables
+                .insert("sentiment".to_string(), VariableType::Float(0.0));
+            return self.apply(&Operations::new(), step);
+        }
+        // 回答澄清时恢复原来的输入，回答无效时按新的输入处理
+        let clarify = std::mem::take(&mut self.clarify);
+        let candidates: Vec<&Transaction> = clarify
+            .candidates
+            .iter()
+            .filter_map(|&i| step.transaction.get(i))
+            .collect();
+        let labels: Vec<&str> = candidates.iter().map(|t| t.label()).collect();
+        if let Some(i) = choose_label(&labels, input) {
+            self.variables
+                .insert("input".to_string(), VariableType::Str(clarify.input));
+            return self.apply_transaction(candidates[i]);
+        }
+        if let Some(option) = step.menu.as_ref().and_then(|menu| menu.choose(input)) {
+            return self.apply(&option.operation, &option.step);
+        }
+        // 只有步骤中有按意图匹配的转移时才打分
+        let scores = if step.transaction.iter().any(|t| !t.intent.is_empty()) {
+            config.get_intents().classify(input)
+        } else {
+            Vec::new()
+        };
+        if let Some(clarification) = config.get_clarification() {
+            if self.clarify_transaction(
+                &step.transaction,
+                input,
+                raw,
+                &scores,
+                clarification.margin,
+            )? {
+                return Ok(());
+            }
+        } else {
+            for transaction in &step.transaction {
+                if match_transaction(transaction, input, raw, &scores, config.get_extractors())?
+                    .is_some()
+                    && self.check_transaction(transaction)?
+                {
+                    self.apply_transaction(transaction)?;
                     return Ok(());
                 }
             }
         }
+        if let Some(form) = &step.form {
+            return self.fill_form(form, raw);
+        }
+        if step.menu.is_some() {
+            // 菜单步骤停留在原步骤并重新提示
+            self.reprompt = true;
+            return Ok(());
+        }
         Err("Invalid input".to_string())
     }
 
+    // 选取得分最高的转移，有多个得分接近的转移时等待用户澄清，没有匹配的转移时返回 false
+    // 得分为 1 的确定匹配（如精确匹配和正则表达式匹配）不参与比较，只比较在第一个确定匹配之前声明的
+    // 意图和模糊匹配；第一个匹配的转移就是确定匹配时直接执行，避免与兜底的转移（如 .*）产生歧义
+    fn clarify_transaction(
+        &mut self,
+        transactions: &[Transaction],
+        input: &str,
+        raw: &str,
+        scores: &[(&str, f64)],
+        margin: f64,
+    ) -> Result<bool, String> {
+        let mut candidates = Vec::new();
+        let mut definite = None;
+        for (i, transaction) in transactions.iter().enumerate() {
+            let extractors = self.config.get_extractors();
+            if let Some(score) = match_transaction(transaction, input, raw, scores, extractors)? {
+                if self.check_transaction(transaction)? {
+                    if score >= 1.0 {
+                        definite = Some(i);
+                        break;
+                    }
+                    candidates.push((i, score));
+                }
+            }
+        }
+        // 稳定排序，得分相同时保持声明顺序
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let Some(&(best, top)) = candidates.first() else {
+            return match definite {
+                Some(i) => self.apply_transaction(&transactions[i]).map(|_| true),
+                None => Ok(false),
+            };
+        };
+        let mut close: Vec<usize> = Vec::new();
+        for &(i, score) in &candidates {
+            // 名称相同的转移只保留第一个
+            if top - score <= margin
+                && close
+                    .iter()
+                    .all(|&j| transactions[j].label() != transactions[i].label())
+            {
+                close.push(i);
+            }
+        }
+        if close.len() > 1 {
+            self.clarify = ClarifyState {
+                candidates: close,
+                input: raw.to_string(),
+            };
+        } else {
+            self.apply_transaction(&transactions[best])?;
+        }
+        Ok(true)
+    }
+
+    // 判断转移条件是否满足
+    fn check_transaction(&self, transaction: &Transaction) -> Result<bool, String> {
+        transaction
+            .compares
+            .calc(&self.variables, self.config.get_calendar())
+    }
+
+    // 从原始输入中提取实体存入变量，然后执行转移的操作并跳转
+    fn apply_transaction(&mut self, transaction: &Transaction) -> Result<(), String> {
+        let raw = match self.variables.get("input") {
+            Some(VariableType::Str(raw)) => raw.clone(),
+            _ => String::new(),
+        };
+        for extraction in transaction.extract.iter() {
+            let value = self
+                .config
+                .get_extractors()
+                .extract(&extraction.extractor, &raw)?;
+            if let Some(value) = value {
+                self.variables
+                    .insert(extraction.variable.clone(), VariableType::Str(value));
+            }
+        }
+        self.apply(&transaction.operation, &transaction.step)
+    }
+
+    // 执行操作并跳转到下一步
+    fn apply(&mut self, operation: &Operations, step: &str) -> Result<(), String> {
+        let mut context = Context {
+            rng: &mut self.rng,
+            clock: self.clock.as_ref(),
+            calendar: self.config.get_calendar(),
+            segmenter: self.config.get_dictionary().segmenter().clone(),
+            faq: self.config.get_faq(),
+        };
+        operation.calculate(&mut self.variables, &mut context)?;
+        self.now_step = step.to_string();
+        self.form = FormState::default();
+        self.clarify = ClarifyState::default();
+        self.choose_variant();
+        Ok(())
+    }
+
+    // 填写表单的当前字段，全部填写完成后执行操作并跳转
+    fn fill_form(&mut self, form: &Form, input: &str) -> Result<(), String> {
+        let field = form.fields.get(self.form.field).ok_or("Invalid form")?;
+        let variable = self
+            .variables
+            .get(&field.variable)
+            .ok_or_else(|| format!("Variable {} not found", field.variable))?;
+        match field.parse(input, variable) {
+            Some(value) => {
+                self.variables.insert(field.variable.clone(), value);
+                self.form = FormState {
+                    field: self.form.field + 1,
+                    ..FormState::default()
+                };
+                if self.form.field == form.fields.len() {
+                    return self.apply(&form.operation, &form.step);
+                }
+            }
+            None => {
+                self.form.attempts += 1;
+                self.form.retry = true;
+                if field.max_attempts > 0 && self.form.attempts >= field.max_attempts {
+                    let fail = form.fail.as_deref().ok_or("Invalid form")?;
+                    return self.apply(&Operations::new(), fail);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // 进入步骤时选取候选输出，没有候选输出时不消耗随机数
+    fn choose_variant(&mut self) {
+        let Some(step) = self.config.get_step(&self.now_step) else {
+            return;
+        };
+        if step.variants.is_empty() {
+            return;
+        }
+        let last = self.variants.get(&self.now_step).copied();
+        let index = step.policy.choose(&step.weights(), last, &mut self.rng);
+        self.variants.insert(self.now_step.clone(), index);
+    }
+
     /// 异步版本输出
     pub async fn output_async<W: AsyncWrite + Unpin>(&self, mut writer: W) -> io::Result<()> {
-        let output = self
-            .output()
-            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        let output = self.output().map_err(io::Error::other)?;
         writer.write_all(output.as_bytes()).await?;
         Ok(())
     }
@@ -139,8 +564,7 @@ impl Session {
     ) -> io::Result<()> {
         let mut input = String::new();
         reader.read_to_string(&mut input).await?;
-        self.handle_input(&input)
-            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        self.handle_input(&input).map_err(io::Error::other)?;
         Ok(())
     }
 
@@ -157,8 +581,9 @@ impl Session {
         Ok(false)
     }
 
-    /// 标准输入输出版本（同步）
+    /// 标准输入输出版本（同步），开始时输出随机数种子以便复现对话
     pub fn run_stdio(&mut self) -> Result<(), String> {
+        println!("[seed {}]", self.get_seed());
         loop {
             if self.need_stop()? {
                 break;
@@ -170,9 +595,637 @@ impl Session {
             }
             println!("[user]");
             let mut input = String::new();
-            std::io::stdin().read_line(&mut input).map_err(|_| "Failed to read line")?;
+            std::io::stdin()
+                .read_line(&mut input)
+                .map_err(|_| "Failed to read line")?;
             self.handle_input(input.trim())?;
         }
         Ok(())
     }
 }
+
+impl SessionSnapshot {
+    /// 获取随机数种子
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// 获取当前步骤
+    pub fn get_step(&self) -> &str {
+        &self.now_step
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 相同种子的会话随机结果一致，快照恢复后继续一致
+    #[test]
+    fn test_seed() {
+        let config = crate::load_config("demo/demo.yaml").unwrap();
+        let mut session1 = Session::with_seed(config.clone(), 42);
+        let mut session2 = Session::with_seed(config.clone(), 42);
+        session1.need_stop().unwrap();
+        session2.need_stop().unwrap();
+        assert_eq!(session1.output(), session2.output());
+
+        let snapshot = session1.snapshot();
+        assert_eq!(snapshot.get_seed(), 42);
+        let yaml = serde_yaml::to_string(&snapshot).unwrap();
+        let snapshot: SessionSnapshot = serde_yaml::from_str(&yaml).unwrap();
+        let mut session3 = Session::restore(config, &snapshot).unwrap();
+        for session in [&mut session1, &mut session3] {
+            session.handle_input("0").unwrap();
+            session.need_stop().unwrap();
+        }
+        assert_eq!(session1.output(), session3.output());
+    }
+
+    // 候选输出按策略轮换，快照恢复后继续轮换
+    #[test]
+    fn test_variants() {
+        let yaml = r#"
+bot_name: Sakiko
+start_step: start
+steps:
+  start:
+    variants:
+      - description: [A, []]
+      - description: [B, []]
+        weight: 2
+      - description: [C, []]
+    policy: RoundRobin
+    transaction:
+      - pattern: next
+        step: start
+"#;
+        let config = Arc::new(SakikoConfig::deserialize(yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(config.clone(), 0);
+        let mut outputs = Vec::new();
+        for _ in 0..4 {
+            outputs.push(session.output().unwrap());
+            session.handle_input("next").unwrap();
+        }
+        assert_eq!(outputs, ["A", "B", "C", "A"]);
+
+        let restored = Session::restore(config, &session.snapshot()).unwrap();
+        assert_eq!(restored.output().unwrap(), "B");
+    }
+
+    // 菜单按序号、选项名或唯一前缀选择，无效输入时重新提示
+    #[test]
+    fn test_menu() {
+        let yaml = r#"
+bot_name: Sakiko
+start_step: start
+variables:
+  count: !Int 0
+steps:
+  start:
+    description: [请选择：, []]
+    menu:
+      options:
+        - label: 查询订单
+          step: start
+          operation:
+            - !Let [count, !Int 1]
+        - label: 查询物流
+          step: start
+        - label: Human
+          step: end
+    transaction:
+      - pattern: 退出
+        step: end
+"#;
+        let config = Arc::new(SakikoConfig::deserialize(yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(config, 0);
+        assert_eq!(
+            session.output().unwrap(),
+            "请选择：\n1. 查询订单\n2. 查询物流\n3. Human"
+        );
+
+        session.handle_input("1").unwrap();
+        assert_eq!(session.variables.get("count"), Some(&VariableType::Int(1)));
+        // 前缀不唯一、序号越界时重新提示
+        session.handle_input("查询").unwrap();
+        assert!(session
+            .output()
+            .unwrap()
+            .starts_with("无效的选项，请重新选择。\n请选择："));
+        session.handle_input("4").unwrap();
+        assert!(session.reprompt);
+        session.handle_input("查询物").unwrap();
+        assert!(session.output().unwrap().starts_with("请选择："));
+        session.handle_input("hu").unwrap();
+        assert!(session.is_end());
+    }
+
+    // 表单依次填写字段，校验失败时重试，超过次数后跳转到失败步骤
+    #[test]
+    fn test_form() {
+        let yaml = r#"
+bot_name: Sakiko
+start_step: start
+variables:
+  name: !Str ""
+  age: !Int 0
+steps:
+  start:
+    description: [请填写资料, []]
+    form:
+      fields:
+        - variable: name
+          prompt: [请输入姓名, []]
+        - variable: age
+          prompt: [请输入年龄, []]
+          validators:
+            - !Range [1, 120]
+          retry: [年龄应为 1 到 120 的整数, []]
+          max_attempts: 2
+      step: done
+      fail: start
+  done:
+    description: ["{name}，{age} 岁", []]
+    transaction: []
+"#;
+        let config = Arc::new(SakikoConfig::deserialize(yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(config, 0);
+        assert_eq!(session.output().unwrap(), "请填写资料\n请输入姓名");
+        session.handle_input("祥子").unwrap();
+        assert_eq!(session.output().unwrap(), "请输入年龄");
+        session.handle_input("200").unwrap();
+        assert_eq!(
+            session.output().unwrap(),
+            "年龄应为 1 到 120 的整数\n请输入年龄"
+        );
+        // 超过次数后回到表单开头
+        session.handle_input("abc").unwrap();
+        assert_eq!(session.output().unwrap(), "请填写资料\n请输入姓名");
+        session.handle_input("祥子").unwrap();
+        session.handle_input("18").unwrap();
+        assert_eq!(session.output().unwrap(), "祥子，18 岁");
+    }
+
+    // 按意图匹配转移，得分低于阈值时不匹配
+    #[test]
+    fn test_intent() {
+        let yaml = r#"
+bot_name: Sakiko
+start_step: start
+intents:
+  order:
+    examples:
+      - 我的订单到哪了
+      - 查一下订单状态
+  human:
+    keywords:
+      - 人工
+steps:
+  start:
+    description: [您好, []]
+    transaction:
+      - pattern: 退出
+        step: end
+      - intent: human
+        step: human
+      - intent: order
+        confidence: 0.6
+        step: order
+  order:
+    description: [正在查询订单, []]
+    transaction: []
+  human:
+    description: [正在转接人工, []]
+    transaction: []
+"#;
+        let config = Arc::new(SakikoConfig::deserialize(yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(Arc::clone(&config), 0);
+        assert!(!session.handle_empty_input().unwrap());
+        assert!(session.handle_input("订单").is_err());
+        session.handle_input("帮我查下订单到哪了").unwrap();
+        assert_eq!(session.output().unwrap(), "正在查询订单");
+        let mut session = Session::with_seed(config, 0);
+        session.handle_input("转人工").unwrap();
+        assert_eq!(session.output().unwrap(), "正在转接人工");
+    }
+
+    // 多个转移的得分接近时询问用户，按回答跳转并恢复原来的输入
+    #[test]
+    fn test_clarify() {
+        let yaml = r#"
+bot_name: Sakiko
+start_step: start
+variables:
+  question: !Str ""
+intents:
+  order:
+    examples:
+      - 查订单
+  delivery:
+    examples:
+      - 查物流
+clarification:
+  margin: 0.1
+  prompt: [请问您是想：, []]
+steps:
+  start:
+    description: [您好, []]
+    transaction:
+      - intent: order
+        confidence: 0.3
+        label: 查询订单
+        step: order
+      - intent: delivery
+        confidence: 0.3
+        label: 查询物流
+        step: delivery
+        operation:
+          - !Inp question
+  order:
+    description: [正在查询订单, []]
+    transaction: []
+  delivery:
+    description: ["正在查询物流：{question}", []]
+    transaction: []
+"#;
+        let config = Arc::new(SakikoConfig::deserialize(yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(Arc::clone(&config), 0);
+        session.handle_input("查订单").unwrap();
+        assert_eq!(session.output().unwrap(), "正在查询订单");
+
+        let mut session = Session::with_seed(Arc::clone(&config), 0);
+        session.handle_input("查一下").unwrap();
+        assert_eq!(
+            session.output().unwrap(),
+            "请问您是想：\n1. 查询订单\n2. 查询物流"
+        );
+        let snapshot = session.snapshot();
+        session.handle_input("2").unwrap();
+        assert_eq!(session.output().unwrap(), "正在查询物流：查一下");
+
+        // 回答无效时按新的输入处理
+        let mut session = Session::restore(config, &snapshot).unwrap();
+        session.handle_input("查订单").unwrap();
+        assert_eq!(session.output().unwrap(), "正在查询订单");
+
+        // 兜底的转移是确定匹配，不参与比较
+        let yaml = yaml.replace(
+            "  order:\n    description",
+            "      - pattern: .*\n        step: fallback\n  fallback:\n    description: [没听懂, []]\n    transaction: []\n  order:\n    description",
+        );
+        let config = Arc::new(SakikoConfig::deserialize(&yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(Arc::clone(&config), 0);
+        session.handle_input("查订单").unwrap();
+        assert_eq!(session.output().unwrap(), "正在查询订单");
+        let mut session = Session::with_seed(Arc::clone(&config), 0);
+        session.handle_input("查一下").unwrap();
+        assert_eq!(
+            session.output().unwrap(),
+            "请问您是想：\n1. 查询订单\n2. 查询物流"
+        );
+        let mut session = Session::with_seed(config, 0);
+        session.handle_input("你好").unwrap();
+        assert_eq!(session.output().unwrap(), "没听懂");
+    }
+
+    // 模糊匹配转移和模糊查询，输入有错别字时仍能匹配
+    #[test]
+    fn test_fuzzy() {
+        let config = crate::load_config("demo/demo2.yaml").unwrap();
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(config, 0);
+        session.need_stop().unwrap();
+        session.handle_empty_input().unwrap();
+        session.handle_input("算法设计与分折").unwrap();
+        session.handle_empty_output().unwrap();
+        assert!(session
+            .output()
+            .unwrap()
+            .starts_with("课程“算法设计与分析”的信息如下："));
+
+        let yaml = r#"
+bot_name: Sakiko
+start_step: start
+steps:
+  start:
+    description: [您好, []]
+    transaction:
+      - pattern: 人工客服
+        fuzzy: 0.7
+        step: human
+  human:
+    description: [正在转接人工, []]
+    transaction: []
+"#;
+        let config = Arc::new(SakikoConfig::deserialize(yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(config, 0);
+        assert!(session.handle_input("人工").is_err());
+        session.handle_input("人工克服").unwrap();
+        assert_eq!(session.output().unwrap(), "正在转接人工");
+    }
+
+    // 匹配前规范化输入，变量 input 保留原始输入
+    #[test]
+    fn test_normalize() {
+        let yaml = r#"
+bot_name: Sakiko
+start_step: start
+variables:
+  raw: !Str ""
+normalize:
+  - HalfWidth
+  - Lowercase
+  - Simplified
+  - StripPunctuation
+  - CollapseWhitespace
+  - !Synonyms
+      订单: [单子]
+steps:
+  start:
+    description: [您好, []]
+    transaction:
+      - pattern: ^查询订单 vip$
+        step: order
+        operation:
+          - !Inp raw
+  order:
+    description: ["收到：{raw}", []]
+    transaction: []
+"#;
+        let config = Arc::new(SakikoConfig::deserialize(yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(config, 0);
+        session.handle_input(" 查詢單子　ＶＩＰ！").unwrap();
+        assert_eq!(session.output().unwrap(), "收到： 查詢單子　ＶＩＰ！");
+    }
+
+    // 测试拼音匹配
+    #[test]
+    fn test_pinyin() {
+        let yaml = r#"
+bot_name: Sakiko
+start_step: start
+steps:
+  start:
+    description: [您好, []]
+    transaction:
+      - pattern: 退出
+        pinyin: true
+        step: exit
+  exit:
+    description: [再见, []]
+    transaction: []
+"#;
+        let config = Arc::new(SakikoConfig::deserialize(yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        for input in ["退出", "TuiChu", "tui chu", "tc"] {
+            let mut session = Session::with_seed(Arc::clone(&config), 0);
+            session.handle_input(input).unwrap();
+            assert_eq!(session.output().unwrap(), "再见");
+        }
+        let mut session = Session::with_seed(config, 0);
+        assert!(session.handle_input("tuic").is_err());
+    }
+
+    // 测试用户词典和分词
+    #[test]
+    fn test_segment() {
+        let yaml = r#"
+bot_name: Sakiko
+start_step: start
+variables:
+  question: !Str ""
+  words: !StrVec []
+dictionary:
+  words: [会员卡, 卡顿]
+intents:
+  card:
+    keywords: [会员卡]
+steps:
+  start:
+    description: [您好, []]
+    transaction:
+      - intent: card
+        step: card
+        operation:
+          - !Inp question
+          - !Seg [words, question]
+  card:
+    description: ["{}", [words]]
+    transaction: []
+"#;
+        let config = Arc::new(SakikoConfig::deserialize(yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(Arc::clone(&config), 0);
+        session.handle_input("我的会员卡丢了").unwrap();
+        assert_eq!(
+            session.variables.get("words"),
+            Some(&VariableType::StrVec(
+                ["我", "的", "会员卡", "丢", "了"]
+                    .map(String::from)
+                    .to_vec()
+            ))
+        );
+        // “会员卡顿”分词为“会员 / 卡顿”，按词匹配时不包含关键词
+        let mut session = Session::with_seed(config, 0);
+        assert!(session.handle_input("会员卡顿怎么办").is_err());
+    }
+
+    // 测试实体提取，只声明提取规则的转移在提取到实体时匹配
+    #[test]
+    fn test_extract() {
+        let yaml = r#"
+bot_name: Sakiko
+start_step: start
+variables:
+  phone: !Str ""
+  ticket: !Str ""
+extractors:
+  ticket:
+    pattern: (?i)工单\s*(t-?\d{6})
+    normalize: [Uppercase, StripPunctuation]
+steps:
+  start:
+    description: [您好, []]
+    transaction:
+      - pattern: 工单
+        extract: ticket -> ticket
+        step: ticket
+      - extract: phone -> phone
+        step: phone
+  ticket:
+    description: ["工单 {ticket} 处理中", []]
+    transaction: []
+  phone:
+    description: ["将致电 {phone}", []]
+    transaction: []
+"#;
+        let config = Arc::new(SakikoConfig::deserialize(yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(Arc::clone(&config), 0);
+        session.handle_input("请回电 138 0013 8000").unwrap();
+        assert_eq!(session.output().unwrap(), "将致电 13800138000");
+        let mut session = Session::with_seed(Arc::clone(&config), 0);
+        session.handle_input("工单 t-123456 进度如何").unwrap();
+        assert_eq!(session.output().unwrap(), "工单 T123456 处理中");
+        // 提到工单但没有工单号时不匹配
+        let mut session = Session::with_seed(Arc::clone(&config), 0);
+        assert!(session.handle_input("我的工单呢").is_err());
+
+        let yaml = yaml.replace("phone -> phone", "phone -> missing");
+        let config = SakikoConfig::deserialize(&yaml).unwrap();
+        assert!(crate::check::check_config(&config).is_err());
+    }
+
+    // 测试敏感词过滤，输入包含敏感词时标记或跳转，输出中的敏感词被遮盖
+    #[test]
+    fn test_sensitive() {
+        let yaml = r#"
+bot_name: Sakiko
+start_step: start
+variables:
+  one: !Int 1
+  text: !Str ""
+sensitive:
+  words: [笨蛋]
+steps:
+  start:
+    description: [您好, []]
+    transaction:
+      - pattern: .
+        compares:
+          - compare: And
+            compare_type: !Eq [sensitive, one]
+        step: warning
+        operation:
+          - !Inp text
+      - pattern: .
+        step: echo
+        operation:
+          - !Inp text
+  warning:
+    description: ["请文明用语：{text}", []]
+    transaction: []
+  echo:
+    description: ["您说：{text}", []]
+    transaction: []
+  blocked:
+    description: [您的消息包含不当内容, []]
+    transaction: []
+"#;
+        let config = Arc::new(SakikoConfig::deserialize(yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(Arc::clone(&config), 0);
+        session.handle_input("你这个笨 蛋").unwrap();
+        assert_eq!(session.output().unwrap(), "请文明用语：你这个* *");
+        let mut session = Session::with_seed(Arc::clone(&config), 0);
+        session.handle_input("我是笨蛋").unwrap();
+        assert_eq!(session.output().unwrap(), "请文明用语：我是**");
+        let mut session = Session::with_seed(config, 0);
+        session.handle_input("你好").unwrap();
+        assert_eq!(session.output().unwrap(), "您说：你好");
+
+        let yaml = yaml.replace("  words: [笨蛋]", "  words: [笨蛋]\n  step: blocked");
+        let config = Arc::new(SakikoConfig::deserialize(&yaml).unwrap());
+        let mut session = Session::with_seed(config, 0);
+        session.handle_input("笨蛋").unwrap();
+        assert_eq!(session.output().unwrap(), "您的消息包含不当内容");
+    }
+
+    // 测试情感分析，会话得分累积到阈值时转人工
+    #[test]
+    fn test_sentiment() {
+        let yaml = r#"
+bot_name: Sakiko
+start_step: start
+sentiment:
+  negative: [坑爹]
+  threshold: -0.9
+  step: human
+steps:
+  start:
+    description: [您好, []]
+    transaction:
+      - pattern: .
+        step: start
+  human:
+    description: [正在为您转接人工客服, []]
+    transaction: []
+"#;
+        let config = Arc::new(SakikoConfig::deserialize(yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(config, 0);
+        session.handle_input("谢谢").unwrap();
+        assert!(matches!(
+            session.variables.get("sentiment"),
+            Some(VariableType::Float(score)) if *score > 0.0
+        ));
+        session.handle_input("太慢了！").unwrap();
+        assert_eq!(session.now_step, "start");
+        session.handle_input("真坑爹，我要投诉").unwrap();
+        assert_eq!(session.output().unwrap(), "正在为您转接人工客服");
+        assert_eq!(
+            session.variables.get("sentiment"),
+            Some(&VariableType::Float(0.0))
+        );
+
+        let yaml = yaml.replace("step: human", "step: missing");
+        let config = SakikoConfig::deserialize(&yaml).unwrap();
+        assert!(crate::check::check_config(&config).is_err());
+    }
+
+    // 测试知识库检索，得分低于阈值时转人工
+    #[test]
+    fn test_faq() {
+        let yaml = r#"
+bot_name: Sakiko
+start_step: start
+variables:
+  question: !Str ""
+  answer: !Str ""
+  score: !Float 0.0
+faq:
+  entries:
+    - question: 怎么申请退款
+      answer: 在订单页点击申请退款即可
+    - question: 发货要多久
+      answer: 下单后 48 小时内发货
+steps:
+  start:
+    description: [您好, []]
+    transaction:
+      - pattern: .
+        step: answer
+        operation:
+          - !Inp question
+          - !Faq [answer, score, question]
+  answer:
+    description: ["{% if score >= 0.3 %}{answer}{% else %}正在为您转接人工客服{% end %}", []]
+    transaction:
+      - pattern: .
+        step: answer
+        operation:
+          - !Inp question
+          - !Faq [answer, score, question]
+"#;
+        let config = Arc::new(SakikoConfig::deserialize(yaml).unwrap());
+        assert!(crate::check::check_config(&config).is_ok());
+        let mut session = Session::with_seed(config, 0);
+        session.handle_input("多久能发货？").unwrap();
+        assert_eq!(session.output().unwrap(), "下单后 48 小时内发货");
+        session.handle_input("我的包裹丢了").unwrap();
+        assert_eq!(session.output().unwrap(), "正在为您转接人工客服");
+
+        let yaml = yaml.replace("answer: 下单后 48 小时内发货", "answer: \"\"");
+        let config = SakikoConfig::deserialize(&yaml).unwrap();
+        assert!(crate::check::check_config(&config).is_err());
+    }
+}
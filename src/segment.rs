@@ -0,0 +1,348 @@
+/// Sakiko 的中文分词
+/// 基于词典分词，内置常用词词典，可在配置中添加用户词典（词语列表或词典文件），不依赖网络。
+/// 英文和数字按单词切分（转为小写），标点和空白作为分隔，其余文字（如汉字）按词典切分：
+/// 选取词数最少的切分，词数相同时单字最少，仍相同时靠后的词更长（即逆向最大匹配）。
+use ::serde::{Deserialize, Serialize};
+use ::std::collections::HashSet;
+use ::std::sync::{Arc, OnceLock};
+
+// 内置词典，各词以空格分隔
+const WORDS: &str = "\
+    我们 你们 他们 她们 它们 自己 大家 别人 人家 咱们 这个 那个 哪个 \
+    这些 那些 哪些 这里 那里 哪里 这儿 那儿 哪儿 这样 那样 怎样 怎么 \
+    怎么样 怎么办 为什么 什么 多少 几个 如何 是否 是不是 有没有 能不能 可不可以 \
+    要不要 会不会 对不对 好不好 行不行 一个 一下 一些 一点 一直 一起 一样 \
+    一定 一般 一共 一次 一切 一边 一旦 已经 还是 或者 而且 但是 可是 \
+    因为 所以 如果 虽然 然后 然而 于是 并且 不过 只是 只要 只有 除了 \
+    除非 即使 即便 无论 不管 尽管 由于 因此 从而 以便 为了 关于 对于 \
+    根据 按照 通过 经过 随着 比如 例如 以及 还有 另外 此外 同时 其实 \
+    其他 其中 其余 当然 确实 实在 真的 的确 也许 可能 大概 应该 必须 \
+    需要 可以 能够 愿意 希望 想要 打算 准备 曾经 正在 马上 立刻 立即 \
+    赶紧 赶快 尽快 终于 总是 经常 常常 往往 一向 从来 永远 仍然 依然 \
+    还要 再次 重新 继续 开始 结束 完成 停止 暂停 现在 今天 明天 后天 \
+    昨天 前天 大后天 大前天 今年 明年 去年 前年 今晚 明晚 昨晚 早上 上午 \
+    中午 下午 晚上 傍晚 夜里 半夜 凌晨 白天 周末 工作日 节假日 假期 节日 \
+    春节 国庆 元旦 中秋 端午 清明 劳动节 星期 星期一 星期二 星期三 星期四 \
+    星期五 星期六 星期日 星期天 周一 周二 周三 周四 周五 周六 周日 礼拜 \
+    礼拜天 上周 下周 本周 这周 上个月 下个月 这个月 本月 月底 月初 年底 \
+    年初 时间 时候 小时 分钟 秒钟 日期 时刻 刚才 刚刚 以前 以后 之前 \
+    之后 最近 将来 未来 过去 目前 当前 当时 平时 每天 每周 每月 每年 \
+    几点 几号 多久 多长时间 你好 您好 谢谢 感谢 多谢 不客气 没关系 对不起 \
+    抱歉 不好意思 麻烦 请问 再见 拜拜 早安 晚安 欢迎 辛苦 打扰 好的 好吧 \
+    可以的 没问题 知道 明白 了解 清楚 理解 同意 不同意 确定 确认 取消 \
+    返回 退出 帮助 菜单 首页 主页 上一步 下一步 跳过 重试 重来 重置 客服 \
+    人工 人工客服 机器人 客户 顾客 用户 会员 账号 账户 帐号 密码 手机 \
+    手机号 电话 号码 邮箱 邮件 短信 验证码 地址 收货地址 姓名 名字 身份证 \
+    证件 信息 资料 个人信息 注册 登录 登陆 注销 绑定 解绑 修改 更改 设置 \
+    查看 查询 搜索 查找 找回 忘记 重设 订单 下单 购买 购物 购物车 商品 \
+    产品 物品 东西 价格 价钱 多少钱 优惠 优惠券 折扣 打折 促销 活动 满减 \
+    红包 积分 余额 充值 提现 转账 付款 支付 付钱 结账 结算 收款 退款 \
+    退货 换货 退换 退钱 退费 售后 保修 维修 质量 问题 故障 损坏 破损 \
+    坏了 缺货 有货 库存 发货 送货 配送 快递 物流 包裹 运费 邮费 包邮 \
+    签收 收货 收到 没收到 到货 取件 寄件 运单 单号 订单号 快递单号 发票 \
+    开票 收据 账单 合同 协议 条款 政策 规则 流程 步骤 方法 方式 办法 \
+    手续 银行 银行卡 信用卡 借记卡 储蓄卡 微信 支付宝 现金 零钱 钱包 利息 \
+    贷款 还款 分期 额度 手续费 费用 收费 免费 价格表 套餐 流量 话费 宽带 \
+    网络 信号 网速 上网 无线 路由器 电脑 手机壳 充电器 耳机 电池 屏幕 \
+    系统 软件 应用 程序 版本 更新 升级 下载 安装 卸载 打开 关闭 启动 \
+    重启 界面 页面 网站 网页 链接 二维码 扫码 客户端 服务器 数据 文件 \
+    图片 视频 照片 截图 服务 服务费 服务台 投诉 建议 意见 反馈 评价 评论 \
+    好评 差评 表扬 举报 申请 申诉 审核 审批 处理 解决 办理 预约 预订 \
+    预定 挂号 排队 等待 等候 催促 催单 加急 紧急 及时 准时 延迟 延误 \
+    超时 过期 有效期 期限 截止 营业 营业时间 上班 下班 值班 休息 放假 \
+    开门 关门 门店 商店 商场 超市 店铺 网店 门口 附近 位置 地点 地方 \
+    地图 导航 路线 方向 距离 学校 学生 老师 教师 同学 课程 课表 上课 \
+    下课 考试 成绩 分数 学分 作业 论文 毕业 入学 报名 选课 退课 教室 \
+    宿舍 食堂 图书馆 体育馆 操场 实验室 办公室 教务处 学院 大学 专业 年级 \
+    班级 学期 寒假 暑假 开学 放学 校园 校区 奖学金 助学金 学费 考研 \
+    研究生 本科生 博士 硕士 算法 数据结构 操作系统 计算机 编译 原理 设计 \
+    分析 数学 英语 语文 物理 化学 生物 历史 地理 政治 医院 医生 护士 \
+    病人 看病 门诊 急诊 挂号费 药品 药店 处方 检查 体检 治疗 手术 住院 \
+    出院 预防 疫苗 健康 身体 生病 感冒 发烧 头疼 咳嗽 过敏 天气 气温 \
+    温度 下雨 下雪 晴天 阴天 多云 刮风 大风 雨伞 空气 污染 雾霾 预报 \
+    旅游 旅行 出差 出发 到达 机票 火车票 车票 门票 酒店 宾馆 民宿 房间 \
+    入住 退房 航班 飞机 火车 高铁 动车 地铁 公交 公交车 出租车 打车 司机 \
+    乘客 座位 行李 托运 机场 车站 火车站 码头 签证 护照 景点 景区 导游 \
+    吃饭 早饭 午饭 晚饭 早餐 午餐 晚餐 外卖 点餐 点菜 饭店 餐厅 饭馆 \
+    咖啡 奶茶 饮料 啤酒 水果 蔬菜 米饭 面条 饺子 包子 馒头 鸡蛋 牛奶 \
+    面包 蛋糕 零食 口味 味道 好吃 难吃 工作 上班族 公司 单位 部门 老板 \
+    经理 领导 同事 员工 职员 招聘 应聘 面试 简历 工资 薪水 奖金 加班 \
+    请假 辞职 离职 入职 转正 实习 培训 会议 开会 项目 任务 计划 安排 \
+    报告 总结 方案 文档 表格 材料 中国 北京 上海 广州 深圳 天津 重庆 \
+    南京 杭州 苏州 武汉 成都 西安 长沙 郑州 济南 青岛 大连 沈阳 哈尔滨 \
+    长春 厦门 福州 合肥 昆明 贵阳 南宁 南昌 太原 石家庄 兰州 乌鲁木齐 \
+    拉萨 香港 澳门 台湾 全国 城市 省份 国家 世界 地区 本地 外地 国内 \
+    国外 海外 朋友 家人 父母 爸爸 妈妈 孩子 儿子 女儿 丈夫 妻子 老公 \
+    老婆 哥哥 姐姐 弟弟 妹妹 爷爷 奶奶 男人 女人 男生 女生 先生 女士 \
+    小姐 小朋友 老人 年轻人 宝宝 喜欢 讨厌 高兴 开心 快乐 满意 不满 \
+    不满意 失望 生气 愤怒 着急 担心 害怕 难过 伤心 郁闷 烦躁 无聊 舒服 \
+    难受 放心 安心 感动 惊喜 后悔 遗憾 可惜 糟糕 垃圾 骗子 欺骗 太差 \
+    太慢 太贵 便宜 划算 实惠 很好 非常 特别 十分 比较 有点 稍微 相当 \
+    完全 根本 绝对 几乎 差不多 大约 左右 以上 以下 以内 之间 之内 之外 \
+    最多 最少 至少 最好 最差 最后 最先 首先 其次 最终 没有 不是 不要 \
+    不用 不能 不会 不行 不对 不错 不好 不知道 不清楚 不明白 不需要 不可以 \
+    不想 不够 不少 不多 不再 不断 不停 不久 不同 相同 一致 区别 差别 \
+    对比 选择 决定 考虑 商量 讨论 联系 沟通 交流 回复 回答 答复 提问 \
+    疑问 咨询 询问 告诉 通知 提醒 说明 介绍 解释 描述 表示 认为 觉得 \
+    感觉 发现 看到 听到 听说 看见 遇到 碰到 收集 提供 得到 获得 拿到 \
+    给予 接受 拒绝 允许 禁止 限制 要求 规定 标准 条件 情况 状态 结果 \
+    原因 理由 目的 作用 影响 效果 好处 坏处 优点 缺点 特点 功能 性能 \
+    内容 部分 全部 所有 每个 任何 各种 多种 某些 整个 进行 发生 出现 \
+    存在 产生 造成 导致 引起 发展 变化 改变 提高 降低 增加 减少 扩大 \
+    缩小 加强 保持 保护 保证 保存 保留 删除 添加 编辑 复制 粘贴 上传 \
+    发送 接收 转发 分享 收藏 关注 订阅 点赞 登记 记录 统计 计算 测试 \
+    检测 验证 认证 授权 实名 实名认证 人脸 识别 指纹 安全 风险 隐私 泄露 \
+    冻结 解冻 封号 解封 异常 错误 失败 成功 正常 可用 不可用 无法 无效 \
+    有效 生效 失效 到期 续费 自动续费 开通 停用 启用 激活 一月 二月 三月 \
+    四月 五月 六月 七月 八月 九月 十月 十一月 十二月 一号 二号 三号 第一 \
+    第二 第三 一百 一千 一万 百分之 两个 三个 几天 几次 一天 两天 三天 \
+    一周 两周 一年 半年 半天 一半 整天 全天";
+
+/// 分词器
+#[derive(Debug)]
+pub struct Segmenter {
+    words: HashSet<String>,
+    max_len: usize, // 最长词的字数
+}
+
+impl Segmenter {
+    /// 创建分词器，包含内置词典和用户词语
+    pub fn new(words: impl IntoIterator<Item = String>) -> Segmenter {
+        let words: HashSet<String> = WORDS.split(' ').map(str::to_string).chain(words).collect();
+        let max_len = words.iter().map(|word| word.chars().count()).max();
+        Segmenter {
+            words,
+            max_len: max_len.unwrap_or(1),
+        }
+    }
+
+    /// 只包含内置词典的分词器
+    pub fn builtin() -> Arc<Segmenter> {
+        static BUILTIN: OnceLock<Arc<Segmenter>> = OnceLock::new();
+        Arc::clone(BUILTIN.get_or_init(|| Arc::new(Segmenter::new(Vec::new()))))
+    }
+
+    /// 分词，英文和数字按单词切分（转为小写），其余文字按词典切分
+    pub fn segment(&self, text: &str) -> Vec<String> {
+        let mut tokens = Vec::new();
+        let mut word = String::new();
+        let mut run: Vec<char> = Vec::new();
+        for c in text.chars().flat_map(char::to_lowercase) {
+            if c.is_ascii_alphanumeric() {
+                self.segment_run(&mut run, &mut tokens);
+                word.push(c);
+                continue;
+            }
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+            if c.is_alphanumeric() {
+                run.push(c);
+            } else {
+                self.segment_run(&mut run, &mut tokens);
+            }
+        }
+        if !word.is_empty() {
+            tokens.push(word);
+        }
+        self.segment_run(&mut run, &mut tokens);
+        tokens
+    }
+
+    // 按词典切分一段连续的文字并清空
+    fn segment_run(&self, run: &mut Vec<char>, tokens: &mut Vec<String>) {
+        if run.is_empty() {
+            return;
+        }
+        // best[i] 为前 i 个字的最优切分：(词数, 单字数, 最后一个词的起点)
+        let mut best: Vec<(usize, usize, usize)> = vec![(0, 0, 0); run.len() + 1];
+        for i in 1..=run.len() {
+            best[i] = (usize::MAX, usize::MAX, 0);
+            // 从长到短尝试最后一个词，得分相同时保留较长的
+            for len in (1..=self.max_len.min(i)).rev() {
+                let start = i - len;
+                if len > 1
+                    && !self
+                        .words
+                        .contains(&run[start..i].iter().collect::<String>())
+                {
+                    continue;
+                }
+                let candidate = (
+                    best[start].0 + 1,
+                    best[start].1 + (len == 1) as usize,
+                    start,
+                );
+                if (candidate.0, candidate.1) < (best[i].0, best[i].1) {
+                    best[i] = candidate;
+                }
+            }
+        }
+        let mut words = Vec::new();
+        let mut end = run.len();
+        while end > 0 {
+            let start = best[end].2;
+            words.push(run[start..end].iter().collect());
+            end = start;
+        }
+        tokens.extend(words.into_iter().rev());
+        run.clear();
+    }
+}
+
+/// 用户词典的来源
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Default)]
+pub struct DictionarySource {
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<String>, // 用户词语（可置空）
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>, // 词典文件路径，每行一个词，忽略空行和 # 开头的行（可置空）
+}
+
+/// 用户词典（序列化为词典来源），加载时读取词典文件并建立分词器
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(try_from = "DictionarySource", into = "DictionarySource")]
+pub struct Dictionary {
+    source: DictionarySource,
+    words: Vec<String>, // 用户词语和词典文件中的词
+    segmenter: Arc<Segmenter>,
+}
+
+// 分词器由词典来源唯一确定，相等性只看来源
+impl PartialEq for Dictionary {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        Dictionary {
+            source: DictionarySource::default(),
+            words: Vec::new(),
+            segmenter: Segmenter::builtin(),
+        }
+    }
+}
+
+impl TryFrom<DictionarySource> for Dictionary {
+    type Error = String;
+
+    fn try_from(source: DictionarySource) -> Result<Self, Self::Error> {
+        let mut words = source.words.clone();
+        for file in &source.files {
+            words.extend(
+                read_word_list(file)
+                    .map_err(|err| format!("Failed to read dictionary {}: {}", file, err))?,
+            );
+        }
+        let segmenter = if words.is_empty() {
+            Segmenter::builtin()
+        } else {
+            Arc::new(Segmenter::new(words.iter().map(|word| word.to_lowercase())))
+        };
+        Ok(Dictionary {
+            source,
+            words,
+            segmenter,
+        })
+    }
+}
+
+impl From<Dictionary> for DictionarySource {
+    fn from(dictionary: Dictionary) -> Self {
+        dictionary.source
+    }
+}
+
+// 读取词表文件，每行一个词，忽略空行和 # 开头的行（用户词典和敏感词表共用）
+pub(crate) fn read_word_list(file: &str) -> Result<Vec<String>, String> {
+    let content = std::fs::read_to_string(file).map_err(|err| err.to_string())?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+impl Dictionary {
+    /// 是否没有用户词典
+    pub fn is_empty(&self) -> bool {
+        self.source == DictionarySource::default()
+    }
+
+    /// 分词器
+    pub fn segmenter(&self) -> &Arc<Segmenter> {
+        &self.segmenter
+    }
+
+    /// 检查用户词语（检测用，会报告所有错误）
+    /// 英文、数字、标点和空白不参与词典切分，词语中不能包含这些字符
+    pub fn check(&self) -> Result<(), String> {
+        let errors: Vec<String> = self
+            .words
+            .iter()
+            .filter(|word| {
+                word.chars().count() < 2
+                    || word
+                        .chars()
+                        .any(|c| c.is_ascii_alphanumeric() || !c.is_alphanumeric())
+            })
+            .map(|word| format!("Invalid word {:?}", word))
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试内置词典
+    #[test]
+    fn test_words() {
+        let words: Vec<&str> = WORDS.split(' ').collect();
+        let unique: HashSet<&str> = words.iter().copied().collect();
+        assert_eq!(words.len(), unique.len());
+        assert!(words.iter().all(|word| word.chars().count() > 1));
+    }
+
+    // 测试分词
+    #[test]
+    fn test_segment() {
+        let segmenter = Segmenter::builtin();
+        assert_eq!(
+            segmenter.segment("我想查询订单状态"),
+            vec!["我", "想", "查询", "订单", "状态"]
+        );
+        assert_eq!(
+            segmenter.segment("我的VIP账号，密码忘记了！"),
+            vec!["我", "的", "vip", "账号", "密码", "忘记", "了"]
+        );
+        assert_eq!(segmenter.segment("退钱包"), vec!["退", "钱包"]);
+        assert_eq!(segmenter.segment("简单"), vec!["简", "单"]);
+        assert!(segmenter.segment("？！ ").is_empty());
+
+        let dictionary: Dictionary = serde_yaml::from_str("words: [简单, 退钱包]").unwrap();
+        assert!(dictionary.check().is_ok());
+        assert_eq!(dictionary.segmenter().segment("简单"), vec!["简单"]);
+        assert_eq!(dictionary.segmenter().segment("退钱包"), vec!["退钱包"]);
+        let yaml = serde_yaml::to_string(&dictionary).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<Dictionary>(&yaml).unwrap(),
+            dictionary
+        );
+
+        let dictionary: Dictionary = serde_yaml::from_str("words: [单, VIP会员]").unwrap();
+        assert_eq!(
+            dictionary.check(),
+            Err("Invalid word \"单\", Invalid word \"VIP会员\"".to_string())
+        );
+        assert!(serde_yaml::from_str::<Dictionary>("files: [not_found.txt]").is_err());
+    }
+}
@@ -0,0 +1,178 @@
+/// Sakiko 的敏感词过滤
+/// 用 Aho-Corasick 自动机在用户输入中检测敏感词，并在机器人输出中将敏感词遮盖为 “*”
+/// 检测时忽略英文大小写，并同时检测去除空白和标点后的输入，如 “傻 * 瓜” 视同 “傻瓜”
+use ::aho_corasick::{AhoCorasick, MatchKind};
+use ::serde::{Deserialize, Serialize};
+use ::std::sync::Arc;
+
+use crate::segment::read_word_list;
+
+/// 敏感词来源和处理方式
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Default)]
+pub struct SensitiveSource {
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<String>, // 敏感词（可置空）
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>, // 词表文件路径，每行一个词，忽略空行和 # 开头的行（可置空）
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    pub mask: Option<char>, // 遮盖字符，默认为 *（可置空）
+    #[serde(default = "String::new", skip_serializing_if = "String::is_empty")]
+    pub step: String, // 输入包含敏感词时跳转的步骤，不声明时只标记（可置空）
+}
+
+/// 敏感词过滤器（序列化为来源），加载时读取词表文件并建立自动机
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(try_from = "SensitiveSource", into = "SensitiveSource")]
+pub struct SensitiveFilter {
+    source: SensitiveSource,
+    words: Vec<String>, // 敏感词和词表文件中的词
+    matcher: Option<Arc<AhoCorasick>>,
+}
+
+// 自动机是从词表构建的，比较来源即可
+impl PartialEq for SensitiveFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl TryFrom<SensitiveSource> for SensitiveFilter {
+    type Error = String;
+
+    fn try_from(source: SensitiveSource) -> Result<Self, Self::Error> {
+        let mut words = source.words.clone();
+        for file in &source.files {
+            words.extend(
+                read_word_list(file)
+                    .map_err(|err| format!("Failed to read sensitive words {}: {}", file, err))?,
+            );
+        }
+        let patterns: Vec<&String> = words.iter().filter(|word| !word.is_empty()).collect();
+        let matcher = if patterns.is_empty() {
+            None
+        } else {
+            let matcher = AhoCorasick::builder()
+                .ascii_case_insensitive(true)
+                .match_kind(MatchKind::LeftmostLongest)
+                .build(patterns)
+                .map_err(|err| format!("Failed to build sensitive words: {}", err))?;
+            Some(Arc::new(matcher))
+        };
+        Ok(SensitiveFilter {
+            source,
+            words,
+            matcher,
+        })
+    }
+}
+
+impl From<SensitiveFilter> for SensitiveSource {
+    fn from(filter: SensitiveFilter) -> Self {
+        filter.source
+    }
+}
+
+impl SensitiveFilter {
+    /// 是否没有声明敏感词过滤
+    pub fn is_empty(&self) -> bool {
+        self.source == SensitiveSource::default()
+    }
+
+    /// 输入包含敏感词时跳转的步骤，为空时不跳转
+    pub fn step(&self) -> &str {
+        &self.source.step
+    }
+
+    /// 文本是否包含敏感词，同时检测去除空白和标点后的文本
+    pub fn contains(&self, text: &str) -> bool {
+        let Some(matcher) = &self.matcher else {
+            return false;
+        };
+        let stripped: String = text.chars().filter(|c| c.is_alphanumeric()).collect();
+        matcher.is_match(text) || matcher.is_match(&stripped)
+    }
+
+    /// 将文本中的敏感词逐字替换为遮盖字符，与检测相同，也遮盖被空白和标点隔开的敏感词（隔开的字符保留）
+    pub fn mask(&self, text: &str) -> String {
+        let Some(matcher) = &self.matcher else {
+            return text.to_string();
+        };
+        let mask = self.source.mask.unwrap_or('*');
+        // 去除空白和标点后的文本，记录每个字在其中和原文中的位置
+        let mut stripped = String::new();
+        let mut positions = Vec::new();
+        for (position, c) in text.char_indices().filter(|(_, c)| c.is_alphanumeric()) {
+            positions.push((stripped.len(), position));
+            stripped.push(c);
+        }
+        let mut masked = vec![false; text.len()];
+        for found in matcher.find_iter(text) {
+            masked[found.range()].fill(true);
+        }
+        for found in matcher.find_iter(&stripped) {
+            let start = positions.partition_point(|(offset, _)| *offset < found.start());
+            let end = positions.partition_point(|(offset, _)| *offset < found.end());
+            for &(_, position) in &positions[start..end] {
+                masked[position] = true;
+            }
+        }
+        text.char_indices()
+            .map(|(position, c)| if masked[position] { mask } else { c })
+            .collect()
+    }
+
+    /// 检查敏感词（检测用，会报告所有错误）
+    pub fn check(&self) -> Result<(), String> {
+        let mut errors: Vec<String> = self
+            .words
+            .iter()
+            .filter(|word| word.trim().is_empty() || word.trim() != word.as_str())
+            .map(|word| format!("Invalid word {:?}", word))
+            .collect();
+        if self.source.mask.is_some_and(char::is_whitespace) {
+            errors.push("Invalid mask".to_string());
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试敏感词检测和遮盖
+    #[test]
+    fn test_sensitive() {
+        let filter: SensitiveFilter =
+            serde_yaml::from_str("words: [笨蛋, 大笨蛋, idiot]\nmask: \"#\"").unwrap();
+        assert!(filter.check().is_ok());
+        assert!(filter.contains("你这个笨蛋"));
+        assert!(filter.contains("you IDIOT!"));
+        assert!(filter.contains("笨 * 蛋"));
+        assert!(!filter.contains("你好"));
+        assert_eq!(filter.mask("你这个大笨蛋，Idiot"), "你这个###，#####");
+        assert_eq!(filter.mask("你好"), "你好");
+        assert_eq!(filter.mask("笨 * 蛋，i-d-i-o-t"), "# * #，#-#-#-#-#");
+        let yaml = serde_yaml::to_string(&filter).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<SensitiveFilter>(&yaml).unwrap(),
+            filter
+        );
+
+        let filter = SensitiveFilter::default();
+        assert!(filter.is_empty());
+        assert!(!filter.contains("笨蛋"));
+        assert_eq!(filter.mask("笨蛋"), "笨蛋");
+
+        let filter: SensitiveFilter = serde_yaml::from_str("words: [\"\", \" 笨蛋\"]").unwrap();
+        assert_eq!(
+            filter.check(),
+            Err("Invalid word \"\", Invalid word \" 笨蛋\"".to_string())
+        );
+        assert!(serde_yaml::from_str::<SensitiveFilter>("files: [not_found.txt]").is_err());
+    }
+}
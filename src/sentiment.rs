@@ -0,0 +1,321 @@
+/// Sakiko 的情感分析
+/// 基于情感词典为用户输入打分，内置中文和英文的正面词与负面词，可在配置中补充，不依赖网络。
+/// 词语前的否定词（如 “不”、“没”、“not”）使情感反转并减弱，程度副词（如 “很”、“太”、“very”）使情感加强，
+/// 感叹号加强负面情感。每次输入的得分在 -1 到 1 之间，会话的得分随每次输入衰减并累加。
+use ::aho_corasick::{AhoCorasick, MatchKind};
+use ::serde::{Deserialize, Serialize};
+use ::std::sync::{Arc, OnceLock};
+use std::ops::Not;
+
+// 内置正面词，各词以空格分隔
+const POSITIVE: &str = "\
+    谢谢 感谢 多谢 谢啦 感激 好的 很好 挺好 真好 不错 满意 喜欢 开心 高兴 \
+    棒 很棒 太棒 真棒 优秀 专业 耐心 热情 周到 及时 快速 方便 好用 好评 \
+    赞 点赞 给力 靠谱 完美 贴心 辛苦了 解决了 搞定了 明白了 放心 满分 惊喜 \
+    thanks thank great good nice excellent awesome amazing love perfect helpful \
+    happy glad satisfied wonderful fantastic appreciate appreciated quick resolved \
+    fixed";
+
+// 内置负面词，各词以空格分隔
+const NEGATIVE: &str = "\
+    生气 愤怒 气死 气人 恼火 火大 烦 烦人 烦死 讨厌 垃圾 差劲 太差 很差 \
+    糟糕 失望 不满 不满意 不行 不好 不爽 投诉 退钱 骗子 骗人 欺骗 坑人 \
+    忽悠 敷衍 无语 离谱 恶心 过分 无耻 混蛋 受够 崩溃 郁闷 急死 太慢 \
+    没人管 没人理 不理人 拖延 推脱 扯皮 搞什么 什么鬼 难用 卡死 差评 后悔 \
+    上当 不靠谱 不负责 不专业 乱扣 乱收费 丢件 到底 凭什么 怎么还 搞错 \
+    弄错 头疼 伤心 难过 可恶 该死 见鬼 \
+    angry mad furious annoyed annoying frustrated frustrating terrible awful \
+    horrible worst bad poor useless ridiculous unacceptable disappointed \
+    disappointing hate sucks scam fraud liar rude slow broken waste nonsense \
+    stupid complaint unhappy upset disgusting pathetic incompetent";
+
+// 否定词
+const NEGATORS: [&str; 5] = ["不", "没", "别", "未", "甭"];
+const ENGLISH_NEGATORS: [&str; 12] = [
+    "not", "no", "never", "dont", "don't", "doesn't", "didn't", "isn't", "wasn't", "can't",
+    "won't", "cannot",
+];
+
+// 程度副词
+const INTENSIFIERS: [&str; 10] = [
+    "非常", "特别", "十分", "超级", "很", "太", "真", "挺", "超", "极",
+];
+const ENGLISH_INTENSIFIERS: [&str; 7] = [
+    "very",
+    "so",
+    "really",
+    "extremely",
+    "too",
+    "super",
+    "totally",
+];
+
+const NEGATION: f64 = -0.75; // 否定后情感的倍数
+const INTENSITY: f64 = 1.5; // 程度副词加强的倍数
+const EXCLAMATION: f64 = 0.2; // 每个感叹号加强负面情感的比例，至多计算三个
+
+/// 情感设置
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Default)]
+pub struct SentimentSource {
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub positive: Vec<String>, // 补充的正面词（可置空）
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub negative: Vec<String>, // 补充的负面词（可置空）
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    pub decay: Option<f64>, // 会话得分的衰减系数，范围为 0 到 1，默认为 0.5（可置空）
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f64>, // 会话得分不高于该值时跳转（可置空）
+    #[serde(default = "String::new", skip_serializing_if = "String::is_empty")]
+    pub step: String, // 跳转的步骤，与 threshold 同时声明（可置空）
+}
+
+// 情感词典，词语和对应的情感（1 为正面，-1 为负面）
+#[derive(Debug)]
+struct Lexicon {
+    weights: Vec<f64>,
+    matcher: AhoCorasick,
+}
+
+/// 情感分析器（序列化为情感设置），加载时建立情感词典
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(from = "SentimentSource", into = "SentimentSource")]
+pub struct Sentiment {
+    source: SentimentSource,
+    lexicon: Arc<Lexicon>,
+}
+
+// 词典由设置决定，只比较设置
+impl PartialEq for Sentiment {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Lexicon {
+    // 由正面词和负面词建立词典，同一个词以后出现的为准
+    fn new<'a>(
+        positive: impl IntoIterator<Item = &'a str>,
+        negative: impl IntoIterator<Item = &'a str>,
+    ) -> Lexicon {
+        let mut words: Vec<(String, f64)> = Vec::new();
+        let entries = positive
+            .into_iter()
+            .map(|word| (word, 1.0))
+            .chain(negative.into_iter().map(|word| (word, -1.0)));
+        for (word, weight) in entries {
+            let word = word.trim().to_lowercase();
+            if word.is_empty() {
+                continue;
+            }
+            match words.iter_mut().find(|(other, _)| *other == word) {
+                Some(entry) => entry.1 = weight,
+                None => words.push((word, weight)),
+            }
+        }
+        let matcher = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(words.iter().map(|(word, _)| word))
+            .unwrap();
+        Lexicon {
+            weights: words.iter().map(|(_, weight)| *weight).collect(),
+            matcher,
+        }
+    }
+
+    // 内置词典，只建立一次，由各处共享
+    fn builtin() -> Arc<Lexicon> {
+        static BUILTIN: OnceLock<Arc<Lexicon>> = OnceLock::new();
+        Arc::clone(BUILTIN.get_or_init(|| {
+            Arc::new(Lexicon::new(
+                POSITIVE.split_whitespace(),
+                NEGATIVE.split_whitespace(),
+            ))
+        }))
+    }
+}
+
+impl From<SentimentSource> for Sentiment {
+    fn from(source: SentimentSource) -> Self {
+        let lexicon = if source.positive.is_empty() && source.negative.is_empty() {
+            Lexicon::builtin()
+        } else {
+            Arc::new(Lexicon::new(
+                POSITIVE
+                    .split_whitespace()
+                    .chain(source.positive.iter().map(String::as_str)),
+                NEGATIVE
+                    .split_whitespace()
+                    .chain(source.negative.iter().map(String::as_str)),
+            ))
+        };
+        Sentiment { source, lexicon }
+    }
+}
+
+impl From<Sentiment> for SentimentSource {
+    fn from(sentiment: Sentiment) -> Self {
+        sentiment.source
+    }
+}
+
+impl Default for Sentiment {
+    fn default() -> Self {
+        Sentiment::from(SentimentSource::default())
+    }
+}
+
+// 词语前同一分句中至多三个汉字和三个英文单词，用于判断否定和程度
+fn modifiers(prefix: &str) -> (String, Vec<&str>) {
+    let chinese: String = prefix
+        .chars()
+        .rev()
+        .take_while(|c| c.is_alphanumeric() && !c.is_ascii())
+        .take(3)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    let clause = prefix
+        .rsplit(|c: char| !(c.is_ascii_alphanumeric() || c == '\'' || c == ' '))
+        .next()
+        .unwrap_or("");
+    let english = clause.split_whitespace().rev().take(3).collect();
+    (chinese, english)
+}
+
+impl Sentiment {
+    /// 为输入打分，范围为 -1 到 1，没有情感词时为 0
+    pub fn score(&self, input: &str) -> f64 {
+        let text = input.to_lowercase();
+        let mut total = 0.0;
+        for found in self.lexicon.matcher.find_iter(&text) {
+            let (start, end) = (found.start(), found.end());
+            let word = &text[start..end];
+            // 英文词必须是完整的单词
+            let before = text[..start].chars().next_back();
+            let after = text[end..].chars().next();
+            if word.is_ascii()
+                && (before.is_some_and(|c| c.is_ascii_alphanumeric())
+                    || after.is_some_and(|c| c.is_ascii_alphanumeric()))
+            {
+                continue;
+            }
+            let mut weight = self.lexicon.weights[found.pattern().as_usize()];
+            let (chinese, english) = modifiers(&text[..start]);
+            if INTENSIFIERS.iter().any(|w| chinese.contains(w))
+                || english.iter().any(|w| ENGLISH_INTENSIFIERS.contains(w))
+            {
+                weight *= INTENSITY;
+            }
+            if NEGATORS.iter().any(|w| chinese.contains(w))
+                || english.iter().any(|w| ENGLISH_NEGATORS.contains(w))
+            {
+                weight *= NEGATION;
+            }
+            total += weight;
+        }
+        if total < 0.0 {
+            let exclamations = text.chars().filter(|c| *c == '!' || *c == '！').count();
+            total *= 1.0 + EXCLAMATION * exclamations.min(3) as f64;
+        }
+        // 归一化到 -1 到 1
+        total / (total * total + 4.0).sqrt()
+    }
+
+    /// 按衰减系数更新会话得分
+    pub fn update(&self, session: f64, input: &str) -> f64 {
+        session * self.source.decay.unwrap_or(0.5) + self.score(input)
+    }
+
+    /// 会话得分达到阈值时跳转的步骤
+    pub fn escalate(&self, session: f64) -> Option<&str> {
+        let threshold = self.source.threshold?;
+        (session <= threshold && self.source.step.is_empty().not()).then_some(&self.source.step)
+    }
+
+    /// 跳转的步骤，为空时不跳转
+    pub fn step(&self) -> &str {
+        &self.source.step
+    }
+
+    /// 检查情感设置（检测用，会报告所有错误）
+    pub fn check(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        for word in self.source.positive.iter().chain(&self.source.negative) {
+            if word.trim().is_empty() {
+                errors.push(format!("Invalid word {:?}", word));
+            }
+        }
+        if let Some(decay) = self.source.decay {
+            if (0.0..=1.0).contains(&decay).not() {
+                errors.push(format!("Invalid decay {}", decay));
+            }
+        }
+        if self.source.threshold.is_some() != self.source.step.is_empty().not() {
+            errors.push("Threshold and step must be declared together".to_string());
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试内置词典
+    #[test]
+    fn test_lexicon() {
+        let words: Vec<&str> = POSITIVE
+            .split_whitespace()
+            .chain(NEGATIVE.split_whitespace())
+            .collect();
+        let unique: std::collections::HashSet<&str> = words.iter().copied().collect();
+        assert_eq!(words.len(), unique.len());
+    }
+
+    // 测试情感打分
+    #[test]
+    fn test_score() {
+        let sentiment = Sentiment::default();
+        assert_eq!(sentiment.score("我想查一下订单"), 0.0);
+        assert!(sentiment.score("谢谢，很满意") > 0.5);
+        assert!(sentiment.score("太慢了，我要投诉") < -0.5);
+        // 否定和程度
+        assert!(sentiment.score("不满意") < 0.0);
+        assert!(sentiment.score("一点都不好用") < 0.0);
+        assert!(sentiment.score("not bad") > 0.0);
+        assert!(sentiment.score("This is terrible") < 0.0);
+        assert!(sentiment.score("非常失望") < sentiment.score("失望"));
+        assert!(sentiment.score("失望！！") < sentiment.score("失望"));
+        // 英文词必须是完整的单词
+        assert_eq!(sentiment.score("badge"), 0.0);
+
+        let decayed = sentiment.update(-1.0, "好的");
+        assert!(decayed > -0.5 && decayed < 0.0);
+        assert_eq!(sentiment.escalate(-5.0), None);
+    }
+
+    // 测试补充词语和检查
+    #[test]
+    fn test_source() {
+        let yaml = "negative: [坑爹, 好评]\nthreshold: -1\nstep: human";
+        let sentiment: Sentiment = serde_yaml::from_str(yaml).unwrap();
+        assert!(sentiment.check().is_ok());
+        assert!(sentiment.score("真坑爹") < 0.0);
+        assert!(sentiment.score("好评") < 0.0);
+        assert_eq!(sentiment.escalate(-1.0), Some("human"));
+        assert_eq!(sentiment.escalate(-0.5), None);
+        let yaml = serde_yaml::to_string(&sentiment).unwrap();
+        assert_eq!(serde_yaml::from_str::<Sentiment>(&yaml).unwrap(), sentiment);
+
+        let sentiment: Sentiment = serde_yaml::from_str("decay: 2\nstep: human").unwrap();
+        assert_eq!(
+            sentiment.check(),
+            Err("Invalid decay 2, Threshold and step must be declared together".to_string())
+        );
+    }
+}
@@ -0,0 +1,942 @@
+/// Sakiko 的输出模板
+/// 模板在加载时编译为语法树，支持占位符、格式说明、过滤器、条件和循环。
+/// 占位符形如 {变量名|过滤器:格式}，标签形如 {% if 条件 %}、{% elif 条件 %}、{% else %}、{% for 变量 in 数组 %} 和 {% end %}。
+use std::cmp::Ordering;
+
+use crate::variable::{VariableType, Variables};
+
+/// 对齐方式
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Align {
+    Left,   // <
+    Right,  // >
+    Center, // ^
+}
+
+/// 格式说明，形如 [[填充]对齐][0][宽度][,][.精度]
+#[derive(Debug, PartialEq, Clone)]
+struct Spec {
+    fill: char,
+    align: Option<Align>,
+    zero: bool,               // 数字用 0 补齐到宽度
+    width: usize,             // 最小宽度（按字符计）
+    thousands: bool,          // 数字使用千位分隔符
+    precision: Option<usize>, // 浮点数的小数位数或字符串的最大长度
+}
+
+impl Default for Spec {
+    fn default() -> Self {
+        Self {
+            fill: ' ',
+            align: None,
+            zero: false,
+            width: 0,
+            thousands: false,
+            precision: None,
+        }
+    }
+}
+
+impl Spec {
+    /// 解析格式说明
+    fn parse(s: &str) -> Result<Self, String> {
+        let err = || format!("Invalid format spec: {}", s);
+        let chars = s.chars().collect::<Vec<char>>();
+        let align_of = |c: char| match c {
+            '<' => Some(Align::Left),
+            '>' => Some(Align::Right),
+            '^' => Some(Align::Center),
+            _ => None,
+        };
+        let mut spec = Spec::default();
+        let mut i = 0;
+        if let Some(align) = chars.get(1).and_then(|c| align_of(*c)) {
+            spec.fill = chars[0];
+            spec.align = Some(align);
+            i = 2;
+        } else if let Some(align) = chars.first().and_then(|c| align_of(*c)) {
+            spec.align = Some(align);
+            i = 1;
+        }
+        if chars.get(i) == Some(&'0') {
+            spec.zero = true;
+            i += 1;
+        }
+        let start = i;
+        while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+            i += 1;
+        }
+        if i > start {
+            spec.width = chars[start..i]
+                .iter()
+                .collect::<String>()
+                .parse()
+                .map_err(|_| err())?;
+        }
+        if chars.get(i) == Some(&',') {
+            spec.thousands = true;
+            i += 1;
+        }
+        if chars.get(i) == Some(&'.') {
+            let start = i + 1;
+            i = start;
+            while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+                i += 1;
+            }
+            if i == start {
+                return Err(err());
+            }
+            spec.precision = Some(
+                chars[start..i]
+                    .iter()
+                    .collect::<String>()
+                    .parse()
+                    .map_err(|_| err())?,
+            );
+        }
+        if i != chars.len() {
+            return Err(err());
+        }
+        Ok(spec)
+    }
+
+    /// 按格式输出变量
+    fn apply(&self, value: &VariableType) -> String {
+        // 数字拆分为符号和数字部分，便于插入千位分隔符和补 0
+        let (sign, body, numeric) = match value {
+            VariableType::Int(i) => {
+                let digits = i.unsigned_abs().to_string();
+                let sign = if *i < 0 { "-" } else { "" };
+                (sign, self.group(&digits), true)
+            }
+            VariableType::Float(f) => {
+                let s = format!("{:.*}", self.precision.unwrap_or(3), f);
+                let (sign, s) = match s.strip_prefix('-') {
+                    Some(s) => ("-", s.to_string()),
+                    None => ("", s),
+                };
+                let body = match s.split_once('.') {
+                    Some((int, frac)) => format!("{}.{}", self.group(int), frac),
+                    None => self.group(&s),
+                };
+                (sign, body, true)
+            }
+            VariableType::Str(s) => match self.precision {
+                Some(p) => ("", s.chars().take(p).collect(), false),
+                None => ("", s.clone(), false),
+            },
+            _ => ("", value.to_string(), false),
+        };
+
+        let len = sign.chars().count() + body.chars().count();
+        if len >= self.width {
+            return format!("{}{}", sign, body);
+        }
+        let pad = self.width - len;
+        if numeric && self.zero && self.align.is_none() {
+            return format!("{}{}{}", sign, "0".repeat(pad), body);
+        }
+        let fill = |n: usize| self.fill.to_string().repeat(n);
+        let align = self
+            .align
+            .unwrap_or(if numeric { Align::Right } else { Align::Left });
+        match align {
+            Align::Left => format!("{}{}{}", sign, body, fill(pad)),
+            Align::Right => format!("{}{}{}", fill(pad), sign, body),
+            Align::Center => format!("{}{}{}{}", fill(pad / 2), sign, body, fill(pad - pad / 2)),
+        }
+    }
+
+    // 按需为整数部分加入千位分隔符
+    fn group(&self, digits: &str) -> String {
+        if !self.thousands {
+            return digits.to_string();
+        }
+        let mut result = String::new();
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                result.push(',');
+            }
+            result.push(c);
+        }
+        result
+    }
+}
+
+/// 过滤器
+#[derive(Debug, PartialEq, Clone)]
+enum Filter {
+    Upper,        // 转大写
+    Lower,        // 转小写
+    Trim,         // 去除首尾空白
+    Len,          // 字符串、数组或字典的长度
+    Join(String), // 以分隔符连接数组
+}
+
+impl Filter {
+    /// 解析过滤器，join 的参数为带引号的字符串
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "upper" => Ok(Filter::Upper),
+            "lower" => Ok(Filter::Lower),
+            "trim" => Ok(Filter::Trim),
+            "len" => Ok(Filter::Len),
+            "join" => Ok(Filter::Join(", ".to_string())),
+            _ => s
+                .strip_prefix("join(")
+                .and_then(|s| s.strip_suffix(')'))
+                .and_then(|s| s.trim().strip_prefix('"'))
+                .and_then(|s| s.strip_suffix('"'))
+                .map(|sep| Filter::Join(sep.to_string()))
+                .ok_or_else(|| format!("Unknown filter: {}", s)),
+        }
+    }
+
+    /// 应用过滤器
+    fn apply(&self, value: VariableType) -> Result<VariableType, &'static str> {
+        match (self, value) {
+            (Filter::Upper, VariableType::Str(s)) => Ok(VariableType::Str(s.to_uppercase())),
+            (Filter::Lower, VariableType::Str(s)) => Ok(VariableType::Str(s.to_lowercase())),
+            (Filter::Trim, VariableType::Str(s)) => Ok(VariableType::Str(s.trim().to_string())),
+            (Filter::Len, VariableType::Str(s)) => Ok(VariableType::Int(s.chars().count() as i32)),
+            (Filter::Len, VariableType::StrVec(v)) => Ok(VariableType::Int(v.len() as i32)),
+            (Filter::Len, VariableType::IntVec(v)) => Ok(VariableType::Int(v.len() as i32)),
+            (Filter::Len, VariableType::FloatVec(v)) => Ok(VariableType::Int(v.len() as i32)),
+            (Filter::Len, VariableType::StrDic(d)) => Ok(VariableType::Int(d.len() as i32)),
+            (Filter::Len, VariableType::IntDic(d)) => Ok(VariableType::Int(d.len() as i32)),
+            (Filter::Len, VariableType::FloatDic(d)) => Ok(VariableType::Int(d.len() as i32)),
+            (Filter::Join(sep), VariableType::StrVec(v)) => Ok(VariableType::Str(v.join(sep))),
+            (Filter::Join(sep), VariableType::IntVec(v)) => Ok(VariableType::Str(
+                v.iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<String>>()
+                    .join(sep),
+            )),
+            (Filter::Join(sep), VariableType::FloatVec(v)) => Ok(VariableType::Str(
+                v.iter()
+                    .map(|f| VariableType::Float(*f).to_string())
+                    .collect::<Vec<String>>()
+                    .join(sep),
+            )),
+            _ => Err("Filter not applicable"),
+        }
+    }
+}
+
+/// 值的来源
+#[derive(Debug, PartialEq, Clone)]
+enum Source {
+    Positional(usize), // 变量数组中的序号
+    Named(String),     // 变量名或循环变量名
+}
+
+/// 表达式，取值后依次经过过滤器
+#[derive(Debug, PartialEq, Clone)]
+struct Expr {
+    source: Source,
+    filters: Vec<Filter>,
+}
+
+/// 条件中的操作数
+#[derive(Debug, PartialEq, Clone)]
+enum Operand {
+    Expr(Expr),
+    Literal(VariableType),
+}
+
+/// 比较符
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Cmp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// 条件原子，形如 [not] 操作数 [比较符 操作数]
+#[derive(Debug, PartialEq, Clone)]
+struct Atom {
+    negate: bool,
+    left: Operand,
+    right: Option<(Cmp, Operand)>,
+}
+
+/// 条件，为若干 and 组用 or 连接
+#[derive(Debug, PartialEq, Clone)]
+struct Condition(Vec<Vec<Atom>>);
+
+/// 语法树节点
+#[derive(Debug, PartialEq, Clone)]
+enum Node {
+    Text(String),
+    Value(Expr, Spec),
+    If(Vec<(Condition, Vec<Node>)>, Vec<Node>), // 各分支及 else 分支
+    For(Option<String>, String, String, Vec<Node>), // 序号或键、元素、容器、循环体
+}
+
+/// 词法单元
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Text(String),
+    Placeholder(String), // {...} 的内容
+    Tag(String),         // {% ... %} 的内容
+}
+
+/// 编译后的模板
+#[derive(Debug, PartialEq, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+    positional: usize, // 位置占位符的个数
+}
+
+// 按分隔符切分，忽略引号内的分隔符
+fn split_unquoted(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c == sep && !quoted => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+// 词法分析，标签后紧跟的换行会被去除
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let tag = chars.peek() == Some(&'%');
+                if tag {
+                    chars.next();
+                }
+                let mut inner = String::new();
+                let mut quoted = false;
+                loop {
+                    match chars.next() {
+                        Some('"') => {
+                            quoted = !quoted;
+                            inner.push('"');
+                        }
+                        Some('%') if tag && !quoted && chars.peek() == Some(&'}') => {
+                            chars.next();
+                            break;
+                        }
+                        Some('}') if !tag && !quoted => break,
+                        Some('{') if !quoted => return Err("Unmatched '{'".to_string()),
+                        None => return Err("Unmatched '{'".to_string()),
+                        Some(c) => inner.push(c),
+                    }
+                }
+                if !text.is_empty() {
+                    tokens.push(Token::Text(std::mem::take(&mut text)));
+                }
+                if tag {
+                    tokens.push(Token::Tag(inner.trim().to_string()));
+                    if chars.peek() == Some(&'\n') {
+                        chars.next();
+                    }
+                } else {
+                    tokens.push(Token::Placeholder(inner));
+                }
+            }
+            '}' => return Err("Unmatched '}'".to_string()),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+    Ok(tokens)
+}
+
+/// 语法分析器
+struct Parser {
+    tokens: std::vec::IntoIter<Token>,
+    positional: usize,
+}
+
+impl Parser {
+    // 解析一段节点，直到 elif、else、end 标签或结尾，返回节点和结束的标签
+    fn parse_block(&mut self) -> Result<(Vec<Node>, Option<String>), String> {
+        let mut nodes = Vec::new();
+        while let Some(token) = self.tokens.next() {
+            match token {
+                Token::Text(text) => nodes.push(Node::Text(text)),
+                Token::Placeholder(inner) => nodes.push(self.parse_placeholder(&inner)?),
+                Token::Tag(tag) => {
+                    let (keyword, rest) = tag.split_once(' ').unwrap_or((&tag, ""));
+                    match keyword {
+                        "if" => nodes.push(self.parse_if(rest)?),
+                        "for" => nodes.push(self.parse_for(rest)?),
+                        "else" | "end" if !rest.is_empty() => {
+                            return Err(format!("Invalid tag: {}", tag))
+                        }
+                        "elif" | "else" | "end" => return Ok((nodes, Some(tag))),
+                        _ => return Err(format!("Unknown tag: {}", tag)),
+                    }
+                }
+            }
+        }
+        Ok((nodes, None))
+    }
+
+    // 解析占位符
+    fn parse_placeholder(&mut self, inner: &str) -> Result<Node, String> {
+        let (expr, spec) = match split_unquoted(inner, ':').as_slice() {
+            [expr] => (*expr, Spec::default()),
+            [expr, spec] => (*expr, Spec::parse(spec)?),
+            _ => return Err(format!("Invalid placeholder: {}", inner)),
+        };
+        let mut parts = split_unquoted(expr, '|').into_iter();
+        let name = parts.next().unwrap_or_default().trim();
+        let source = if name.is_empty() {
+            self.positional += 1;
+            Source::Positional(self.positional - 1)
+        } else {
+            Source::Named(name.to_string())
+        };
+        let filters = parts
+            .map(|f| Filter::parse(f.trim()))
+            .collect::<Result<Vec<Filter>, String>>()?;
+        Ok(Node::Value(Expr { source, filters }, spec))
+    }
+
+    // 解析条件分支，直到 end 标签
+    fn parse_if(&mut self, condition: &str) -> Result<Node, String> {
+        let mut branches = Vec::new();
+        let mut condition = parse_condition(condition)?;
+        loop {
+            let (body, end) = self.parse_block()?;
+            branches.push((condition, body));
+            let end = end.ok_or("Unclosed if")?;
+            let (keyword, rest) = end.split_once(' ').unwrap_or((&end, ""));
+            match keyword {
+                "elif" => condition = parse_condition(rest)?,
+                "else" => {
+                    let (otherwise, end) = self.parse_block()?;
+                    return match end.as_deref() {
+                        Some("end") => Ok(Node::If(branches, otherwise)),
+                        Some(tag) => Err(format!("Unexpected tag: {}", tag)),
+                        None => Err("Unclosed if".to_string()),
+                    };
+                }
+                _ => return Ok(Node::If(branches, Vec::new())),
+            }
+        }
+    }
+
+    // 解析循环，形如 元素 in 容器 或 序号, 元素 in 容器
+    fn parse_for(&mut self, header: &str) -> Result<Node, String> {
+        let err = || format!("Invalid loop: {}", header);
+        let (names, container) = header.split_once(" in ").ok_or_else(err)?;
+        let container = container.trim();
+        let names = names.split(',').map(str::trim).collect::<Vec<&str>>();
+        if container.is_empty() || container.contains(' ') || names.iter().any(|n| n.is_empty()) {
+            return Err(err());
+        }
+        let (key, value) = match names.as_slice() {
+            [value] => (None, value.to_string()),
+            [key, value] => (Some(key.to_string()), value.to_string()),
+            _ => return Err(err()),
+        };
+        let (body, end) = self.parse_block()?;
+        match end.as_deref() {
+            Some("end") => Ok(Node::For(key, value, container.to_string(), body)),
+            Some(tag) => Err(format!("Unexpected tag: {}", tag)),
+            None => Err("Unclosed for".to_string()),
+        }
+    }
+}
+
+// 解析条件，or 的优先级低于 and
+fn parse_condition(s: &str) -> Result<Condition, String> {
+    let err = || format!("Invalid condition: {}", s);
+    let words = split_unquoted(s, ' ')
+        .into_iter()
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<&str>>();
+    let mut groups = Vec::new();
+    for group in words.split(|w| *w == "or") {
+        let mut atoms = Vec::new();
+        for atom in group.split(|w| *w == "and") {
+            let (negate, atom) = match atom {
+                ["not", rest @ ..] => (true, rest),
+                _ => (false, atom),
+            };
+            let atom = match atom {
+                [left] => Atom {
+                    negate,
+                    left: parse_operand(left).ok_or_else(err)?,
+                    right: None,
+                },
+                [left, cmp, right] => {
+                    let cmp = match *cmp {
+                        "==" => Cmp::Eq,
+                        "!=" => Cmp::Ne,
+                        ">" => Cmp::Gt,
+                        ">=" => Cmp::Ge,
+                        "<" => Cmp::Lt,
+                        "<=" => Cmp::Le,
+                        _ => return Err(err()),
+                    };
+                    Atom {
+                        negate,
+                        left: parse_operand(left).ok_or_else(err)?,
+                        right: Some((cmp, parse_operand(right).ok_or_else(err)?)),
+                    }
+                }
+                _ => return Err(err()),
+            };
+            atoms.push(atom);
+        }
+        groups.push(atoms);
+    }
+    Ok(Condition(groups))
+}
+
+// 解析操作数，可以是带引号的字符串、数字或带过滤器的变量
+fn parse_operand(s: &str) -> Option<Operand> {
+    if let Some(s) = s.strip_prefix('"') {
+        return s
+            .strip_suffix('"')
+            .map(|s| Operand::Literal(VariableType::Str(s.to_string())));
+    }
+    if s.trim_start_matches('-')
+        .starts_with(|c: char| c.is_ascii_digit())
+    {
+        return match s.parse::<i32>() {
+            Ok(i) => Some(Operand::Literal(VariableType::Int(i))),
+            Err(_) => s
+                .parse::<f64>()
+                .ok()
+                .map(|f| Operand::Literal(VariableType::Float(f))),
+        };
+    }
+    let mut parts = split_unquoted(s, '|').into_iter();
+    let name = parts.next()?;
+    if name.is_empty() {
+        return None;
+    }
+    let filters = parts
+        .map(|f| Filter::parse(f).ok())
+        .collect::<Option<Vec<Filter>>>()?;
+    Some(Operand::Expr(Expr {
+        source: Source::Named(name.to_string()),
+        filters,
+    }))
+}
+
+// 判断值是否为真：非零数字、非空字符串和集合为真
+fn truthy(value: &VariableType) -> bool {
+    match value {
+        VariableType::Int(i) => *i != 0,
+        VariableType::Float(f) => *f != 0.0,
+        VariableType::Str(s) => !s.is_empty(),
+        VariableType::StrVec(v) => !v.is_empty(),
+        VariableType::IntVec(v) => !v.is_empty(),
+        VariableType::FloatVec(v) => !v.is_empty(),
+        VariableType::StrDic(d) => !d.is_empty(),
+        VariableType::IntDic(d) => !d.is_empty(),
+        VariableType::FloatDic(d) => !d.is_empty(),
+        VariableType::DateTime(_) => true,
+        VariableType::Duration(d) => *d != 0,
+    }
+}
+
+// 比较两个值，整数和浮点数可以混合比较
+fn order(a: &VariableType, b: &VariableType) -> Result<Ordering, &'static str> {
+    match (a, b) {
+        (VariableType::Int(a), VariableType::Int(b)) => Ok(a.cmp(b)),
+        (VariableType::Str(a), VariableType::Str(b)) => Ok(a.cmp(b)),
+        (VariableType::DateTime(a), VariableType::DateTime(b)) => Ok(a.cmp(b)),
+        (VariableType::Duration(a), VariableType::Duration(b)) => Ok(a.cmp(b)),
+        _ => match (a.to_float(), b.to_float()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).ok_or("Invalid number"),
+            _ => Err("Type mismatch"),
+        },
+    }
+}
+
+// 展开容器，数组的序号从 1 开始，字典按键排序
+fn items(value: &VariableType) -> Result<Vec<(VariableType, VariableType)>, &'static str> {
+    fn sorted<T: Clone>(
+        d: &std::collections::HashMap<String, T>,
+        f: fn(T) -> VariableType,
+    ) -> Vec<(VariableType, VariableType)> {
+        let mut items = d.iter().collect::<Vec<_>>();
+        items.sort_by(|a, b| a.0.cmp(b.0));
+        items
+            .into_iter()
+            .map(|(k, v)| (VariableType::Str(k.clone()), f(v.clone())))
+            .collect()
+    }
+    fn indexed<T: Clone>(v: &[T], f: fn(T) -> VariableType) -> Vec<(VariableType, VariableType)> {
+        v.iter()
+            .enumerate()
+            .map(|(i, x)| (VariableType::Int(i as i32 + 1), f(x.clone())))
+            .collect()
+    }
+    match value {
+        VariableType::StrVec(v) => Ok(indexed(v, VariableType::Str)),
+        VariableType::IntVec(v) => Ok(indexed(v, VariableType::Int)),
+        VariableType::FloatVec(v) => Ok(indexed(v, VariableType::Float)),
+        VariableType::StrDic(d) => Ok(sorted(d, VariableType::Str)),
+        VariableType::IntDic(d) => Ok(sorted(d, VariableType::Int)),
+        VariableType::FloatDic(d) => Ok(sorted(d, VariableType::Float)),
+        _ => Err("Type mismatch"),
+    }
+}
+
+// 容器元素的样例，用于检测循环体的类型
+fn sample(value: &VariableType) -> Result<(VariableType, VariableType), &'static str> {
+    let key = if value.is_dict() {
+        VariableType::Str(String::new())
+    } else {
+        VariableType::Int(1)
+    };
+    match value {
+        VariableType::StrVec(_) | VariableType::StrDic(_) => {
+            Ok((key, VariableType::Str(String::new())))
+        }
+        VariableType::IntVec(_) | VariableType::IntDic(_) => Ok((key, VariableType::Int(0))),
+        VariableType::FloatVec(_) | VariableType::FloatDic(_) => {
+            Ok((key, VariableType::Float(0.0)))
+        }
+        _ => Err("Type mismatch"),
+    }
+}
+
+/// 渲染时的作用域，循环变量会遮蔽同名的全局变量
+struct Scope<'a> {
+    args: &'a [String],
+    variables: &'a Variables,
+    locals: Vec<(String, VariableType)>,
+}
+
+impl Scope<'_> {
+    // 按名字查找变量
+    fn lookup(&self, name: &str) -> Option<&VariableType> {
+        self.locals
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v)
+            .or_else(|| self.variables.get(name))
+    }
+
+    // 表达式的名字（报错用）
+    fn name_of(&self, expr: &Expr) -> String {
+        match &expr.source {
+            Source::Positional(i) => self.args.get(*i).cloned().unwrap_or("{}".to_string()),
+            Source::Named(name) => name.clone(),
+        }
+    }
+
+    // 计算表达式
+    fn eval(&self, expr: &Expr) -> Result<VariableType, &'static str> {
+        let value = match &expr.source {
+            Source::Positional(i) => {
+                let name = self.args.get(*i).ok_or("Not enough variables")?;
+                self.lookup(name)
+            }
+            Source::Named(name) => self.lookup(name),
+        };
+        let mut value = value.ok_or("Variable not found")?.clone();
+        for filter in &expr.filters {
+            value = filter.apply(value)?;
+        }
+        Ok(value)
+    }
+
+    // 计算操作数
+    fn operand(&self, operand: &Operand) -> Result<VariableType, &'static str> {
+        match operand {
+            Operand::Expr(expr) => self.eval(expr),
+            Operand::Literal(value) => Ok(value.clone()),
+        }
+    }
+
+    // 计算条件原子
+    fn atom(&self, atom: &Atom) -> Result<bool, &'static str> {
+        let left = self.operand(&atom.left)?;
+        let result = match &atom.right {
+            None => truthy(&left),
+            Some((cmp, right)) => {
+                let ordering = order(&left, &self.operand(right)?)?;
+                match cmp {
+                    Cmp::Eq => ordering == Ordering::Equal,
+                    Cmp::Ne => ordering != Ordering::Equal,
+                    Cmp::Gt => ordering == Ordering::Greater,
+                    Cmp::Ge => ordering != Ordering::Less,
+                    Cmp::Lt => ordering == Ordering::Less,
+                    Cmp::Le => ordering != Ordering::Greater,
+                }
+            }
+        };
+        Ok(result != atom.negate)
+    }
+
+    // 计算条件
+    fn condition(&self, condition: &Condition) -> Result<bool, &'static str> {
+        for group in &condition.0 {
+            let mut result = true;
+            for atom in group {
+                if !self.atom(atom)? {
+                    result = false;
+                    break;
+                }
+            }
+            if result {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    // 渲染节点
+    fn render(&mut self, nodes: &[Node], out: &mut String) -> Result<(), &'static str> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Value(expr, spec) => out.push_str(&spec.apply(&self.eval(expr)?)),
+                Node::If(branches, otherwise) => {
+                    let mut body = otherwise;
+                    for (condition, branch) in branches {
+                        if self.condition(condition)? {
+                            body = branch;
+                            break;
+                        }
+                    }
+                    self.render(body, out)?;
+                }
+                Node::For(key, value, container, body) => {
+                    let container = self.lookup(container).ok_or("Variable not found")?;
+                    for (k, v) in items(container)? {
+                        let len = self.locals.len();
+                        if let Some(key) = key {
+                            self.locals.push((key.clone(), k));
+                        }
+                        self.locals.push((value.clone(), v));
+                        let result = self.render(body, out);
+                        self.locals.truncate(len);
+                        result?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // 检测节点，条件的每个部分和每个分支都会检测，循环变量使用样例值
+    fn check(&mut self, nodes: &[Node], errors: &mut Vec<String>) {
+        let operand_name = |scope: &Self, operand: &Operand| match operand {
+            Operand::Expr(expr) => scope.name_of(expr),
+            Operand::Literal(value) => value.to_string(),
+        };
+        for node in nodes {
+            match node {
+                Node::Text(_) => {}
+                Node::Value(expr, _) => {
+                    if let Err(err) = self.eval(expr) {
+                        errors.push(format!("{}: {}", err, self.name_of(expr)));
+                    }
+                }
+                Node::If(branches, otherwise) => {
+                    for (condition, branch) in branches {
+                        for atom in condition.0.iter().flatten() {
+                            if let Err(err) = self.atom(atom) {
+                                errors.push(format!("{}: {}", err, operand_name(self, &atom.left)));
+                            }
+                        }
+                        self.check(branch, errors);
+                    }
+                    self.check(otherwise, errors);
+                }
+                Node::For(key, value, container, body) => {
+                    let sample = self
+                        .lookup(container)
+                        .ok_or("Variable not found")
+                        .and_then(sample);
+                    match sample {
+                        Ok((k, v)) => {
+                            let len = self.locals.len();
+                            if let Some(key) = key {
+                                self.locals.push((key.clone(), k));
+                            }
+                            self.locals.push((value.clone(), v));
+                            self.check(body, errors);
+                            self.locals.truncate(len);
+                        }
+                        Err(err) => errors.push(format!("{}: {}", err, container)),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Template {
+    /// 编译模板
+    pub fn compile(source: &str) -> Result<Template, String> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?.into_iter(),
+            positional: 0,
+        };
+        match parser.parse_block()? {
+            (nodes, None) => Ok(Template {
+                nodes,
+                positional: parser.positional,
+            }),
+            (_, Some(tag)) => Err(format!("Unexpected tag: {}", tag)),
+        }
+    }
+
+    /// 位置占位符的个数
+    pub fn positional(&self) -> usize {
+        self.positional
+    }
+
+    /// 渲染模板，位置占位符按顺序使用变量数组
+    pub fn render(&self, args: &[String], variables: &Variables) -> Result<String, &'static str> {
+        let mut scope = Scope {
+            args,
+            variables,
+            locals: Vec::new(),
+        };
+        let mut out = String::new();
+        scope.render(&self.nodes, &mut out)?;
+        Ok(out)
+    }
+
+    /// 检测模板中的变量、过滤器和条件（检测用，会报告所有错误）
+    pub fn check(&self, args: &[String], variables: &Variables) -> Vec<String> {
+        let mut scope = Scope {
+            args,
+            variables,
+            locals: Vec::new(),
+        };
+        let mut errors = Vec::new();
+        scope.check(&self.nodes, &mut errors);
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn create_test_variables() -> Variables {
+        let mut variables = Variables::new();
+        variables.insert("name".to_string(), VariableType::Str("sakiko".to_string()));
+        variables.insert("vip".to_string(), VariableType::Int(1));
+        variables.insert("total".to_string(), VariableType::Float(12.5));
+        variables.insert(
+            "items".to_string(),
+            VariableType::StrVec(vec!["apple".to_string(), "pear".to_string()]),
+        );
+        variables.insert(
+            "cart".to_string(),
+            VariableType::IntDic(HashMap::from([
+                ("pear".to_string(), 3),
+                ("apple".to_string(), 2),
+            ])),
+        );
+        variables
+    }
+
+    // 测试条件、循环和过滤器
+    #[test]
+    fn test_render() {
+        let variables = create_test_variables();
+        let render = |source: &str| Template::compile(source).unwrap().render(&[], &variables);
+
+        assert_eq!(
+            render("{name|upper} {items|join(\" / \")} {items|len}").unwrap(),
+            "SAKIKO apple / pear 2"
+        );
+        assert_eq!(
+            render("{% if vip %}VIP {% end %}{name}").unwrap(),
+            "VIP sakiko"
+        );
+        assert_eq!(
+            render("{% if total > 20 %}big{% elif total >= 12 and not vip %}mid{% else %}small{% end %}").unwrap(),
+            "small"
+        );
+        assert_eq!(
+            render("{% if name|len == 6 or missing %}yes{% end %}").unwrap(),
+            "yes"
+        );
+        assert_eq!(
+            render("{% for k, v in cart %}\n{k}: {v}\n{% end %}\n").unwrap(),
+            "apple: 2\npear: 3\n"
+        );
+        assert_eq!(
+            render("{% for i, item in items %}{i}.{item} {% end %}").unwrap(),
+            "1.apple 2.pear "
+        );
+
+        // 运行时错误
+        assert_eq!(render("{% for x in name %}{% end %}"), Err("Type mismatch"));
+        assert_eq!(render("{vip|upper}"), Err("Filter not applicable"));
+    }
+
+    // 测试编译和检测
+    #[test]
+    fn test_compile_and_check() {
+        let variables = create_test_variables();
+
+        assert!(Template::compile("{% if vip %}").is_err());
+        assert!(Template::compile("{% for x of items %}{% end %}").is_err());
+        assert!(Template::compile("{% else %}").is_err());
+        assert!(Template::compile("{% while vip %}{% end %}").is_err());
+        assert!(Template::compile("{name|reverse}").is_err());
+        assert!(Template::compile("{% if vip ~ 1 %}{% end %}").is_err());
+        assert!(Template::compile("{% if vip %}{% end if %}").is_err());
+        assert!(Template::compile("{% if vip %}{% else vip %}{% end %}").is_err());
+        assert!(Template::compile("{% for x in items %}{% end foo %}").is_err());
+        assert_eq!(
+            Template::compile("{} {% if vip %}{}{% else %}{}{% end %}")
+                .unwrap()
+                .positional(),
+            3
+        );
+
+        let check = |source: &str| Template::compile(source).unwrap().check(&[], &variables);
+        assert!(check("{% for k, v in cart %}{k|upper}{v:>3}{% end %}").is_empty());
+        assert_eq!(
+            check("{% for x in items %}{x|len}{% end %}{x}"),
+            vec!["Variable not found: x"]
+        );
+        assert_eq!(
+            check("{% if 1 > \"a\" %}{% else %}{total|join}{% end %}").len(),
+            2
+        );
+        assert_eq!(
+            check("{% for x in vip %}{x}{% end %}"),
+            vec!["Type mismatch: vip"]
+        );
+    }
+}
@@ -0,0 +1,464 @@
+/// Sakiko 的日期时间
+/// 日期时间以本地时间（按配置中的时区偏移）距 1970-01-01 00:00:00 的秒数保存，时长以秒数保存
+/// 提供可替换的时钟，以及由每周营业时间和节假日组成的营业日历
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::ops::Not;
+
+/// 日期时间
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct DateTime(pub i64);
+
+/// 星期
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Weekday {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+/// 时间单位
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum TimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+}
+
+/// 日期时间的组成部分
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum DatePart {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+    Weekday, // 1 为周一，7 为周日
+}
+
+/// 时钟，返回当前 UTC 时间距 1970-01-01 00:00:00 的秒数
+pub trait Clock: std::fmt::Debug + Send + Sync {
+    fn now(&self) -> i64;
+}
+
+/// 系统时钟
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock;
+
+/// 固定时钟（测试用）
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub i64);
+
+/// 营业日历
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Default)]
+pub struct Calendar {
+    #[serde(default = "String::new")]
+    pub utc_offset: String, // 时区偏移，如 +08:00（可置空，默认为 UTC）
+    #[serde(default = "HashMap::new")]
+    pub opening_hours: HashMap<Weekday, Vec<String>>, // 每周营业时间，如 09:00-18:00（可置空，默认全天营业）
+    #[serde(default = "Vec::new")]
+    pub holidays: Vec<String>, // 节假日，如 2024-10-01（可置空）
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0)
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> i64 {
+        self.0
+    }
+}
+
+// 公历日期转换为距 1970-01-01 的天数
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// 距 1970-01-01 的天数转换为公历日期
+fn civil_from_days(z: i64) -> (i64, i64, i64) {
+    let z = z + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    (
+        if m <= 2 {
+            yoe + era * 400 + 1
+        } else {
+            yoe + era * 400
+        },
+        m,
+        d,
+    )
+}
+
+// 某月的天数
+fn days_in_month(y: i64, m: i64) -> i64 {
+    days_from_civil(
+        if m == 12 { y + 1 } else { y },
+        if m == 12 { 1 } else { m + 1 },
+        1,
+    ) - days_from_civil(y, m, 1)
+}
+
+// 解析 HH:MM[:SS]，返回当天的秒数，允许 24:00
+fn parse_clock(s: &str) -> Option<i64> {
+    let parts = s.trim().split(':').collect::<Vec<&str>>();
+    if parts.len() < 2 || parts.len() > 3 {
+        return None;
+    }
+    let h = parts[0].parse::<i64>().ok()?;
+    let m = parts[1].parse::<i64>().ok()?;
+    let s = parts.get(2).map_or(Some(0), |s| s.parse::<i64>().ok())?;
+    if (0..60).contains(&m).not() || (0..60).contains(&s).not() {
+        return None;
+    }
+    match h {
+        0..=23 => Some(h * 3600 + m * 60 + s),
+        24 if m == 0 && s == 0 => Some(86400),
+        _ => None,
+    }
+}
+
+// 解析 YYYY-MM-DD，返回距 1970-01-01 的天数
+fn parse_date(s: &str) -> Option<i64> {
+    let parts = s.trim().split('-').collect::<Vec<&str>>();
+    if parts.len() != 3 {
+        return None;
+    }
+    let y = parts[0].parse::<i64>().ok()?;
+    let m = parts[1].parse::<i64>().ok()?;
+    let d = parts[2].parse::<i64>().ok()?;
+    if (1..=12).contains(&m).not() || d < 1 || d > days_in_month(y, m) {
+        return None;
+    }
+    Some(days_from_civil(y, m, d))
+}
+
+// 解析 HH:MM-HH:MM，返回当天的开始和结束秒数
+fn parse_range(s: &str) -> Option<(i64, i64)> {
+    let (l, r) = s.split_once('-')?;
+    let l = parse_clock(l)?;
+    let r = parse_clock(r)?;
+    if l < r {
+        Some((l, r))
+    } else {
+        None
+    }
+}
+
+impl DateTime {
+    /// 由日期和时间创建
+    pub fn from_parts(y: i64, m: i64, d: i64, h: i64, min: i64, s: i64) -> DateTime {
+        DateTime(days_from_civil(y, m, d) * 86400 + h * 3600 + min * 60 + s)
+    }
+
+    /// 解析 YYYY-MM-DD、YYYY-MM-DD HH:MM 或 YYYY-MM-DD HH:MM:SS
+    pub fn parse(s: &str) -> Option<DateTime> {
+        let s = s.trim();
+        let (date, time) = match s.split_once([' ', 'T']) {
+            Some((date, time)) => (date, Some(time)),
+            None => (s, None),
+        };
+        let days = parse_date(date)?;
+        let seconds = match time {
+            Some(time) => parse_clock(time).filter(|s| *s < 86400)?,
+            None => 0,
+        };
+        Some(DateTime(days * 86400 + seconds))
+    }
+
+    /// 距 1970-01-01 的天数
+    pub fn days(&self) -> i64 {
+        self.0.div_euclid(86400)
+    }
+
+    /// 当天的秒数
+    pub fn seconds_of_day(&self) -> i64 {
+        self.0.rem_euclid(86400)
+    }
+
+    /// 星期
+    pub fn weekday(&self) -> Weekday {
+        match (self.days() + 3).rem_euclid(7) {
+            0 => Weekday::Mon,
+            1 => Weekday::Tue,
+            2 => Weekday::Wed,
+            3 => Weekday::Thu,
+            4 => Weekday::Fri,
+            5 => Weekday::Sat,
+            _ => Weekday::Sun,
+        }
+    }
+
+    /// 获取组成部分
+    pub fn part(&self, part: DatePart) -> i64 {
+        let (y, m, d) = civil_from_days(self.days());
+        let s = self.seconds_of_day();
+        match part {
+            DatePart::Year => y,
+            DatePart::Month => m,
+            DatePart::Day => d,
+            DatePart::Hour => s / 3600,
+            DatePart::Minute => s % 3600 / 60,
+            DatePart::Second => s % 60,
+            DatePart::Weekday => (self.days() + 3).rem_euclid(7) + 1,
+        }
+    }
+
+    /// 按格式输出，支持 %Y %m %d %H %M %S %u（星期 1-7）%a（周一至周日）%%
+    pub fn format(&self, pattern: &str) -> String {
+        let mut result = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                result.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => result.push_str(&format!("{:04}", self.part(DatePart::Year))),
+                Some('m') => result.push_str(&format!("{:02}", self.part(DatePart::Month))),
+                Some('d') => result.push_str(&format!("{:02}", self.part(DatePart::Day))),
+                Some('H') => result.push_str(&format!("{:02}", self.part(DatePart::Hour))),
+                Some('M') => result.push_str(&format!("{:02}", self.part(DatePart::Minute))),
+                Some('S') => result.push_str(&format!("{:02}", self.part(DatePart::Second))),
+                Some('u') => result.push_str(&self.part(DatePart::Weekday).to_string()),
+                Some('a') => result.push_str(self.weekday().name()),
+                Some('%') => result.push('%'),
+                Some(other) => {
+                    result.push('%');
+                    result.push(other);
+                }
+                None => result.push('%'),
+            }
+        }
+        result
+    }
+}
+
+impl std::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.format("%Y-%m-%d %H:%M:%S"))
+    }
+}
+
+/// 以字符串形式序列化
+impl Serialize for DateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for DateTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        DateTime::parse(&s)
+            .ok_or_else(|| ::serde::de::Error::custom(format!("Invalid date time: {}", s)))
+    }
+}
+
+impl Weekday {
+    /// 中文名
+    pub fn name(&self) -> &'static str {
+        match self {
+            Weekday::Mon => "周一",
+            Weekday::Tue => "周二",
+            Weekday::Wed => "周三",
+            Weekday::Thu => "周四",
+            Weekday::Fri => "周五",
+            Weekday::Sat => "周六",
+            Weekday::Sun => "周日",
+        }
+    }
+}
+
+impl TimeUnit {
+    /// 单位对应的秒数
+    pub fn seconds(&self) -> i64 {
+        match self {
+            TimeUnit::Second => 1,
+            TimeUnit::Minute => 60,
+            TimeUnit::Hour => 3600,
+            TimeUnit::Day => 86400,
+        }
+    }
+}
+
+/// 时长格式化为 [Nd ]HH:MM:SS
+pub fn format_duration(seconds: i64) -> String {
+    let sign = if seconds < 0 { "-" } else { "" };
+    let s = seconds.unsigned_abs();
+    let (d, h, m, s) = (s / 86400, s % 86400 / 3600, s % 3600 / 60, s % 60);
+    if d > 0 {
+        format!("{}{}d {:02}:{:02}:{:02}", sign, d, h, m, s)
+    } else {
+        format!("{}{:02}:{:02}:{:02}", sign, h, m, s)
+    }
+}
+
+impl Calendar {
+    /// 是否为默认值（序列化时省略）
+    pub fn is_empty(&self) -> bool {
+        *self == Calendar::default()
+    }
+
+    /// 时区偏移的秒数
+    pub fn offset_seconds(&self) -> Result<i64, String> {
+        let s = self.utc_offset.trim();
+        if s.is_empty() {
+            return Ok(0);
+        }
+        let (sign, rest) = match s.split_at(1) {
+            ("+", rest) => (1, rest),
+            ("-", rest) => (-1, rest),
+            _ => (1, s),
+        };
+        parse_clock(rest)
+            .filter(|s| *s < 86400)
+            .map(|s| sign * s)
+            .ok_or_else(|| format!("Invalid utc offset {}", self.utc_offset))
+    }
+
+    /// 将时钟时间转换为本地日期时间
+    pub fn local(&self, clock: &dyn Clock) -> Result<DateTime, String> {
+        Ok(DateTime(clock.now() + self.offset_seconds()?))
+    }
+
+    // 某天的营业时间段，节假日为空
+    fn ranges_of_day(&self, days: i64) -> Result<Vec<(i64, i64)>, String> {
+        for holiday in &self.holidays {
+            if parse_date(holiday).ok_or_else(|| format!("Invalid holiday {}", holiday))? == days {
+                return Ok(Vec::new());
+            }
+        }
+        if self.opening_hours.is_empty() {
+            return Ok(vec![(0, 86400)]);
+        }
+        let weekday = DateTime(days * 86400).weekday();
+        let mut ranges = Vec::new();
+        for range in self.opening_hours.get(&weekday).into_iter().flatten() {
+            ranges.push(
+                parse_range(range).ok_or_else(|| format!("Invalid opening hours {}", range))?,
+            );
+        }
+        ranges.sort();
+        Ok(ranges)
+    }
+
+    /// 是否处于营业时间
+    pub fn is_open(&self, time: DateTime) -> Result<bool, String> {
+        let s = time.seconds_of_day();
+        Ok(self
+            .ranges_of_day(time.days())?
+            .iter()
+            .any(|(l, r)| *l <= s && s < *r))
+    }
+
+    /// 下一次营业的时间，正在营业时返回传入的时间，一年内没有营业时间时返回错误
+    pub fn next_open(&self, time: DateTime) -> Result<DateTime, String> {
+        for offset in 0..=366 {
+            let days = time.days() + offset;
+            for (l, r) in self.ranges_of_day(days)? {
+                let start = DateTime(days * 86400 + l);
+                let end = DateTime(days * 86400 + r);
+                if time < end {
+                    return Ok(start.max(time));
+                }
+            }
+        }
+        Err("No opening hours".to_string())
+    }
+
+    /// 检查日历（检测用，会报告所有错误）
+    pub fn check(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        if let Err(err) = self.offset_seconds() {
+            errors.push(err);
+        }
+        for ranges in self.opening_hours.values() {
+            for range in ranges {
+                if parse_range(range).is_none() {
+                    errors.push(format!("Invalid opening hours {}", range));
+                }
+            }
+        }
+        for holiday in &self.holidays {
+            if parse_date(holiday).is_none() {
+                errors.push(format!("Invalid holiday {}", holiday));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试日期转换与格式化
+    #[test]
+    fn test_date_time() {
+        let time = DateTime::parse("2024-02-29 13:05:09").unwrap();
+        assert_eq!(time, DateTime::from_parts(2024, 2, 29, 13, 5, 9));
+        assert_eq!(time.weekday(), Weekday::Thu);
+        assert_eq!(time.part(DatePart::Weekday), 4);
+        assert_eq!(time.format("%m/%d %H:%M %a"), "02/29 13:05 周四");
+        assert_eq!(time.to_string(), "2024-02-29 13:05:09");
+        assert!(DateTime::parse("2023-02-29").is_none());
+        assert_eq!(DateTime::parse("1970-01-01"), Some(DateTime(0)));
+        assert_eq!(format_duration(90061), "1d 01:01:01");
+        assert_eq!(format_duration(-90), "-00:01:30");
+    }
+
+    // 测试营业日历
+    #[test]
+    fn test_calendar() {
+        let calendar: Calendar = serde_yaml::from_str(
+            "utc_offset: +08:00\nopening_hours:\n  Mon: [09:00-12:00, 13:00-18:00]\n  Tue: [09:00-18:00]\nholidays: [2024-10-01]\n",
+        )
+        .unwrap();
+        assert_eq!(calendar.check(), Ok(()));
+        assert_eq!(calendar.offset_seconds(), Ok(8 * 3600));
+        // 2024-09-30 为周一
+        let lunch = DateTime::parse("2024-09-30 12:30").unwrap();
+        assert_eq!(calendar.is_open(lunch), Ok(false));
+        assert_eq!(
+            calendar.next_open(lunch),
+            Ok(DateTime::parse("2024-09-30 13:00").unwrap())
+        );
+        // 周一晚上跳过周二的节假日，到下周一
+        let night = DateTime::parse("2024-09-30 20:00").unwrap();
+        assert_eq!(
+            calendar.next_open(night),
+            Ok(DateTime::parse("2024-10-07 09:00").unwrap())
+        );
+        assert_eq!(
+            calendar.local(&FixedClock(0)),
+            Ok(DateTime::parse("1970-01-01 08:00").unwrap())
+        );
+    }
+}
@@ -3,6 +3,8 @@
 use ::serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::time::{format_duration, DateTime};
+
 /// 变量类型
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub enum VariableType {
@@ -15,6 +17,8 @@ pub enum VariableType {
     StrDic(HashMap<String, String>),
     IntDic(HashMap<String, i32>),
     FloatDic(HashMap<String, f64>),
+    DateTime(DateTime),
+    Duration(i64), // 秒数
 }
 
 /// 变量表
@@ -76,6 +80,8 @@ impl VariableType {
                 | (VariableType::StrDic(_), VariableType::StrDic(_))
                 | (VariableType::IntDic(_), VariableType::IntDic(_))
                 | (VariableType::FloatDic(_), VariableType::FloatDic(_))
+                | (VariableType::DateTime(_), VariableType::DateTime(_))
+                | (VariableType::Duration(_), VariableType::Duration(_))
         )
     }
 
@@ -84,6 +90,20 @@ impl VariableType {
         matches!(self, VariableType::Int(_) | VariableType::Float(_))
     }
 
+    // 转换为浮点数（仅数字）
+    pub fn to_float(&self) -> Option<f64> {
+        match self {
+            VariableType::Int(i) => Some(*i as f64),
+            VariableType::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    // 检查是否是日期时间或时长
+    pub fn is_time(&self) -> bool {
+        matches!(self, VariableType::DateTime(_) | VariableType::Duration(_))
+    }
+
     // 检查是否是数组
     pub fn is_vector(&self) -> bool {
         matches!(
@@ -99,10 +119,12 @@ impl VariableType {
             VariableType::StrDic(_) | VariableType::IntDic(_) | VariableType::FloatDic(_)
         )
     }
+}
 
-    // 转换为字符串
-    pub fn to_string(&self) -> String {
-        match self {
+/// 转换为字符串
+impl std::fmt::Display for VariableType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
             VariableType::Str(s) => s.clone(),
             VariableType::Int(i) => i.to_string(),
             VariableType::Float(f) => format!("{:.3}", f),
@@ -138,7 +160,10 @@ impl VariableType {
                 }
                 s
             }
-        }
+            VariableType::DateTime(t) => t.to_string(),
+            VariableType::Duration(d) => format_duration(*d),
+        };
+        f.write_str(&s)
     }
 }
 